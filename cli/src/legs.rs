@@ -0,0 +1,96 @@
+use std::fmt::Write;
+
+use definition::Plan;
+use kneeboard_core::planner::route_name;
+use kneeboard_core::route::{calc_legs, convert_leg, Leg};
+use kneeboard_core::upper_winds::plan_upper_winds;
+
+const HEADINGS: [&str; 11] = [
+    "From", "To", "Trk", "Dist", "TAS", "Wind", "G/S", "HDG(T)", "HDG(M)", "Time", "Total",
+];
+
+/// Formats the outbound legs of every route as plain text columns, one table per route.
+pub fn legs_table(plan: &Plan) -> String {
+    let mut out = String::new();
+
+    let winds = plan_upper_winds(plan);
+    for (idx, route) in plan.routes.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let name = route_name(route, idx);
+        let _ = writeln!(out, "{name}");
+
+        let legs: Vec<Leg> = route.legs.iter().map(convert_leg).collect();
+        let mut rows = vec![HEADINGS.map(str::to_owned).to_vec()];
+        for (leg, calc) in calc_legs(&legs, winds.as_ref()) {
+            let (from, to) = &leg.name;
+            rows.push(vec![
+                from.clone(),
+                to.clone(),
+                leg.course.as_heading(),
+                format!("{}", leg.distance),
+                format!("{}", leg.speed),
+                format!("{}/{}", calc.wind_direction.as_heading(), calc.wind_speed),
+                format!("{:.0}", calc.ground_speed),
+                calc.heading.as_heading(),
+                calc.heading_magnetic.as_heading(),
+                format!("{:.0}", calc.time),
+                format!("{:.0}", calc.total),
+            ]);
+        }
+        write_rows(&mut out, &rows);
+    }
+
+    out
+}
+
+/// Writes `rows` with each column padded to its widest cell. Names are left aligned and
+/// numbers right aligned.
+fn write_rows(out: &mut String, rows: &[Vec<String>]) {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| match column {
+                0 | 1 => format!("{cell:<width$}"),
+                _ => format!("{cell:>width$}"),
+            })
+            .collect();
+        let _ = writeln!(out, "{}", cells.join("  ").trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::create_template_plan;
+
+    use super::legs_table;
+
+    #[test]
+    fn columns_line_up() {
+        let plan = create_template_plan();
+        let table = legs_table(&plan);
+
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[1].starts_with("From"));
+        assert!(lines[1].ends_with("Total"));
+
+        // Every row ends under the right hand heading
+        let width = lines[1].len();
+        for line in &lines[2..=plan.routes[0].legs.len() + 1] {
+            assert_eq!(line.len(), width, "{line}");
+        }
+    }
+}
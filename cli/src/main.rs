@@ -0,0 +1,219 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use common::migration::{read_plan, to_file_value, FileType};
+use common::schema::schema;
+use common::validation::{has_errors, validate_plan, Severity};
+use common::{create_template_plan, KneeboardError};
+use definition::Plan;
+use kneeboard_core::planner::{build_planning, create_planning};
+use pdf::PdfDate;
+
+mod legs;
+
+#[derive(Parser)]
+#[command(
+    name = "kneeboard",
+    version,
+    about = "Create A5 kneeboard notes from plan files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render a plan to a kneeboard PDF
+    Render {
+        /// Plan JSON file, as saved from the web app
+        plan: PathBuf,
+        /// Where to write the PDF, defaults to the plan path with a .pdf extension
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a starter plan to build on
+    Template {
+        /// Write the plan to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check that a plan file can be loaded, listing any problems with its fields
+    Validate { plan: PathBuf },
+    /// Print the computed legs of each route
+    Legs { plan: PathBuf },
+    /// Print the JSON Schema for plan or profile files
+    Schema {
+        #[arg(value_enum)]
+        kind: SchemaKind,
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SchemaKind {
+    Plan,
+    Profile,
+}
+
+/// Exit status for each kind of `KneeboardError`. Argument errors exit with 2, from clap.
+fn exit_code(err: &KneeboardError) -> u8 {
+    match err {
+        KneeboardError::String(_) => 1,
+        KneeboardError::Json(_) => 3,
+        KneeboardError::File(_) => 4,
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Render { plan, output } => render(&plan, output),
+        Command::Template { output } => template(output),
+        Command::Validate { plan } => validate(&plan),
+        Command::Legs { plan } => print_legs(&plan),
+        Command::Schema { kind, output } => print_schema(kind, output),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("kneeboard: {}", err.to_err_string());
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+fn render(path: &Path, output: Option<PathBuf>) -> Result<(), KneeboardError> {
+    let plan = load_plan(path)?;
+
+    let mut doc = create_planning(&plan);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    doc.info_mut().creation_date = Some(PdfDate::from_unix_seconds(now));
+
+    let mut data = vec![];
+    doc.write(&mut data)?;
+
+    let output = output.unwrap_or_else(|| path.with_extension("pdf"));
+    fs::write(&output, data)?;
+    println!("Wrote {}", output.display());
+    Ok(())
+}
+
+fn template(output: Option<PathBuf>) -> Result<(), KneeboardError> {
+    let json = plan_json(&create_template_plan())?;
+    match output {
+        Some(output) => fs::write(output, json)?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+fn validate(path: &Path) -> Result<(), KneeboardError> {
+    let plan = load_plan(path)?;
+    let pages = build_planning(&plan).page_count();
+
+    let legs: usize = plan.routes.iter().map(|route| route.legs.len()).sum();
+    println!(
+        "{}: {} routes, {legs} legs, {pages} pages",
+        path.display(),
+        plan.routes.len()
+    );
+
+    let diagnostics = validate_plan(&plan);
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+
+    if has_errors(&diagnostics) {
+        let errors = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        let noun = if errors == 1 { "error" } else { "errors" };
+        return Err(KneeboardError::String(format!(
+            "{} has {errors} {noun}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+fn print_legs(path: &Path) -> Result<(), KneeboardError> {
+    let plan = load_plan(path)?;
+    print!("{}", legs::legs_table(&plan));
+    Ok(())
+}
+
+fn print_schema(kind: SchemaKind, output: Option<PathBuf>) -> Result<(), KneeboardError> {
+    let file_type = match kind {
+        SchemaKind::Plan => FileType::Plan,
+        SchemaKind::Profile => FileType::Profile,
+    };
+    let json = serde_json::to_string_pretty(&schema(file_type))?;
+    match output {
+        Some(output) => fs::write(output, json + "\n")?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+/// Reads a plan of any format version, rejecting other files saved by the web app such as
+/// profiles.
+fn load_plan(path: &Path) -> Result<Plan, KneeboardError> {
+    let data = fs::read(path).map_err(|err| {
+        let msg = format!("{}: {err}", path.display());
+        KneeboardError::File(io::Error::new(err.kind(), msg))
+    })?;
+
+    read_plan(&data).map_err(|err| match err {
+        KneeboardError::String(msg) => KneeboardError::String(format!("{}: {msg}", path.display())),
+        err => err,
+    })
+}
+
+/// Pretty JSON tagged as a plan, matching the files exported by the web app.
+fn plan_json(plan: &Plan) -> Result<String, KneeboardError> {
+    let value = to_file_value(plan, FileType::Plan)?;
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use common::{create_template_plan, KneeboardError};
+
+    use super::{exit_code, plan_json};
+
+    #[test]
+    fn template_round_trips() {
+        let json = plan_json(&create_template_plan()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["file_type"], "plan");
+        assert_eq!(value["version"], definition::PLAN_VERSION);
+        assert!(serde_json::from_value::<definition::Plan>(value).is_ok());
+    }
+
+    #[test]
+    fn exit_codes_differ_by_kind() {
+        let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let codes = [
+            exit_code(&KneeboardError::String("invalid".to_owned())),
+            exit_code(&KneeboardError::Json(json)),
+            exit_code(&KneeboardError::File(io::Error::other("missing"))),
+        ];
+
+        assert_eq!(codes, [1, 3, 4]);
+    }
+}
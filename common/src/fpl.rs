@@ -0,0 +1,190 @@
+use definition::Coordinate;
+use roxmltree::{Document, Node};
+
+use crate::import::{child_text, children, report_unknown, Imported, ImportedRoute, Waypoint};
+use crate::KneeboardError;
+
+/// Elements read, or known not to matter to the route, under each element of the file.
+const FLIGHT_PLAN_ELEMENTS: [&str; 5] = [
+    "created",
+    "file-description",
+    "author",
+    "waypoint-table",
+    "route",
+];
+const WAYPOINT_ELEMENTS: [&str; 7] = [
+    "identifier",
+    "type",
+    "country-code",
+    "lat",
+    "lon",
+    "comment",
+    "elevation",
+];
+const ROUTE_ELEMENTS: [&str; 4] = [
+    "route-name",
+    "route-description",
+    "flight-plan-index",
+    "route-point",
+];
+const ROUTE_POINT_ELEMENTS: [&str; 3] = [
+    "waypoint-identifier",
+    "waypoint-type",
+    "waypoint-country-code",
+];
+
+/// An entry of the waypoint table, which route points refer to by identifier, type and
+/// country.
+struct TableEntry {
+    identifier: String,
+    kind: String,
+    country: String,
+    position: Coordinate,
+}
+
+/// Reads a Garmin `.fpl` flight plan, as saved by Garmin Pilot and the Garmin avionics
+/// trainers. The format holds no altitudes, speeds or winds.
+pub fn parse_fpl(text: &str) -> Result<Imported, KneeboardError> {
+    let doc = Document::parse(text)
+        .map_err(|err| KneeboardError::String(format!("Not a Garmin flight plan: {err}")))?;
+    let plan = doc.root_element();
+    if plan.tag_name().name() != "flight-plan" {
+        return Err(KneeboardError::String(format!(
+            "Not a Garmin flight plan, found <{}>",
+            plan.tag_name().name()
+        )));
+    }
+
+    let mut imported = Imported::default();
+    report_unknown(plan, &FLIGHT_PLAN_ELEMENTS, &mut imported);
+
+    let mut table = vec![];
+    for waypoint_table in children(plan, "waypoint-table") {
+        for waypoint in children(waypoint_table, "waypoint") {
+            report_unknown(waypoint, &WAYPOINT_ELEMENTS, &mut imported);
+            table.push(table_entry(waypoint)?);
+        }
+    }
+
+    for route in children(plan, "route") {
+        report_unknown(route, &ROUTE_ELEMENTS, &mut imported);
+
+        let mut waypoints = vec![];
+        for point in children(route, "route-point") {
+            report_unknown(point, &ROUTE_POINT_ELEMENTS, &mut imported);
+
+            let identifier = child_text(point, "waypoint-identifier").unwrap_or_default();
+            let kind = child_text(point, "waypoint-type").unwrap_or_default();
+            let country = child_text(point, "waypoint-country-code").unwrap_or_default();
+            let entry = table
+                .iter()
+                .find(|entry| {
+                    entry.identifier == identifier && entry.kind == kind && entry.country == country
+                })
+                .ok_or_else(|| {
+                    KneeboardError::String(format!(
+                        "Route point {identifier} isn't in the waypoint table"
+                    ))
+                })?;
+            waypoints.push(Waypoint::new(identifier, entry.position));
+        }
+
+        imported.routes.push(ImportedRoute {
+            name: child_text(route, "route-name").unwrap_or_default(),
+            waypoints,
+        });
+    }
+
+    imported.routes.retain(|route| route.waypoints.len() >= 2);
+    if imported.routes.is_empty() {
+        return Err(KneeboardError::String(
+            "The flight plan has no route with at least two points".to_owned(),
+        ));
+    }
+    Ok(imported)
+}
+
+fn table_entry(waypoint: Node) -> Result<TableEntry, KneeboardError> {
+    let identifier = child_text(waypoint, "identifier").unwrap_or_default();
+    let coordinate = |name: &str| child_text(waypoint, name).and_then(|text| text.parse().ok());
+
+    let position = match (coordinate("lat"), coordinate("lon")) {
+        (Some(lat), Some(lon)) => Coordinate::new(lat, lon),
+        _ => {
+            return Err(KneeboardError::String(format!(
+                "Waypoint {identifier} has no valid lat and lon"
+            )))
+        }
+    };
+
+    Ok(TableEntry {
+        kind: child_text(waypoint, "type").unwrap_or_default(),
+        country: child_text(waypoint, "country-code").unwrap_or_default(),
+        identifier,
+        position,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_fpl;
+
+    const PLAN: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <flight-plan xmlns="http://www8.garmin.com/xmlschemas/FlightPlan/v1">
+          <created>2024-05-01T10:00:00Z</created>
+          <waypoint-table>
+            <waypoint>
+              <identifier>EGLF</identifier>
+              <type>AIRPORT</type>
+              <country-code>EG</country-code>
+              <lat>51.2758</lat>
+              <lon>-0.7763</lon>
+              <comment />
+            </waypoint>
+            <waypoint>
+              <identifier>OCK</identifier>
+              <type>VOR</type>
+              <country-code>EG</country-code>
+              <lat>51.3050</lat>
+              <lon>-0.4473</lon>
+              <comment />
+              <magvar>1</magvar>
+            </waypoint>
+          </waypoint-table>
+          <route>
+            <route-name>EGLF TO OCK</route-name>
+            <flight-plan-index>1</flight-plan-index>
+            <route-point>
+              <waypoint-identifier>EGLF</waypoint-identifier>
+              <waypoint-type>AIRPORT</waypoint-type>
+              <waypoint-country-code>EG</waypoint-country-code>
+            </route-point>
+            <route-point>
+              <waypoint-identifier>OCK</waypoint-identifier>
+              <waypoint-type>VOR</waypoint-type>
+              <waypoint-country-code>EG</waypoint-country-code>
+            </route-point>
+          </route>
+        </flight-plan>"#;
+
+    #[test]
+    fn route_points_come_from_the_table() {
+        let imported = parse_fpl(PLAN).unwrap();
+
+        let route = &imported.routes[0];
+        assert_eq!(route.name, "EGLF TO OCK");
+        assert_eq!(route.waypoints[1].name, "OCK");
+        assert_eq!(route.waypoints[1].position.longitude, -0.4473);
+        assert_eq!(imported.unsupported, ["<magvar> in <waypoint>"]);
+    }
+
+    #[test]
+    fn missing_waypoints_are_an_error() {
+        let plan = PLAN.replace(
+            "<identifier>OCK</identifier>",
+            "<identifier>BIG</identifier>",
+        );
+
+        assert!(parse_fpl(&plan).is_err());
+    }
+}
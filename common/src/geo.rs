@@ -0,0 +1,155 @@
+use definition::Coordinate;
+
+/// Mean radius of the earth in nautical miles.
+const EARTH_RADIUS_NM: f64 = 3440.065;
+
+/// Great circle navigation and ICAO notation for coordinates.
+pub trait Geo: Sized {
+    /// Whether the latitude and longitude are within their ranges.
+    fn is_valid(&self) -> bool;
+
+    /// Great circle distance to `other` in nautical miles.
+    fn distance_to(&self, other: &Self) -> f64;
+
+    /// True course at the start of the great circle to `other`, from 0 up to 360 degrees.
+    fn course_to(&self, other: &Self) -> f64;
+
+    /// The point halfway along the great circle to `other`.
+    fn midpoint(&self, other: &Self) -> Self;
+
+    /// The position as whole degrees and minutes in the style of ICAO flight plans, such
+    /// as `5117N00047W`.
+    fn to_icao(&self) -> String;
+
+    /// Reads ICAO coordinates in whole degrees, such as `51N001W`, or degrees and minutes,
+    /// such as `5117N00047W`.
+    fn from_icao(text: &str) -> Option<Self>;
+}
+
+impl Geo for Coordinate {
+    fn is_valid(&self) -> bool {
+        (-90.0..=90.).contains(&self.latitude) && (-180.0..=180.).contains(&self.longitude)
+    }
+
+    fn distance_to(&self, other: &Coordinate) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.).sin().powi(2);
+        2. * EARTH_RADIUS_NM * a.sqrt().asin()
+    }
+
+    fn course_to(&self, other: &Coordinate) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let y = d_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.)
+    }
+
+    fn midpoint(&self, other: &Coordinate) -> Coordinate {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let lon1 = self.longitude.to_radians();
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let bx = lat2.cos() * d_lon.cos();
+        let by = lat2.cos() * d_lon.sin();
+        let lat = (lat1.sin() + lat2.sin()).atan2(((lat1.cos() + bx).powi(2) + by.powi(2)).sqrt());
+        let lon = lon1 + by.atan2(lat1.cos() + bx);
+        Coordinate::new(lat.to_degrees(), (lon.to_degrees() + 540.) % 360. - 180.)
+    }
+
+    fn to_icao(&self) -> String {
+        fn degrees_minutes(value: f64) -> (u32, u32) {
+            let minutes = (value.abs() * 60.).round() as u32;
+            (minutes / 60, minutes % 60)
+        }
+
+        let (lat_deg, lat_min) = degrees_minutes(self.latitude);
+        let (lon_deg, lon_min) = degrees_minutes(self.longitude);
+        let ns = if self.latitude < 0. { 'S' } else { 'N' };
+        let ew = if self.longitude < 0. { 'W' } else { 'E' };
+        format!("{lat_deg:02}{lat_min:02}{ns}{lon_deg:03}{lon_min:02}{ew}")
+    }
+
+    fn from_icao(text: &str) -> Option<Coordinate> {
+        fn part(digits: &str, hemisphere: char, (positive, negative): (char, char)) -> Option<f64> {
+            if !digits.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let degree_digits = match digits.len() {
+                2 | 3 => digits.len(),
+                4 | 5 => digits.len() - 2,
+                _ => return None,
+            };
+            let degrees: f64 = digits[..degree_digits].parse().ok()?;
+            let minutes: f64 = match &digits[degree_digits..] {
+                "" => 0.,
+                minutes => minutes.parse().ok()?,
+            };
+            let value = degrees + minutes / 60.;
+            match hemisphere {
+                c if c == positive => Some(value),
+                c if c == negative => Some(-value),
+                _ => None,
+            }
+        }
+
+        let text = text.trim();
+        let split = text.find(['N', 'S'])?;
+        let (lat, rest) = text.split_at(split);
+        let mut rest = rest.chars();
+        let ns = rest.next()?;
+        let lon = rest.as_str();
+        let ew = lon.chars().last()?;
+        let lon = &lon[..lon.len() - ew.len_utf8()];
+
+        // Degrees take two digits of latitude and three of longitude
+        if !matches!((lat.len(), lon.len()), (2, 3) | (4, 5)) {
+            return None;
+        }
+        let position = Coordinate::new(part(lat, ns, ('N', 'S'))?, part(lon, ew, ('E', 'W'))?);
+        position.is_valid().then_some(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::Coordinate;
+
+    use super::Geo;
+
+    #[test]
+    fn course_and_distance() {
+        let equator = Coordinate::new(0., 0.);
+
+        assert!((equator.distance_to(&Coordinate::new(1., 0.)) - 60.04).abs() < 0.01);
+        assert!((equator.course_to(&Coordinate::new(0., 1.)) - 90.).abs() < 1e-9);
+        assert!((equator.course_to(&Coordinate::new(-1., 0.)) - 180.).abs() < 1e-9);
+        assert!((equator.course_to(&Coordinate::new(0., -1.)) - 270.).abs() < 1e-9);
+
+        let midpoint = Coordinate::new(0., 179.).midpoint(&Coordinate::new(0., -179.));
+        assert!(midpoint.latitude.abs() < 1e-9);
+        assert!((midpoint.longitude.abs() - 180.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn icao_coordinates() {
+        let farnborough = Coordinate::new(51.2758, -0.7763);
+
+        assert_eq!(farnborough.to_icao(), "5117N00047W");
+        assert_eq!(Coordinate::new(-33.9461, 151.1772).to_icao(), "3357S15111E");
+
+        let read = Coordinate::from_icao("5117N00047W").unwrap();
+        assert!((read.latitude - 51.2833).abs() < 1e-4);
+        assert!((read.longitude + 0.7833).abs() < 1e-4);
+        assert_eq!(
+            Coordinate::from_icao("50S001E"),
+            Some(Coordinate::new(-50., 1.))
+        );
+        assert_eq!(Coordinate::from_icao("MID"), None);
+        assert_eq!(Coordinate::from_icao("5117N0047W"), None);
+    }
+}
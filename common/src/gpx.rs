@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use definition::{Coordinate, DefaultLegValues, Plan, Route};
+use roxmltree::{Document, Node};
+
+use crate::geo::Geo;
+use crate::import::{child_text, children, ImportedRoute, Waypoint};
+use crate::map::{escape_xml, map_routes};
+use crate::KneeboardError;
+
+/// Reads the routes from a GPX file: each `<rte>` in the file or, when there are none, the
+/// `<wpt>` list in file order. Routes with fewer than two points are left out.
+pub fn parse_gpx(text: &str) -> Result<Vec<ImportedRoute>, KneeboardError> {
+    let doc = Document::parse(text)
+        .map_err(|err| KneeboardError::String(format!("Not a GPX file: {err}")))?;
+    let gpx = doc.root_element();
+    if gpx.tag_name().name() != "gpx" {
+        return Err(KneeboardError::String(format!(
+            "Not a GPX file, found <{}>",
+            gpx.tag_name().name()
+        )));
+    }
+
+    let mut routes = vec![];
+    for rte in children(gpx, "rte") {
+        let waypoints = waypoints(children(rte, "rtept"))?;
+        routes.push(ImportedRoute {
+            name: child_text(rte, "name").unwrap_or_default(),
+            waypoints,
+        });
+    }
+
+    if routes.is_empty() {
+        let name = children(gpx, "metadata")
+            .find_map(|metadata| child_text(metadata, "name"))
+            .unwrap_or_default();
+        routes.push(ImportedRoute {
+            name,
+            waypoints: waypoints(children(gpx, "wpt"))?,
+        });
+    }
+
+    routes.retain(|route| route.waypoints.len() >= 2);
+    if routes.is_empty() {
+        return Err(KneeboardError::String(
+            "The GPX file has no route or waypoint list with at least two points".to_owned(),
+        ));
+    }
+    Ok(routes)
+}
+
+/// Routes from a GPX file, with the leg values that aren't in it taken from `defaults`.
+pub fn import_gpx(text: &str, defaults: &DefaultLegValues) -> Result<Vec<Route>, KneeboardError> {
+    let routes = parse_gpx(text)?;
+    Ok(routes
+        .iter()
+        .map(|route| route.to_route(defaults))
+        .collect())
+}
+
+/// Writes the routes of `plan` that have coordinates as a GPX file, with each point once in
+/// the waypoint list and an `<rte>` per route. The label of the leg to each route point is
+/// its description.
+pub fn write_gpx(plan: &Plan, include_return: bool) -> Result<String, KneeboardError> {
+    let routes = map_routes(plan, include_return)?;
+
+    let mut gpx = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<gpx version=\"1.1\" creator=\"Kneeboard Notes\" ",
+        "xmlns=\"http://www.topografix.com/GPX/1/1\">\n"
+    ));
+
+    let mut written: Vec<(&str, Coordinate)> = vec![];
+    for (name, position) in routes.iter().flat_map(|route| route.points()) {
+        if !written.contains(&(name, position)) {
+            written.push((name, position));
+            gpx += &point("wpt", name, position, None, "  ");
+        }
+    }
+
+    for route in &routes {
+        gpx += "  <rte>\n";
+        let _ = writeln!(gpx, "    <name>{}</name>", escape_xml(&route.name));
+        for (idx, (name, position)) in route.points().into_iter().enumerate() {
+            let label = idx.checked_sub(1).map(|leg| route.legs[leg].label());
+            gpx += &point("rtept", name, position, label.as_deref(), "    ");
+        }
+        gpx += "  </rte>\n";
+    }
+
+    gpx += "</gpx>\n";
+    Ok(gpx)
+}
+
+fn point(tag: &str, name: &str, position: Coordinate, desc: Option<&str>, indent: &str) -> String {
+    let mut point = format!(
+        "{indent}<{tag} lat=\"{:.6}\" lon=\"{:.6}\">\n{indent}  <name>{}</name>\n",
+        position.latitude,
+        position.longitude,
+        escape_xml(name)
+    );
+    if let Some(desc) = desc {
+        let _ = writeln!(point, "{indent}  <desc>{}</desc>", escape_xml(desc));
+    }
+    let _ = writeln!(point, "{indent}</{tag}>");
+    point
+}
+
+/// Reads `wpt` or `rtept` elements. Points without a name are named by their coordinates.
+fn waypoints<'a, 'input: 'a>(
+    points: impl Iterator<Item = Node<'a, 'input>>,
+) -> Result<Vec<Waypoint>, KneeboardError> {
+    points
+        .enumerate()
+        .map(|(idx, point)| {
+            let coordinate = |attribute: &str| {
+                point
+                    .attribute(attribute)
+                    .and_then(|value| value.trim().parse::<f64>().ok())
+            };
+            let position = match (coordinate("lat"), coordinate("lon")) {
+                (Some(lat), Some(lon)) => Coordinate::new(lat, lon),
+                _ => {
+                    return Err(KneeboardError::String(format!(
+                        "GPX point {} has no valid lat and lon",
+                        idx + 1
+                    )))
+                }
+            };
+            if !position.is_valid() {
+                return Err(KneeboardError::String(format!(
+                    "GPX point {} is off the globe at {}, {}",
+                    idx + 1,
+                    position.latitude,
+                    position.longitude
+                )));
+            }
+
+            let name = child_text(point, "name").unwrap_or_else(|| position.to_icao());
+            Ok(Waypoint::new(name, position))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::{Coordinate, DefaultLegValues};
+
+    use crate::create_template_plan;
+    use crate::map::route_through;
+
+    use super::{import_gpx, parse_gpx, write_gpx};
+
+    #[test]
+    fn routes_are_read_in_order() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+              <wpt lat="10" lon="10"><name>IGNORED</name></wpt>
+              <rte>
+                <name>Farnborough to Fairoaks</name>
+                <rtept lat="51.2758" lon="-0.7763"><name>EGLF</name></rtept>
+                <rtept lat="51.3000" lon="-0.7000"></rtept>
+                <rtept lat="51.3481" lon="-0.5589"><name> EGTF </name></rtept>
+              </rte>
+            </gpx>"#;
+
+        let routes = parse_gpx(gpx).unwrap();
+
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].name, "Farnborough to Fairoaks");
+        let names: Vec<&str> = routes[0]
+            .waypoints
+            .iter()
+            .map(|waypoint| waypoint.name.as_str())
+            .collect();
+        assert_eq!(names, ["EGLF", "5118N00042W", "EGTF"]);
+    }
+
+    #[test]
+    fn waypoint_lists_make_a_route() {
+        let gpx = r#"<gpx version="1.0">
+              <metadata><name>Coast</name></metadata>
+              <wpt lat="50" lon="0"><name>A</name></wpt>
+              <wpt lat="50" lon="1"><name>B</name></wpt>
+            </gpx>"#;
+
+        let routes = import_gpx(gpx, &DefaultLegValues::default()).unwrap();
+
+        assert_eq!(routes[0].name, "Coast");
+        assert_eq!(routes[0].legs[0].course, 90);
+        assert_eq!(routes[0].legs[0].distance, 39);
+    }
+
+    #[test]
+    fn bad_files_are_refused() {
+        assert!(parse_gpx("<kml></kml>").is_err());
+        assert!(parse_gpx(r#"<gpx><wpt lat="50" lon="0"/></gpx>"#).is_err());
+        assert!(
+            parse_gpx(r#"<gpx><wpt lat="north" lon="0"/><wpt lat="1" lon="0"/></gpx>"#).is_err()
+        );
+    }
+
+    #[test]
+    fn written_routes_read_back() {
+        let mut plan = create_template_plan();
+        plan.routes = vec![route_through(
+            "Coast & back",
+            &[("A", 50., 0.), ("B", 50., 1.)],
+        )];
+
+        let gpx = write_gpx(&plan, true).unwrap();
+
+        assert!(gpx.contains("<name>Coast &amp; back</name>"));
+        assert_eq!(gpx.matches("<wpt ").count(), 2);
+        let routes = parse_gpx(&gpx).unwrap();
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[1].name, "Coast & back (return)");
+        assert_eq!(routes[1].waypoints[0].name, "B");
+        assert_eq!(routes[1].waypoints[1].position, Coordinate::new(50., 0.));
+    }
+}
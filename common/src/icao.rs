@@ -0,0 +1,693 @@
+use core::flight_plan::create_flight_plan;
+use core::planner::route_name;
+use core::route::{calc_legs, convert_leg, Leg as CalcLeg};
+use core::upper_winds::plan_upper_winds;
+use definition::{Coordinate, DefaultLegValues, FontType, Leg, Plan, ProfileConfig, Route};
+use pdf::{PDFDocument, PDFDocumentBuilder, A5};
+
+use crate::geo::Geo;
+use crate::import::{set_positions, thousands_of_feet};
+use crate::validation::{Diagnostic, Severity};
+use crate::waypoints::WaypointDatabase;
+use crate::{create_leg_with_defaults, KneeboardError};
+
+/// Stands in for an aerodrome without an ICAO location indicator, which is then named in
+/// Item 18.
+const NO_INDICATOR: &str = "ZZZZ";
+
+const KM_PER_NM: f64 = 1.852;
+const FEET_PER_METRE: f64 = 3.28084;
+
+/// The items of an ICAO 2012 flight plan filed for one route. The off-block time of Item 13
+/// isn't known when planning, so is added when filing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlightPlan {
+    /// Item 7.
+    pub aircraft_id: String,
+    /// Item 8.
+    pub flight_rules: String,
+    pub flight_type: String,
+    /// Item 9.
+    pub aircraft_type: String,
+    pub wake_turbulence: String,
+    /// Item 10.
+    pub equipment: String,
+    /// Item 13.
+    pub departure: String,
+    /// Item 15, such as `N0100`.
+    pub cruising_speed: String,
+    /// Item 15, such as `A025`.
+    pub level: String,
+    /// Item 15 route, such as `DCT OCK DCT`.
+    pub route: String,
+    /// Item 16.
+    pub destination: String,
+    /// Item 16 as `HHMM`.
+    pub total_eet: String,
+    pub alternates: Vec<String>,
+    /// Item 18, `0` when there is nothing to add.
+    pub other_information: String,
+}
+
+impl FlightPlan {
+    /// The flight plan as an ATS message, one item per line.
+    pub fn to_message(&self) -> String {
+        let mut destination = format!("{}{}", self.destination, self.total_eet);
+        for alternate in &self.alternates {
+            destination += " ";
+            destination += alternate;
+        }
+
+        format!(
+            "(FPL-{}-{}{}\n-{}/{}-{}\n-{}\n-{}{} {}\n-{}\n-{})",
+            self.aircraft_id,
+            self.flight_rules,
+            self.flight_type,
+            self.aircraft_type,
+            self.wake_turbulence,
+            self.equipment,
+            self.departure,
+            self.cruising_speed,
+            self.level,
+            self.route,
+            destination,
+            self.other_information
+        )
+    }
+
+    /// Each item with its number and name, as printed on the page.
+    pub fn items(&self) -> Vec<(String, String)> {
+        [
+            ("7 Aircraft identification", self.aircraft_id.clone()),
+            (
+                "8 Flight rules / Type of flight",
+                format!("{} / {}", self.flight_rules, self.flight_type),
+            ),
+            (
+                "9 Type of aircraft / Wake turbulence cat.",
+                format!("{} / {}", self.aircraft_type, self.wake_turbulence),
+            ),
+            ("10 Equipment", self.equipment.clone()),
+            (
+                "13 Departure aerodrome / Time",
+                format!("{} / ____", self.departure),
+            ),
+            (
+                "15 Cruising speed / Level",
+                format!("{} / {}", self.cruising_speed, self.level),
+            ),
+            ("15 Route", self.route.clone()),
+            (
+                "16 Destination aerodrome / Total EET",
+                format!("{} / {}", self.destination, self.total_eet),
+            ),
+            ("16 Alternates", self.alternates.join(" ")),
+            ("18 Other information", self.other_information.clone()),
+        ]
+        .into_iter()
+        .map(|(label, value)| (label.to_owned(), value))
+        .collect()
+    }
+}
+
+/// Fills in a flight plan for route `route_idx` of `plan`, with the aircraft from `profile`.
+/// Fields that couldn't be filled in, or are filled in with stand-ins, are returned with the
+/// path of what should be set, profile fields under `profile`.
+pub fn create_icao_flight_plan(
+    plan: &Plan,
+    route_idx: usize,
+    profile: &ProfileConfig,
+) -> Result<(FlightPlan, Vec<Diagnostic>), KneeboardError> {
+    let route = plan
+        .routes
+        .get(route_idx)
+        .ok_or_else(|| KneeboardError::String(format!("There is no route {}", route_idx + 1)))?;
+    let (Some(first), Some(last)) = (route.legs.first(), route.legs.last()) else {
+        return Err(KneeboardError::String(format!(
+            "{} has no legs to file",
+            route_name(route, route_idx)
+        )));
+    };
+
+    let mut diagnostics = vec![];
+    let mut missing = |path: &str, message: &str| {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            path: path.to_owned(),
+            message: message.to_owned(),
+        });
+    };
+
+    let aircraft = &profile.aircraft;
+    let aircraft_id: String = plan
+        .detail
+        .call_sign
+        .iter()
+        .chain(&plan.detail.tail)
+        .map(|id| designator(id))
+        .find(|id| !id.is_empty())
+        .unwrap_or_default();
+    if aircraft_id.is_empty() {
+        missing("detail.call_sign", "a call sign or registration is needed");
+    }
+
+    let required = [
+        ("profile.aircraft.aircraft_type", &aircraft.aircraft_type),
+        (
+            "profile.aircraft.wake_turbulence",
+            &aircraft.wake_turbulence,
+        ),
+        ("profile.aircraft.equipment", &aircraft.equipment),
+        ("profile.aircraft.flight_rules", &aircraft.flight_rules),
+        ("profile.aircraft.flight_type", &aircraft.flight_type),
+    ];
+    for (path, value) in required {
+        if value.trim().is_empty() {
+            missing(path, "needed for the flight plan");
+        }
+    }
+
+    let path = format!("routes[{route_idx}].legs");
+    let speed = route.legs.iter().map(|leg| leg.speed).max().unwrap_or(0);
+    if speed <= 0 {
+        missing(&format!("{path}[0].speed"), "a cruising speed is needed");
+    }
+    let level = level(&first.planned).unwrap_or_else(|| {
+        missing(
+            &format!("{path}[0].planned"),
+            "the planned altitude must be in thousands of feet",
+        );
+        "VFR".to_owned()
+    });
+
+    let mut other = vec![];
+    let mut aerodrome = |name: &str, position: Option<Coordinate>, item: &str| {
+        let name = name.trim().to_uppercase();
+        if name.len() == 4 && name.chars().all(|c| c.is_ascii_alphabetic()) {
+            return name;
+        }
+        let described = position.map_or(name, |position| position.to_icao());
+        other.push(format!("{item}/{described}"));
+        NO_INDICATOR.to_owned()
+    };
+    let departure = aerodrome(&first.from, first.from_position, "DEP");
+    let destination = aerodrome(&last.to, last.to_position, "DEST");
+
+    let mut points = vec![];
+    for (idx, (leg, next)) in route.legs.iter().zip(&route.legs[1..]).enumerate() {
+        let point = route_point(leg).unwrap_or_else(|| {
+            missing(
+                &format!("{path}[{idx}].to"),
+                "needs a designator of 2 to 5 letters and digits or coordinates",
+            );
+            leg.to.trim().to_uppercase()
+        });
+        // Level changes are filed at the point they start
+        match level_change(leg, next, speed) {
+            Some(change) => points.push(format!("{point}/{change}")),
+            None => points.push(point),
+        }
+    }
+    let mut route_text = String::from("DCT");
+    for point in points {
+        route_text += &format!(" {point} DCT");
+    }
+
+    let legs: Vec<CalcLeg> = route.legs.iter().map(convert_leg).collect();
+    let winds = plan_upper_winds(plan);
+    let minutes = calc_legs(&legs, winds.as_ref())
+        .last()
+        .map_or(0., |(_, calc)| calc.total);
+    if !minutes.is_finite() {
+        missing(&path, "the legs have no ground speed to time them");
+    }
+
+    let mut alternates: Vec<String> = vec![];
+    for (idx, alternate) in plan.alternates.iter().enumerate() {
+        let alternate = alternate.trim().to_uppercase();
+        if alternate.len() != 4 || !alternate.chars().all(|c| c.is_ascii_alphabetic()) {
+            missing(
+                &format!("alternates[{idx}]"),
+                "alternates must be ICAO location indicators",
+            );
+        } else if alternates.len() < 2 {
+            alternates.push(alternate);
+        } else {
+            other.push(format!("ALTN/{alternate}"));
+        }
+    }
+
+    let mut other_information: Vec<String> = aircraft
+        .other_information
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect();
+    other_information.extend(other);
+
+    let flight_plan = FlightPlan {
+        aircraft_id,
+        flight_rules: aircraft.flight_rules.trim().to_uppercase(),
+        flight_type: aircraft.flight_type.trim().to_uppercase(),
+        aircraft_type: aircraft.aircraft_type.trim().to_uppercase(),
+        wake_turbulence: aircraft.wake_turbulence.trim().to_uppercase(),
+        equipment: aircraft.equipment.trim().to_uppercase(),
+        departure,
+        cruising_speed: format!("N{speed:04}"),
+        level,
+        route: route_text,
+        destination,
+        total_eet: hours_minutes(minutes),
+        alternates,
+        other_information: match other_information.is_empty() {
+            true => "0".to_owned(),
+            false => other_information.join(" "),
+        },
+    };
+    Ok((flight_plan, diagnostics))
+}
+
+/// An A5 page of the flight plan, listing what is missing from it.
+pub fn flight_plan_pdf(
+    title: &str,
+    flight_plan: &FlightPlan,
+    diagnostics: &[Diagnostic],
+) -> PDFDocument {
+    let mut doc = PDFDocumentBuilder::new();
+    doc.info_mut().title = Some(title.to_owned());
+    doc.add_bookmark(title, 0);
+
+    let missing: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+    let mut page = doc.create_page(A5);
+    create_flight_plan(&mut page, title, &flight_plan.items(), &missing);
+    doc.to_doc()
+}
+
+/// A route read from an ICAO route string, with what couldn't be used from it.
+#[derive(Debug)]
+pub struct IcaoRoute {
+    pub route: Route,
+    /// Points found neither as coordinates nor in the waypoint database, so without a
+    /// position.
+    pub missing: Vec<String>,
+    /// Airways and other elements that can't be turned into legs, once each.
+    pub unsupported: Vec<String>,
+}
+
+/// A point of the route with the speed and level flown from it, when they change there.
+struct RoutePoint {
+    name: String,
+    position: Option<Coordinate>,
+    speed: Option<i64>,
+    planned: Option<String>,
+}
+
+/// Reads a route as written in Item 15 of a flight plan, such as `EGTK N0100F025 DCT DTY
+/// DCT EGBJ`, with the aerodromes at either end. `DCT` is dropped, speed and level groups
+/// set the TAS and planned altitude of the legs from there on, and points are placed by
+/// their coordinates or from `database`. Everything else comes from `defaults`.
+pub fn parse_icao_route(
+    text: &str,
+    defaults: &DefaultLegValues,
+    database: &WaypointDatabase,
+) -> Result<IcaoRoute, KneeboardError> {
+    let mut points: Vec<RoutePoint> = vec![];
+    let mut unsupported: Vec<String> = vec![];
+    let mut pending: Option<(Option<i64>, Option<String>)> = None;
+
+    let tokens: Vec<String> = text
+        .split_whitespace()
+        .map(|token| token.trim_matches(|c| c == '-' || c == '(' || c == ')'))
+        .filter(|token| !token.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+    let last = tokens.len().saturating_sub(1);
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if token == "DCT" {
+            continue;
+        }
+
+        let (name, change) = match token.split_once('/') {
+            Some((name, change)) => (name, Some(speed_level(change)?)),
+            None => (token.as_str(), None),
+        };
+        if change.is_none() {
+            if let Some(group) = speed_level_group(name) {
+                // Applies to the legs from the point before it
+                let group = group?;
+                match points.last_mut() {
+                    Some(point) => apply(point, group),
+                    None => pending = Some(group),
+                }
+                continue;
+            }
+        }
+
+        let name = if idx == 0 || idx == last {
+            aerodrome_without_time(name)
+        } else {
+            name
+        };
+        let position = Coordinate::from_icao(name);
+        if position.is_none() && is_airway(name) {
+            if !unsupported.contains(&name.to_owned()) {
+                unsupported.push(name.to_owned());
+            }
+            continue;
+        }
+
+        let mut point = RoutePoint {
+            name: name.to_owned(),
+            position,
+            speed: None,
+            planned: None,
+        };
+        if let Some(group) = change.or(pending.take()) {
+            apply(&mut point, group);
+        }
+        points.push(point);
+    }
+
+    if points.len() < 2 {
+        return Err(KneeboardError::String(
+            "The route needs at least two points".to_owned(),
+        ));
+    }
+
+    let mut missing: Vec<String> = vec![];
+    let mut speed = defaults.speed;
+    let mut planned = defaults.planned.clone();
+    let mut legs = vec![];
+    for pair in points.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        speed = from.speed.unwrap_or(speed);
+        planned = from.planned.clone().unwrap_or(planned);
+
+        let mut leg = create_leg_with_defaults(from.name.clone(), to.name.clone(), defaults);
+        leg.speed = speed;
+        leg.planned = planned.clone();
+
+        let mut locate = |point: &RoutePoint| {
+            let position = point
+                .position
+                .or_else(|| database.lookup(&point.name).map(|entry| entry.position));
+            if position.is_none() && !missing.contains(&point.name) {
+                missing.push(point.name.clone());
+            }
+            position
+        };
+        if let (Some(from), Some(to)) = (locate(from), locate(to)) {
+            set_positions(&mut leg, from, to);
+        }
+        legs.push(leg);
+    }
+
+    Ok(IcaoRoute {
+        route: Route {
+            name: String::new(),
+            legs,
+            notes: vec![FontType::Blank],
+        },
+        missing,
+        unsupported,
+    })
+}
+
+type SpeedLevel = (Option<i64>, Option<String>);
+
+fn apply(point: &mut RoutePoint, (speed, planned): SpeedLevel) {
+    point.speed = speed.or(point.speed);
+    point.planned = planned.or(point.planned.take());
+}
+
+/// Reads a speed and level group, such as `N0100F025`, when `token` looks like one.
+fn speed_level_group(token: &str) -> Option<Result<SpeedLevel, KneeboardError>> {
+    let mut chars = token.chars();
+    let unit = chars.next()?;
+    let digits = chars.take_while(char::is_ascii_digit).count();
+    let is_group = matches!((unit, digits), ('N' | 'K', 4) | ('M', 3)) && token.len() > digits + 1;
+    is_group.then(|| speed_level(token))
+}
+
+/// A speed in knots and a planned altitude in thousands of feet from a group such as
+/// `N0100F025`, `K0185A045` or `N0100VFR`. VFR keeps the planned altitude as it was.
+fn speed_level(group: &str) -> Result<SpeedLevel, KneeboardError> {
+    let invalid = || KneeboardError::String(format!("{group} isn't a speed and level"));
+    if !group.is_ascii() {
+        return Err(invalid());
+    }
+
+    let (speed, level) = match group.chars().next() {
+        Some('N' | 'K') => group.split_at_checked(5).ok_or_else(invalid)?,
+        Some('M') => {
+            return Err(KneeboardError::String(format!(
+                "Mach numbers such as {group} can't be planned, use knots"
+            )))
+        }
+        _ => return Err(invalid()),
+    };
+    let value: f64 = speed[1..].parse().map_err(|_| invalid())?;
+    let knots = match speed.starts_with('K') {
+        true => value / KM_PER_NM,
+        false => value,
+    };
+
+    let feet = match level.split_at(level.len().min(1)) {
+        (_, "") | ("V", "FR") => None,
+        ("F" | "A", hundreds) if hundreds.len() == 3 => {
+            Some(hundreds.parse::<f64>().map_err(|_| invalid())? * 100.)
+        }
+        ("S" | "M", tens) if tens.len() == 4 => {
+            Some(tens.parse::<f64>().map_err(|_| invalid())? * 10. * FEET_PER_METRE)
+        }
+        _ => return Err(invalid()),
+    };
+
+    Ok((
+        Some(knots.round() as i64),
+        feet.map(|feet| thousands_of_feet(feet.round() as i64)),
+    ))
+}
+
+/// Departure and destination may be followed by a time, as in `EGBJ0045`.
+fn aerodrome_without_time(token: &str) -> &str {
+    let is_timed = token.len() == 8
+        && token.is_ascii()
+        && token[..4].chars().all(|c| c.is_ascii_alphabetic())
+        && token[4..].chars().all(|c| c.is_ascii_digit());
+    match is_timed {
+        true => &token[..4],
+        false => token,
+    }
+}
+
+/// Airway designators are one or two letters then up to three digits, such as `L9` or
+/// `UN864`, which points never are.
+fn is_airway(token: &str) -> bool {
+    let letters = token.chars().take_while(char::is_ascii_alphabetic).count();
+    let rest = &token[letters..];
+    (1..=3).contains(&letters)
+        && (1..=4).contains(&rest.len())
+        && rest.chars().next().is_some_and(|c| c.is_ascii_digit())
+        && rest.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Call signs and registrations are filed without hyphens or spaces.
+fn designator(id: &str) -> String {
+    id.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// The point at the end of `leg` as filed: its name when it is a designator, otherwise its
+/// coordinates.
+fn route_point(leg: &Leg) -> Option<String> {
+    let name = leg.to.trim().to_uppercase();
+    let is_designator = (2..=5).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric());
+    if is_designator {
+        return Some(name);
+    }
+    leg.to_position.map(|position| position.to_icao())
+}
+
+/// A planned altitude in thousands of feet, such as `2.5`, as an altitude in hundreds of
+/// feet, such as `A025`.
+fn level(planned: &str) -> Option<String> {
+    let thousands: f64 = planned.trim().parse().ok()?;
+    let hundreds = (thousands * 10.).round();
+    (0. ..1000.)
+        .contains(&hundreds)
+        .then(|| format!("A{:03}", hundreds as i64))
+}
+
+/// The speed and level from the start of `next`, when its level differs from `leg`.
+fn level_change(leg: &Leg, next: &Leg, speed: i64) -> Option<String> {
+    let next_level = level(&next.planned)?;
+    (level(&leg.planned)? != next_level).then(|| format!("N{speed:04}{next_level}"))
+}
+
+fn hours_minutes(minutes: f64) -> String {
+    if !minutes.is_finite() {
+        return "0000".to_owned();
+    }
+    let minutes = minutes.round() as i64;
+    format!("{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::{Coordinate, DefaultLegValues, ProfileConfig};
+
+    use crate::create_template_plan;
+    use crate::waypoints::{parse_csv, WaypointDatabase};
+
+    use super::{create_icao_flight_plan, parse_icao_route};
+
+    fn profile() -> ProfileConfig {
+        let mut profile = ProfileConfig::default();
+        profile.aircraft.aircraft_type = "c172".to_owned();
+        profile.aircraft.equipment = "SDFGY/S".to_owned();
+        profile
+    }
+
+    #[test]
+    fn items_come_from_the_plan_and_profile() {
+        let mut plan = create_template_plan();
+        plan.detail.call_sign = Some("G-ABCD".to_owned());
+        plan.alternates = vec!["egkk".to_owned()];
+        let legs = &mut plan.routes[0].legs;
+        legs[0].from = "EGLF".to_owned();
+        legs[0].to = "Ockham VOR".to_owned();
+        legs[0].to_position = Some(Coordinate::new(51.305, -0.4473));
+        legs[1].from = "Ockham VOR".to_owned();
+        legs[1].to = "Fairoaks".to_owned();
+        legs[1].planned = "3.5".to_owned();
+
+        let (flight_plan, diagnostics) = create_icao_flight_plan(&plan, 0, &profile()).unwrap();
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert_eq!(flight_plan.aircraft_id, "GABCD");
+        assert_eq!(flight_plan.route, "DCT 5118N00027W/N0100A035 DCT");
+        assert_eq!(flight_plan.destination, "ZZZZ");
+        assert_eq!(flight_plan.other_information, "DEST/FAIROAKS");
+
+        let message = flight_plan.to_message();
+        assert!(message.starts_with("(FPL-GABCD-VG\n-C172/L-SDFGY/S\n-EGLF\n-N0100A022 DCT"));
+        assert!(message.ends_with(&format!(
+            "\n-ZZZZ{} EGKK\n-DEST/FAIROAKS)",
+            flight_plan.total_eet
+        )));
+    }
+
+    #[test]
+    fn missing_fields_are_reported() {
+        let mut plan = create_template_plan();
+        plan.detail.call_sign = None;
+        plan.detail.tail = None;
+
+        let (_, diagnostics) =
+            create_icao_flight_plan(&plan, 0, &ProfileConfig::default()).unwrap();
+
+        let paths: Vec<&str> = diagnostics.iter().map(|d| d.path.as_str()).collect();
+        assert!(paths.contains(&"detail.call_sign"));
+        assert!(paths.contains(&"profile.aircraft.aircraft_type"));
+        assert!(paths.contains(&"profile.aircraft.equipment"));
+        assert!(create_icao_flight_plan(&plan, 9, &profile()).is_err());
+    }
+
+    #[test]
+    fn route_strings_become_legs() {
+        let database = WaypointDatabase::new(
+            parse_csv(
+                "ident,name,lat,lon\nEGTK,Oxford,51.8369,-1.32\nDTY,Daventry,52.18,-1.1136\n",
+            )
+            .unwrap(),
+        );
+        let defaults = DefaultLegValues::default();
+
+        let parsed = parse_icao_route(
+            "EGTK0900 N0100F025 DCT DTY/K0185A045 DCT 5230N00100W L9 EGBJ0045",
+            &defaults,
+            &database,
+        )
+        .unwrap();
+
+        let legs = &parsed.route.legs;
+        let names: Vec<(&str, &str)> = legs
+            .iter()
+            .map(|leg| (leg.from.as_str(), leg.to.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("EGTK", "DTY"),
+                ("DTY", "5230N00100W"),
+                ("5230N00100W", "EGBJ")
+            ]
+        );
+        assert_eq!((legs[0].speed, legs[0].planned.as_str()), (100, "2.5"));
+        assert_eq!((legs[1].speed, legs[1].planned.as_str()), (100, "4.5"));
+        assert_eq!(legs[0].course, 20);
+        assert!(legs[1].to_position.is_some());
+        assert!(legs[2].to_position.is_none());
+        assert_eq!(parsed.missing, ["EGBJ"]);
+        assert_eq!(parsed.unsupported, ["L9"]);
+    }
+
+    #[test]
+    fn unknown_aerodromes_are_missing_not_guessed() {
+        let database = WaypointDatabase::new(
+            parse_csv("ident,name,lat,lon\nEGTF,Fairoaks,51.3481,-0.5589\n").unwrap(),
+        );
+
+        let parsed = parse_icao_route(
+            "EGTK N0100F025 DCT DTY DCT EGBJ",
+            &DefaultLegValues::default(),
+            &database,
+        )
+        .unwrap();
+
+        assert!(parsed.route.legs[0].from_position.is_none());
+        assert_eq!(parsed.missing, ["EGTK", "DTY", "EGBJ"]);
+    }
+
+    #[test]
+    fn plain_routes_keep_the_defaults() {
+        let defaults = DefaultLegValues::default();
+
+        let parsed = parse_icao_route(
+            "EGKB DCT SFD DCT MAY EGHR",
+            &defaults,
+            &WaypointDatabase::default(),
+        )
+        .unwrap();
+
+        assert_eq!(parsed.route.legs.len(), 3);
+        assert_eq!(parsed.route.legs[2].from, "MAY");
+        assert_eq!(parsed.route.legs[0].planned, defaults.planned);
+        assert!(parse_icao_route("EGKB DCT", &defaults, &WaypointDatabase::default()).is_err());
+        assert!(parse_icao_route(
+            "EGKB M082F350 EGLL",
+            &defaults,
+            &WaypointDatabase::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn pasted_symbols_are_rejected_not_sliced() {
+        let defaults = DefaultLegValues::default();
+        let database = WaypointDatabase::default();
+
+        assert!(parse_icao_route("EGTK/N000é DCT EGBJ", &defaults, &database).is_err());
+        assert!(parse_icao_route("EGTK N0100É25 DTY EGBJ", &defaults, &database).is_err());
+        assert!(parse_icao_route("EGTK DTY/N0100é EGBJ", &defaults, &database).is_err());
+        let parsed = parse_icao_route("ABCé123 DCT EGBJ", &defaults, &database).unwrap();
+        assert_eq!(parsed.route.legs[0].from, "ABCÉ123");
+        let parsed = parse_icao_route("EGTK BSPéEMB", &defaults, &database).unwrap();
+        assert_eq!(parsed.route.legs[0].to, "BSPÉEMB");
+    }
+}
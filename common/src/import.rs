@@ -0,0 +1,161 @@
+use definition::{Coordinate, DefaultLegValues, FontType, Leg, Route};
+use roxmltree::Node;
+
+use crate::create_leg_with_defaults;
+use crate::geo::Geo;
+
+/// A named point read from a route file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Waypoint {
+    pub name: String,
+    pub position: Coordinate,
+    /// Planned altitude in feet for the leg that ends here.
+    pub altitude: Option<i64>,
+}
+
+impl Waypoint {
+    pub fn new(name: impl Into<String>, position: Coordinate) -> Self {
+        Self {
+            name: name.into(),
+            position,
+            altitude: None,
+        }
+    }
+}
+
+/// Routes read from a flight plan file, along with the parts of it that were left out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Imported {
+    pub routes: Vec<ImportedRoute>,
+    /// Elements the importer doesn't understand, such as `<Aircraft>`, once each.
+    pub unsupported: Vec<String>,
+}
+
+impl Imported {
+    pub(crate) fn unsupported(&mut self, what: String) {
+        if !self.unsupported.contains(&what) {
+            self.unsupported.push(what);
+        }
+    }
+
+    /// Converts the routes, filling in what the file doesn't give from `defaults`.
+    pub fn to_routes(&self, defaults: &DefaultLegValues) -> Vec<Route> {
+        self.routes
+            .iter()
+            .map(|route| route.to_route(defaults))
+            .collect()
+    }
+}
+
+/// An ordered list of waypoints read from another planning tool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedRoute {
+    pub name: String,
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl ImportedRoute {
+    /// A route with a leg between each pair of waypoints. Course and distance come from the
+    /// coordinates and the planned altitude from the waypoint at the end of the leg, when
+    /// it has one. Everything else comes from `defaults`.
+    pub fn to_route(&self, defaults: &DefaultLegValues) -> Route {
+        let legs = self
+            .waypoints
+            .windows(2)
+            .map(|pair| {
+                let (from, to) = (&pair[0], &pair[1]);
+                let mut leg =
+                    create_leg_with_defaults(from.name.clone(), to.name.clone(), defaults);
+                set_positions(&mut leg, from.position, to.position);
+                if let Some(altitude) = to.altitude {
+                    leg.planned = thousands_of_feet(altitude);
+                }
+                leg
+            })
+            .collect();
+
+        Route {
+            name: self.name.clone(),
+            legs,
+            notes: vec![FontType::Blank],
+        }
+    }
+}
+
+/// Sets the ends of `leg`, with its course and distance worked out from them.
+pub(crate) fn set_positions(leg: &mut Leg, from: Coordinate, to: Coordinate) {
+    leg.course = from.course_to(&to).round() as i64 % 360;
+    leg.distance = from.distance_to(&to).round() as i64;
+    leg.from_position = Some(from);
+    leg.to_position = Some(to);
+}
+
+/// Altitudes are entered in thousands of feet, like the default of `2.2`.
+pub(crate) fn thousands_of_feet(feet: i64) -> String {
+    format!("{:.1}", feet as f64 / 1000.)
+}
+
+/// Child elements named `name`, whatever their namespace, as versions of a format often
+/// differ only in that.
+pub(crate) fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Trimmed text of the first child element named `name`, if it has any.
+pub(crate) fn child_text(node: Node, name: &str) -> Option<String> {
+    children(node, name)
+        .next()
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_owned())
+        .filter(|text| !text.is_empty())
+}
+
+/// Records the child elements of `node` not listed in `known`.
+pub(crate) fn report_unknown(node: Node, known: &[&str], imported: &mut Imported) {
+    for child in node.children().filter(Node::is_element) {
+        let name = child.tag_name().name();
+        if !known.contains(&name) {
+            imported.unsupported(format!("<{name}> in <{}>", node.tag_name().name()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::{Coordinate, DefaultLegValues};
+
+    use super::{ImportedRoute, Waypoint};
+
+    #[test]
+    fn legs_join_the_waypoints() {
+        let imported = ImportedRoute {
+            name: "North".to_owned(),
+            waypoints: vec![
+                Waypoint::new("A", Coordinate::new(50., 0.)),
+                Waypoint {
+                    altitude: Some(3500),
+                    ..Waypoint::new("B", Coordinate::new(50.5, 0.))
+                },
+                Waypoint::new("C", Coordinate::new(50.5, 0.5)),
+            ],
+        };
+
+        let route = imported.to_route(&DefaultLegValues::default());
+
+        assert_eq!(route.name, "North");
+        assert_eq!(route.legs.len(), 2);
+        assert_eq!(
+            (route.legs[0].from.as_str(), route.legs[0].to.as_str()),
+            ("A", "B")
+        );
+        assert_eq!((route.legs[0].course, route.legs[0].distance), (0, 30));
+        assert_eq!(route.legs[0].planned, "3.5");
+        assert_eq!(route.legs[1].course, 90);
+        assert_eq!(route.legs[1].planned, DefaultLegValues::default().planned);
+        assert_eq!(route.legs[1].speed, DefaultLegValues::default().speed);
+    }
+}
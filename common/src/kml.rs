@@ -0,0 +1,94 @@
+use std::fmt::Write;
+
+use definition::{Coordinate, Plan};
+
+use crate::geo::Geo;
+use crate::map::{escape_xml, map_routes};
+use crate::KneeboardError;
+
+/// Writes the routes of `plan` that have coordinates as KML, with a folder per route holding
+/// its line, its waypoints and a label at the middle of each leg with its track, distance and
+/// heading.
+pub fn write_kml(plan: &Plan, include_return: bool) -> Result<String, KneeboardError> {
+    let routes = map_routes(plan, include_return)?;
+
+    let mut kml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n",
+        "<Document>\n",
+        "  <name>Kneeboard Notes</name>\n"
+    ));
+
+    for route in &routes {
+        kml += "  <Folder>\n";
+        let _ = writeln!(kml, "    <name>{}</name>", escape_xml(&route.name));
+
+        let points = route.points();
+        let line: Vec<String> = points
+            .iter()
+            .map(|(_, position)| coordinates(*position))
+            .collect();
+        let _ = writeln!(
+            kml,
+            "    <Placemark>\n      <name>{}</name>\n      <LineString>\n        \
+             <tessellate>1</tessellate>\n        <coordinates>{}</coordinates>\n      \
+             </LineString>\n    </Placemark>",
+            escape_xml(&route.name),
+            line.join(" ")
+        );
+
+        for (name, position) in points {
+            kml += &placemark(name, position);
+        }
+        for leg in &route.legs {
+            let middle = leg.from_position.midpoint(&leg.to_position);
+            kml += &placemark(&leg.label(), middle);
+        }
+
+        kml += "  </Folder>\n";
+    }
+
+    kml += "</Document>\n</kml>\n";
+    Ok(kml)
+}
+
+/// KML coordinates are longitude first.
+fn coordinates(position: Coordinate) -> String {
+    format!("{:.6},{:.6}", position.longitude, position.latitude)
+}
+
+fn placemark(name: &str, position: Coordinate) -> String {
+    format!(
+        "    <Placemark>\n      <name>{}</name>\n      \
+         <Point><coordinates>{}</coordinates></Point>\n    </Placemark>\n",
+        escape_xml(name),
+        coordinates(position)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use roxmltree::Document;
+
+    use crate::create_template_plan;
+    use crate::map::route_through;
+
+    use super::write_kml;
+
+    #[test]
+    fn legs_are_labelled_at_their_middle() {
+        let mut plan = create_template_plan();
+        plan.routes = vec![route_through("East", &[("A", 50., 0.), ("B", 50., 0.5)])];
+
+        let kml = write_kml(&plan, false).unwrap();
+
+        let doc = Document::parse(&kml).unwrap();
+        let names: Vec<&str> = doc
+            .descendants()
+            .filter(|node| node.has_tag_name("Placemark"))
+            .filter_map(|placemark| placemark.first_element_child()?.text())
+            .collect();
+        assert_eq!(names, ["East", "A", "B", "TRK 090 19NM HDG 090M"]);
+        assert!(kml.contains("0.000000,50.000000 0.500000,50.000000"));
+    }
+}
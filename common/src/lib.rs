@@ -1,5 +1,20 @@
-use definition::{Detail, Diversion, FontType, Hold, Leg, Plan, Route, Velocity};
+use definition::{DefaultLegValues, Detail, Diversion, FontType, Hold, Leg, Plan, Route, Velocity};
 use std::io;
+use waypoints::WaypointDatabase;
+
+pub mod fpl;
+pub mod geo;
+pub mod gpx;
+pub mod icao;
+pub mod import;
+pub mod kml;
+pub mod map;
+pub mod migration;
+pub mod plog;
+pub mod schema;
+pub mod skydemon;
+pub mod validation;
+pub mod waypoints;
 
 impl From<serde_json::Error> for KneeboardError {
     fn from(value: serde_json::Error) -> Self {
@@ -88,7 +103,7 @@ pub fn create_template_plan() -> Plan {
         detail,
         diversions,
         routes,
-        holds: vec![],
+        ..Plan::default()
     }
 }
 
@@ -118,6 +133,8 @@ pub fn create_template_route() -> Route {
                 variation,
                 wind_direction,
                 wind_speed,
+                from_position: None,
+                to_position: None,
             }
         };
 
@@ -145,6 +162,8 @@ pub fn create_template_route() -> Route {
                 variation,
                 wind_direction,
                 wind_speed,
+                from_position: None,
+                to_position: None,
             }
         };
 
@@ -195,9 +214,50 @@ pub fn create_template_leg_with_from(from: Option<String>) -> Leg {
         variation,
         wind_direction,
         wind_speed,
+        from_position: None,
+        to_position: None,
     }
 }
 
+/// A leg between two named points filled in with the profile's default values.
+pub fn create_leg_with_defaults(from: String, to: String, defaults: &DefaultLegValues) -> Leg {
+    Leg {
+        from,
+        to,
+        safe: defaults.safe.clone(),
+        planned: defaults.planned.clone(),
+        speed: defaults.speed,
+        course: defaults.course,
+        distance: defaults.distance,
+        variation: defaults.variation,
+        wind_direction: defaults.wind_direction,
+        wind_speed: defaults.wind_speed,
+        from_position: None,
+        to_position: None,
+    }
+}
+
+/// A route through `waypoints` with legs from `defaults`. Legs between points found in
+/// `database` get their coordinates, course and distance from it, and the names that
+/// weren't found are returned with the route.
+pub fn create_route_from_waypoints(
+    waypoints: &[String],
+    defaults: &DefaultLegValues,
+    database: &WaypointDatabase,
+) -> (Route, Vec<String>) {
+    let legs = waypoints
+        .windows(2)
+        .map(|pair| create_leg_with_defaults(pair[0].clone(), pair[1].clone(), defaults))
+        .collect();
+    let mut route = Route {
+        name: String::new(),
+        legs,
+        notes: vec![FontType::Blank],
+    };
+    let missing = database.fill_route(&mut route);
+    (route, missing)
+}
+
 pub fn create_template_hold() -> Hold {
     Hold {
         description: String::new(),
@@ -209,6 +269,7 @@ pub fn create_template_hold() -> Hold {
         },
         aircraft_speed: 100,
         variation: 0,
+        rotate_to_track: false,
     }
 }
 
@@ -0,0 +1,192 @@
+use core::calc::Degree;
+use core::planner::{return_legs, route_name};
+use core::route::{calc_legs, convert_leg, Leg};
+use core::upper_winds::{plan_upper_winds, UpperWinds};
+use definition::{Coordinate, Plan};
+
+use crate::KneeboardError;
+
+/// A leg with the positions of its ends, as drawn on a map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapLeg {
+    pub from: String,
+    pub to: String,
+    pub from_position: Coordinate,
+    pub to_position: Coordinate,
+    pub course: Degree,
+    pub distance: f64,
+    pub heading_magnetic: Degree,
+}
+
+impl MapLeg {
+    /// Track, distance and heading, as labelled on the leg.
+    pub fn label(&self) -> String {
+        format!(
+            "TRK {} {}NM HDG {}M",
+            self.course.as_heading(),
+            self.distance,
+            self.heading_magnetic.as_heading()
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapRoute {
+    pub name: String,
+    pub legs: Vec<MapLeg>,
+}
+
+impl MapRoute {
+    /// The start of each leg then the end of the last, with their names.
+    pub fn points(&self) -> Vec<(&str, Coordinate)> {
+        let mut points: Vec<(&str, Coordinate)> = self
+            .legs
+            .iter()
+            .map(|leg| (leg.from.as_str(), leg.from_position))
+            .collect();
+        if let Some(last) = self.legs.last() {
+            points.push((last.to.as_str(), last.to_position));
+        }
+        points
+    }
+}
+
+/// The routes of `plan` that can be drawn on a map, each followed by its return route when
+/// `include_return` is set. Routes with a leg missing its positions are left out, as
+/// routes typed in rather than imported have none.
+pub fn map_routes(plan: &Plan, include_return: bool) -> Result<Vec<MapRoute>, KneeboardError> {
+    let mut routes = vec![];
+
+    let winds = plan_upper_winds(plan);
+    for (idx, route) in plan.routes.iter().enumerate() {
+        let positions: Option<Vec<(Coordinate, Coordinate)>> = route
+            .legs
+            .iter()
+            .map(|leg| Some((leg.from_position?, leg.to_position?)))
+            .collect();
+        let Some(positions) = positions.filter(|positions| !positions.is_empty()) else {
+            continue;
+        };
+
+        let name = route_name(route, idx);
+        let legs: Vec<Leg> = route.legs.iter().map(convert_leg).collect();
+        routes.push(MapRoute {
+            name: name.clone(),
+            legs: map_legs(&legs, &positions, winds.as_ref()),
+        });
+
+        if include_return {
+            let positions: Vec<(Coordinate, Coordinate)> = positions
+                .iter()
+                .rev()
+                .map(|&(from, to)| (to, from))
+                .collect();
+            routes.push(MapRoute {
+                name: format!("{name} (return)"),
+                legs: map_legs(&return_legs(legs), &positions, winds.as_ref()),
+            });
+        }
+    }
+
+    if routes.is_empty() {
+        return Err(KneeboardError::String(
+            "No route has coordinates to export, import one from a GPX or flight plan file"
+                .to_owned(),
+        ));
+    }
+    Ok(routes)
+}
+
+fn map_legs(
+    legs: &[Leg],
+    positions: &[(Coordinate, Coordinate)],
+    winds: Option<&UpperWinds>,
+) -> Vec<MapLeg> {
+    calc_legs(legs, winds)
+        .into_iter()
+        .zip(positions)
+        .map(|((leg, calc), &(from_position, to_position))| {
+            let (from, to) = leg.name.clone();
+            MapLeg {
+                from,
+                to,
+                from_position,
+                to_position,
+                course: leg.course,
+                distance: leg.distance,
+                heading_magnetic: calc.heading_magnetic,
+            }
+        })
+        .collect()
+}
+
+/// Escapes text for XML content and attribute values.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A route with the default leg values through `points`, each a name, latitude and
+/// longitude, for tests that need legs with coordinates.
+#[cfg(test)]
+pub(crate) fn route_through(name: &str, points: &[(&str, f64, f64)]) -> definition::Route {
+    use crate::import::{ImportedRoute, Waypoint};
+
+    let waypoints = points
+        .iter()
+        .map(|&(point, latitude, longitude)| {
+            Waypoint::new(point, Coordinate::new(latitude, longitude))
+        })
+        .collect();
+    let imported = ImportedRoute {
+        name: name.to_owned(),
+        waypoints,
+    };
+    imported.to_route(&definition::DefaultLegValues::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::Coordinate;
+
+    use crate::create_template_plan;
+
+    use super::{map_routes, route_through};
+
+    #[test]
+    fn return_routes_run_backwards() {
+        let mut plan = create_template_plan();
+        plan.routes.push(route_through(
+            "East",
+            &[("A", 50., 0.), ("B", 50., 0.5), ("C", 50.5, 0.5)],
+        ));
+
+        let routes = map_routes(&plan, true).unwrap();
+
+        // The template route was typed in, so has no coordinates
+        let names: Vec<&str> = routes.iter().map(|route| route.name.as_str()).collect();
+        assert_eq!(names, ["East", "East (return)"]);
+
+        let back = &routes[1];
+        let points: Vec<&str> = back.points().iter().map(|(name, _)| *name).collect();
+        assert_eq!(points, ["C", "B", "A"]);
+        assert_eq!(back.legs[0].course.degrees, 180.);
+        assert_eq!(back.legs[0].from_position, Coordinate::new(50.5, 0.5));
+        assert_eq!(back.legs[1].label(), "TRK 270 19NM HDG 270M");
+    }
+
+    #[test]
+    fn plans_without_coordinates_cannot_be_mapped() {
+        assert!(map_routes(&create_template_plan(), false).is_err());
+    }
+}
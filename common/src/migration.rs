@@ -0,0 +1,278 @@
+use definition::{AircraftDetails, Plan, ProfileConfig, PLAN_VERSION, PROFILE_VERSION};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::KneeboardError;
+
+/// Upgrades a document from one version to the next.
+type Migration = fn(&mut Map<String, Value>);
+
+/// Plan migrations, where entry `n` upgrades a version `n` document to version `n + 1`.
+/// Files saved before versioning count as version 0.
+const PLAN_MIGRATIONS: [Migration; PLAN_VERSION as usize] = [
+    plan_v0_to_v1,
+    plan_v1_to_v2,
+    plan_v2_to_v3,
+    plan_v3_to_v4,
+    plan_v4_to_v5,
+];
+
+const PROFILE_MIGRATIONS: [Migration; PROFILE_VERSION as usize] =
+    [profile_v0_to_v1, profile_v1_to_v2];
+
+/// The kinds of file saved by the app, told apart by their `file_type` tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileType {
+    Plan,
+    Profile,
+}
+
+impl FileType {
+    pub fn tag(self) -> &'static str {
+        match self {
+            FileType::Plan => "plan",
+            FileType::Profile => "profile",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "plan" => Some(FileType::Plan),
+            "profile" => Some(FileType::Profile),
+            _ => None,
+        }
+    }
+
+    /// The format version written by this build.
+    pub fn version(self) -> u32 {
+        match self {
+            FileType::Plan => PLAN_VERSION,
+            FileType::Profile => PROFILE_VERSION,
+        }
+    }
+
+    fn migrations(self) -> &'static [Migration] {
+        match self {
+            FileType::Plan => &PLAN_MIGRATIONS,
+            FileType::Profile => &PROFILE_MIGRATIONS,
+        }
+    }
+
+    /// Works out the type from the tag or, for files saved before tagging, from the keys
+    /// only that type has.
+    pub fn detect(value: &Value) -> Option<Self> {
+        match value.get("file_type").and_then(Value::as_str) {
+            Some(tag) => Self::from_tag(tag),
+            None if value.get("routes").is_some() => Some(FileType::Plan),
+            None if value.get("aircraft_registrations").is_some() => Some(FileType::Profile),
+            None => None,
+        }
+    }
+}
+
+/// Upgrades `value` in place to the current version of `file_type`.
+pub fn migrate(file_type: FileType, value: &mut Value) -> Result<(), KneeboardError> {
+    let tag = file_type.tag();
+    let Some(obj) = value.as_object_mut() else {
+        return Err(KneeboardError::String(format!(
+            "A {tag} file must be a JSON object"
+        )));
+    };
+
+    let migrations = file_type.migrations();
+    let current = migrations.len() as u64;
+    let version = match obj.get("version") {
+        None => 0,
+        Some(version) => version.as_u64().ok_or_else(|| {
+            KneeboardError::String(format!("The {tag} version must be a whole number"))
+        })?,
+    };
+
+    if version > current {
+        return Err(KneeboardError::String(format!(
+            "This {tag} file is format version {version} but this version of Kneeboard Notes \
+             only understands up to {current}. Update Kneeboard Notes to open it."
+        )));
+    }
+
+    for migration in &migrations[version as usize..] {
+        migration(obj);
+    }
+    obj.insert("version".to_owned(), json!(current));
+    Ok(())
+}
+
+/// Reads a plan file of any version, refusing other file types.
+pub fn read_plan(data: &[u8]) -> Result<Plan, KneeboardError> {
+    let value = read_file(data, FileType::Plan)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Reads a profile file of any version, refusing other file types.
+pub fn read_profile(data: &[u8]) -> Result<ProfileConfig, KneeboardError> {
+    let value = read_file(data, FileType::Profile)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Upgrades a parsed plan document of any version.
+pub fn plan_from_value(mut value: Value) -> Result<Plan, KneeboardError> {
+    migrate(FileType::Plan, &mut value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Upgrades a parsed profile document of any version.
+pub fn profile_from_value(mut value: Value) -> Result<ProfileConfig, KneeboardError> {
+    migrate(FileType::Profile, &mut value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+fn read_file(data: &[u8], expected: FileType) -> Result<Value, KneeboardError> {
+    let mut value: Value = serde_json::from_slice(data)?;
+
+    // Untagged files are trusted to be what was asked for, as older versions saved them
+    if let Some(tag) = value.get("file_type").and_then(Value::as_str) {
+        if tag != expected.tag() {
+            return Err(KneeboardError::String(format!(
+                "This is a {tag} file, not a {}",
+                expected.tag()
+            )));
+        }
+    }
+
+    migrate(expected, &mut value)?;
+    Ok(value)
+}
+
+/// The JSON saved for a plan or profile, tagged with its file type.
+pub fn to_file_value(value: &impl Serialize, file_type: FileType) -> Result<Value, KneeboardError> {
+    let mut value = serde_json::to_value(value)?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("file_type".to_owned(), json!(file_type.tag()));
+    }
+    Ok(value)
+}
+
+/// Sets `key` to `default` when an older file left it out.
+fn insert_missing(obj: &mut Map<String, Value>, key: &str, default: Value) {
+    obj.entry(key).or_insert(default);
+}
+
+/// Unversioned plans could leave out the details, diversions, routes and route names.
+fn plan_v0_to_v1(plan: &mut Map<String, Value>) {
+    insert_missing(plan, "detail", json!({}));
+    insert_missing(plan, "diversions", json!([]));
+    insert_missing(plan, "routes", json!([]));
+
+    if let Some(routes) = plan.get_mut("routes").and_then(Value::as_array_mut) {
+        for route in routes.iter_mut().filter_map(Value::as_object_mut) {
+            insert_missing(route, "name", json!(""));
+        }
+    }
+}
+
+/// Version 2 added the coordinates of each leg's ends, which older legs go without.
+fn plan_v1_to_v2(_plan: &mut Map<String, Value>) {}
+
+/// Version 3 added the alternates filed with the flight plan, which default to none.
+fn plan_v2_to_v3(_plan: &mut Map<String, Value>) {}
+
+/// Version 4 added pasted METARs and TAFs, which default to none.
+fn plan_v3_to_v4(_plan: &mut Map<String, Value>) {}
+
+/// Version 5 added a pasted upper-wind forecast, which defaults to empty.
+fn plan_v4_to_v5(_plan: &mut Map<String, Value>) {}
+
+/// Unversioned profiles were saved before holds could be kept in them.
+fn profile_v0_to_v1(profile: &mut Map<String, Value>) {
+    insert_missing(profile, "saved_holds", json!([]));
+}
+
+/// Version 1 profiles were saved before flight plans could be filed from them.
+fn profile_v1_to_v2(profile: &mut Map<String, Value>) {
+    let aircraft = serde_json::to_value(AircraftDetails::default()).unwrap_or_default();
+    insert_missing(profile, "aircraft", aircraft);
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::PLAN_VERSION;
+    use serde_json::json;
+
+    use crate::create_template_plan;
+    use crate::KneeboardError;
+
+    use super::{read_plan, read_profile, to_file_value, FileType};
+
+    #[test]
+    fn unversioned_plans_are_upgraded() {
+        let data = json!({
+            "routes": [{ "legs": [], "notes": [] }],
+        });
+
+        let plan = read_plan(data.to_string().as_bytes()).unwrap();
+
+        assert_eq!(plan.version, PLAN_VERSION);
+        assert_eq!(plan.routes[0].name, "");
+        assert!(plan.diversions.is_empty());
+    }
+
+    #[test]
+    fn version_1_plans_are_upgraded() {
+        let data = json!({
+            "file_type": "plan",
+            "version": 1,
+            "detail": {},
+            "diversions": [],
+            "routes": [{ "name": "", "legs": [], "notes": [] }],
+        });
+
+        let plan = read_plan(data.to_string().as_bytes()).unwrap();
+
+        assert_eq!(plan.version, PLAN_VERSION);
+        assert!(plan.alternates.is_empty());
+        assert!(plan.weather.is_empty());
+        assert!(plan.upper_winds.is_empty());
+    }
+
+    #[test]
+    fn unversioned_profiles_are_upgraded() {
+        let data = json!({
+            "aircraft_registrations": ["G-ABCD"],
+            "pics": [],
+            "call_signs": [],
+            "saved_routes": [],
+            "default_leg_values": {
+                "safe": "1.8", "planned": "2.2", "speed": 100, "course": 0,
+                "distance": 10, "variation": 0, "wind_direction": 270, "wind_speed": 20,
+            },
+        });
+
+        let profile = read_profile(data.to_string().as_bytes()).unwrap();
+
+        assert!(profile.saved_holds.is_empty());
+        assert_eq!(profile.aircraft.flight_rules, "V");
+    }
+
+    #[test]
+    fn newer_files_are_refused() {
+        let mut value = to_file_value(&create_template_plan(), FileType::Plan).unwrap();
+        value["version"] = json!(PLAN_VERSION + 1);
+
+        let err = read_plan(value.to_string().as_bytes()).unwrap_err();
+
+        let KneeboardError::String(message) = err else {
+            panic!("unexpected error {err:?}");
+        };
+        assert!(message.contains("Update Kneeboard"), "{message}");
+    }
+
+    #[test]
+    fn saved_plans_round_trip() {
+        let value = to_file_value(&create_template_plan(), FileType::Plan).unwrap();
+        assert_eq!(value["file_type"], "plan");
+        assert_eq!(value["version"], PLAN_VERSION);
+
+        assert!(read_plan(value.to_string().as_bytes()).is_ok());
+        assert!(read_profile(value.to_string().as_bytes()).is_err());
+    }
+}
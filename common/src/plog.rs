@@ -0,0 +1,230 @@
+use std::fmt::Write;
+
+use core::planner::{return_legs, route_name};
+use core::route::{calc_legs, convert_leg, Leg};
+use core::upper_winds::{plan_upper_winds, UpperWinds};
+use definition::Plan;
+
+use crate::map::escape_xml;
+
+/// Column headings, in the order of the PDF plog. The plog's From and To share a cell, and
+/// Total is the running time the PDF carries between pages.
+pub const PLOG_COLUMNS: [&str; 16] = [
+    "From", "To", "Safe", "Plan", "Spd", "Track", "Dist", "Wind", "G/S", "HD(T)", "HD(M)", "Time",
+    "Total", "S/C", "ETA", "ATA",
+];
+
+/// The computed legs of one direction of a route, as text ready for a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlogTable {
+    pub name: String,
+    pub rows: Vec<[String; 16]>,
+    /// Distance and time flown over the whole table.
+    pub totals: [String; 16],
+}
+
+/// A table for each route then its return, as they are printed in the kneeboard.
+pub fn plog_tables(plan: &Plan) -> Vec<PlogTable> {
+    let winds = plan_upper_winds(plan);
+    let mut tables = vec![];
+    for (idx, route) in plan.routes.iter().enumerate() {
+        let name = route_name(route, idx);
+        let legs: Vec<Leg> = route.legs.iter().map(convert_leg).collect();
+        tables.push(plog_table(name.clone(), &legs, winds.as_ref()));
+        tables.push(plog_table(
+            format!("{name} (return)"),
+            &return_legs(legs),
+            winds.as_ref(),
+        ));
+    }
+    tables
+}
+
+fn plog_table(name: String, legs: &[Leg], winds: Option<&UpperWinds>) -> PlogTable {
+    let calc_legs = calc_legs(legs, winds);
+
+    let rows = calc_legs
+        .iter()
+        .map(|(leg, calc)| {
+            let (from, to) = leg.name.clone();
+            [
+                from,
+                to,
+                leg.safe.clone(),
+                leg.planned.clone(),
+                whole(leg.speed),
+                leg.course.as_heading(),
+                whole(leg.distance),
+                format!(
+                    "{}@{}",
+                    calc.wind_direction.as_heading(),
+                    whole(calc.wind_speed)
+                ),
+                whole(calc.ground_speed),
+                calc.heading.as_heading(),
+                calc.heading_magnetic.as_heading(),
+                whole(calc.time),
+                whole(calc.total),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]
+        })
+        .collect();
+
+    let mut totals: [String; 16] = Default::default();
+    totals[0] = "Total".to_owned();
+    totals[6] = whole(legs.iter().map(|leg| leg.distance).sum());
+    totals[11] = whole(calc_legs.last().map_or(0., |(_, calc)| calc.total));
+
+    PlogTable { name, rows, totals }
+}
+
+/// Rounds to a whole number, as the PDF plog shows values.
+fn whole(value: f64) -> String {
+    (value.round() as i32).to_string()
+}
+
+/// The plog of every route as CSV, with the route name in the first column of each row.
+pub fn write_csv(plan: &Plan) -> String {
+    let mut csv = String::new();
+    let header: Vec<String> = ["Route"]
+        .iter()
+        .chain(&PLOG_COLUMNS)
+        .map(|heading| csv_field(heading))
+        .collect();
+    let _ = writeln!(csv, "{}", header.join(","));
+
+    for table in plog_tables(plan) {
+        for row in table.rows.iter().chain([&table.totals]) {
+            let fields: Vec<String> = [&table.name]
+                .into_iter()
+                .chain(row)
+                .map(|field| csv_field(field))
+                .collect();
+            let _ = writeln!(csv, "{}", fields.join(","));
+        }
+    }
+    csv
+}
+
+/// Quotes fields holding separators, quotes or line breaks.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// The plog of every route as Markdown, a heading and table for each.
+pub fn write_markdown(plan: &Plan) -> String {
+    let mut markdown = String::new();
+    for table in plog_tables(plan) {
+        let _ = writeln!(markdown, "## {}\n", table.name.trim());
+        let _ = writeln!(markdown, "| {} |", PLOG_COLUMNS.join(" | "));
+        let _ = writeln!(markdown, "|{}", "---|".repeat(PLOG_COLUMNS.len()));
+        for row in &table.rows {
+            markdown_row(&mut markdown, row);
+        }
+        let mut totals = table.totals.clone();
+        for value in totals.iter_mut().filter(|value| !value.is_empty()) {
+            *value = format!("**{value}**");
+        }
+        markdown_row(&mut markdown, &totals);
+        markdown += "\n";
+    }
+    markdown
+}
+
+fn markdown_row(markdown: &mut String, row: &[String]) {
+    let cells: Vec<String> = row
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+        .collect();
+    let _ = writeln!(markdown, "| {} |", cells.join(" | "));
+}
+
+/// The plog of every route as a standalone HTML page, a table for each.
+pub fn write_html(plan: &Plan) -> String {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>Kneeboard Notes</title>\n<style>\n",
+        "body { font-family: sans-serif; }\n",
+        "table { border-collapse: collapse; margin-bottom: 2em; }\n",
+        "th, td { border: 1px solid #888; padding: 2px 6px; }\n",
+        "tfoot td { font-weight: bold; }\n",
+        "</style>\n</head>\n<body>\n"
+    ));
+
+    for table in plog_tables(plan) {
+        let _ = writeln!(html, "<h2>{}</h2>\n<table>", escape_xml(&table.name));
+        html += "<thead>\n";
+        html_row(&mut html, "th", PLOG_COLUMNS);
+        html += "</thead>\n<tbody>\n";
+        for row in &table.rows {
+            html_row(&mut html, "td", row);
+        }
+        html += "</tbody>\n<tfoot>\n";
+        html_row(&mut html, "td", &table.totals);
+        html += "</tfoot>\n</table>\n";
+    }
+
+    html += "</body>\n</html>\n";
+    html
+}
+
+fn html_row(html: &mut String, tag: &str, row: impl IntoIterator<Item = impl AsRef<str>>) {
+    *html += "<tr>";
+    for cell in row {
+        let _ = write!(html, "<{tag}>{}</{tag}>", escape_xml(cell.as_ref()));
+    }
+    *html += "</tr>\n";
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create_template_plan;
+
+    use super::{plog_tables, write_csv, write_html, write_markdown, PLOG_COLUMNS};
+
+    #[test]
+    fn tables_follow_the_kneeboard() {
+        let plan = create_template_plan();
+
+        let tables = plog_tables(&plan);
+
+        assert_eq!(tables.len(), 2 * plan.routes.len());
+        let outbound = &tables[0];
+        let back = &tables[1];
+        assert_eq!(back.name, format!("{} (return)", outbound.name));
+        assert_eq!(back.rows[0][0], outbound.rows.last().unwrap()[1]);
+
+        let total = outbound.rows.last().unwrap()[12].clone();
+        assert_eq!(outbound.totals[11], total);
+        let distance: i32 = outbound
+            .rows
+            .iter()
+            .map(|row| row[6].parse::<i32>().unwrap())
+            .sum();
+        assert_eq!(outbound.totals[6], distance.to_string());
+    }
+
+    #[test]
+    fn every_format_has_every_column() {
+        let mut plan = create_template_plan();
+        plan.routes[0].name = "Out, \"there\" & <back>".to_owned();
+
+        let csv = write_csv(&plan);
+        let markdown = write_markdown(&plan);
+        let html = write_html(&plan);
+
+        let header = csv.lines().next().unwrap();
+        assert_eq!(header.split(',').count(), PLOG_COLUMNS.len() + 1);
+        assert!(csv.contains("\"Out, \"\"there\"\" & <back>\","));
+        assert!(markdown.contains("## Out, \"there\" & <back>\n"));
+        assert!(markdown.contains("| **Total** |"));
+        assert!(html.contains("<h2>Out, &quot;there&quot; &amp; &lt;back&gt;</h2>"));
+        assert_eq!(html.matches("<th>").count(), 2 * PLOG_COLUMNS.len());
+    }
+}
@@ -0,0 +1,223 @@
+use definition::{Plan, ProfileConfig};
+use schemars::{schema_for, JsonSchema};
+use serde_json::{json, Value};
+
+use crate::migration::FileType;
+
+/// JSON Schema for plan files as saved by the app.
+pub fn plan_schema() -> Value {
+    file_schema::<Plan>(FileType::Plan)
+}
+
+/// JSON Schema for profile files as saved by the app.
+pub fn profile_schema() -> Value {
+    file_schema::<ProfileConfig>(FileType::Profile)
+}
+
+/// Schema for files of `file_type`.
+pub fn schema(file_type: FileType) -> Value {
+    match file_type {
+        FileType::Plan => plan_schema(),
+        FileType::Profile => profile_schema(),
+    }
+}
+
+/// The schema of `T`, adding the `file_type` tag written alongside its fields and capping
+/// `version` at the newest format this build reads.
+fn file_schema<T: JsonSchema>(file_type: FileType) -> Value {
+    let mut schema = schema_for!(T).to_value();
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        if let Some(version) = properties.get_mut("version") {
+            version["maximum"] = json!(file_type.version());
+        }
+        properties.insert(
+            "file_type".to_owned(),
+            json!({
+                "description": "Kind of file, added when saved from the app.",
+                "const": file_type.tag(),
+            }),
+        );
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use definition::{PlanImage, ProfileConfig, SavedHold, SavedRoute};
+    use serde_json::{Map, Value};
+
+    use crate::create_template_hold;
+    use crate::create_template_plan;
+    use crate::create_template_route;
+    use crate::migration::{to_file_value, FileType};
+
+    use super::schema;
+
+    /// Checks `value` against the parts of JSON Schema the generated schemas use. Keys
+    /// missing from a schema's properties are refused, so fields serde writes but the
+    /// schema leaves out are caught.
+    fn check(value: &Value, schema: &Value, root: &Value, path: &str) -> Result<(), String> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.trim_start_matches("#/$defs/");
+            return check(value, &root["$defs"][name], root, path);
+        }
+
+        if let Some(options) = schema.get("oneOf").and_then(Value::as_array) {
+            let matches = options
+                .iter()
+                .filter(|option| check(value, option, root, path).is_ok())
+                .count();
+            return match matches {
+                1 => Ok(()),
+                _ => Err(format!("{path}: matches {matches} of oneOf")),
+            };
+        }
+
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                return Err(format!("{path}: {value} is not {expected}"));
+            }
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                return Err(format!("{path}: {value} is not one of {allowed:?}"));
+            }
+        }
+
+        if let Some(types) = schema.get("type") {
+            let kind = match value {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(number) if number.is_f64() => "number",
+                Value::Number(_) => "integer",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            let allowed = match types {
+                Value::Array(types) => types.contains(&Value::from(kind)),
+                types => types == kind,
+            };
+            if !allowed {
+                return Err(format!("{path}: {kind} is not {types}"));
+            }
+        }
+
+        if let (Some(number), Some(maximum)) = (value.as_f64(), schema.get("maximum")) {
+            if number > maximum.as_f64().unwrap_or(f64::MAX) {
+                return Err(format!("{path}: {number} is over {maximum}"));
+            }
+        }
+
+        match value {
+            Value::Object(obj) => check_object(obj, schema, root, path),
+            Value::Array(items) => items.iter().enumerate().try_for_each(|(idx, item)| {
+                check(item, &schema["items"], root, &format!("{path}[{idx}]"))
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_object(
+        obj: &Map<String, Value>,
+        schema: &Value,
+        root: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        let empty = Map::new();
+        let properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+
+        for (key, value) in obj {
+            let Some(property) = properties.get(key) else {
+                return Err(format!("{path}: {key} is not in the schema"));
+            };
+            check(value, property, root, &format!("{path}.{key}"))?;
+        }
+
+        let required = schema.get("required").and_then(Value::as_array);
+        for key in required.into_iter().flatten().filter_map(Value::as_str) {
+            if !obj.contains_key(key) {
+                return Err(format!("{path}: {key} is required"));
+            }
+        }
+        Ok(())
+    }
+
+    fn checked_in(file_type: FileType) -> PathBuf {
+        let name = format!("{}.schema.json", file_type.tag());
+        [env!("CARGO_MANIFEST_DIR"), "..", "schema", &name]
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn checked_in_schemas_are_current() {
+        for file_type in [FileType::Plan, FileType::Profile] {
+            let path = checked_in(file_type);
+            let saved: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+
+            assert!(
+                saved == schema(file_type),
+                "{} is out of date, regenerate it with \
+                 `cargo run -p kneeboard -- schema {} -o schema/{}.schema.json`",
+                path.display(),
+                file_type.tag(),
+                file_type.tag(),
+            );
+        }
+    }
+
+    #[test]
+    fn saved_plans_conform() {
+        let mut plan = create_template_plan();
+        let mut hold = create_template_hold();
+        hold.rotate_to_track = true;
+        plan.holds = vec![hold];
+        plan.images = vec![PlanImage {
+            caption: "Airfield".to_owned(),
+            data: String::new(),
+            after_route: Some(0),
+        }];
+
+        let value = to_file_value(&plan, FileType::Plan).unwrap();
+        let schema = schema(FileType::Plan);
+
+        check(&value, &schema, &schema, "plan").unwrap();
+    }
+
+    #[test]
+    fn saved_profiles_conform() {
+        let route = create_template_route();
+        let profile = ProfileConfig {
+            aircraft_registrations: vec!["G-ABCD".to_owned()],
+            saved_routes: vec![SavedRoute {
+                name: "Local".to_owned(),
+                waypoints: "EGLF EGTF".to_owned(),
+                legs: route.legs,
+                notes: route.notes,
+            }],
+            saved_holds: vec![SavedHold::default()],
+            ..ProfileConfig::default()
+        };
+
+        let value = to_file_value(&profile, FileType::Profile).unwrap();
+        let schema = schema(FileType::Profile);
+
+        check(&value, &schema, &schema, "profile").unwrap();
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut value = to_file_value(&create_template_plan(), FileType::Plan).unwrap();
+        value["version"] = Value::from(FileType::Plan.version() + 1);
+        let schema = schema(FileType::Plan);
+
+        assert!(check(&value, &schema, &schema, "plan").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use definition::Coordinate;
+use roxmltree::{Document, Node};
+
+use crate::geo::Geo;
+use crate::import::{children, report_unknown, Imported, ImportedRoute, Waypoint};
+use crate::KneeboardError;
+
+const PLANNER_ELEMENTS: [&str; 1] = ["PrimaryRoute"];
+const ROUTE_ELEMENTS: [&str; 1] = ["RoutePoint"];
+
+/// Reads a SkyDemon `.flightplan`. The route starts at its `Start` location, which has no
+/// name so is named by its coordinates, and the `Level` of each point becomes the planned
+/// altitude of the leg to it. Speeds and winds aren't saved in the file.
+pub fn parse_skydemon(text: &str) -> Result<Imported, KneeboardError> {
+    let doc = Document::parse(text)
+        .map_err(|err| KneeboardError::String(format!("Not a SkyDemon flight plan: {err}")))?;
+    let planner = doc.root_element();
+    if planner.tag_name().name() != "DivelementsFlightPlanner" {
+        return Err(KneeboardError::String(format!(
+            "Not a SkyDemon flight plan, found <{}>",
+            planner.tag_name().name()
+        )));
+    }
+
+    let mut imported = Imported::default();
+    report_unknown(planner, &PLANNER_ELEMENTS, &mut imported);
+
+    let route = children(planner, "PrimaryRoute").next().ok_or_else(|| {
+        KneeboardError::String("The flight plan has no <PrimaryRoute>".to_owned())
+    })?;
+    report_unknown(route, &ROUTE_ELEMENTS, &mut imported);
+
+    let start = location(route, "Start")?;
+    let mut waypoints = vec![Waypoint::new(start.to_icao(), start)];
+    let mut level = altitude(route, &mut imported);
+
+    for point in children(route, "RoutePoint") {
+        let position = location(point, "Location")?;
+        let name = match point.attribute("Name").map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_owned(),
+            _ => position.to_icao(),
+        };
+        // Points without a level keep the one set before them
+        level = altitude(point, &mut imported).or(level);
+
+        waypoints.push(Waypoint {
+            altitude: level,
+            ..Waypoint::new(name, position)
+        });
+    }
+
+    if waypoints.len() < 2 {
+        return Err(KneeboardError::String(
+            "The flight plan has no route points".to_owned(),
+        ));
+    }
+
+    imported.routes.push(ImportedRoute {
+        name: String::new(),
+        waypoints,
+    });
+    Ok(imported)
+}
+
+/// The `Level` of `node` in feet. Flight levels can't be planned in the leg table, so are
+/// reported rather than converted.
+fn altitude(node: Node, imported: &mut Imported) -> Option<i64> {
+    let level = node.attribute("Level")?.trim();
+    match level.parse() {
+        Ok(feet) => Some(feet),
+        Err(_) => {
+            imported.unsupported(format!("Level {level}"));
+            None
+        }
+    }
+}
+
+fn location(node: Node, attribute: &str) -> Result<Coordinate, KneeboardError> {
+    node.attribute(attribute)
+        .and_then(parse_location)
+        .ok_or_else(|| {
+            KneeboardError::String(format!(
+                "<{}> has no valid {attribute} location",
+                node.tag_name().name()
+            ))
+        })
+}
+
+/// Parses SkyDemon's degrees, minutes and seconds, such as `N513133.00 W0004640.00`.
+fn parse_location(text: &str) -> Option<Coordinate> {
+    let (lat, lon) = text.trim().split_once(' ')?;
+    let position = Coordinate::new(dms(lat, 2, ('N', 'S'))?, dms(lon.trim(), 3, ('E', 'W'))?);
+    position.is_valid().then_some(position)
+}
+
+/// A hemisphere letter then `degree_digits` digits of degrees, two of minutes and seconds
+/// with optional decimals.
+fn dms(text: &str, degree_digits: usize, (positive, negative): (char, char)) -> Option<f64> {
+    let mut chars = text.chars();
+    let sign = match chars.next()? {
+        hemisphere if hemisphere == positive => 1.,
+        hemisphere if hemisphere == negative => -1.,
+        _ => return None,
+    };
+    let digits = chars.as_str();
+    if digits.len() < degree_digits + 4 || !digits.is_ascii() {
+        return None;
+    }
+
+    let degrees: f64 = digits[..degree_digits].parse().ok()?;
+    let minutes: f64 = digits[degree_digits..degree_digits + 2].parse().ok()?;
+    let seconds: f64 = digits[degree_digits + 2..].parse().ok()?;
+    Some(sign * (degrees + minutes / 60. + seconds / 3600.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_location, parse_skydemon};
+
+    #[test]
+    fn locations_are_degrees_minutes_seconds() {
+        let position = parse_location("N513133.00 W0004640.50").unwrap();
+
+        assert!((position.latitude - 51.525833).abs() < 1e-6);
+        assert!((position.longitude + 0.777917).abs() < 1e-6);
+        assert!(parse_location("E513133.00 W0004640.00").is_none());
+        assert!(parse_location("N51 W000").is_none());
+        assert!(parse_location("N5é1234 W0004640").is_none());
+    }
+
+    #[test]
+    fn route_points_and_levels() {
+        let plan = r#"<?xml version="1.0" encoding="utf-8"?>
+            <DivelementsFlightPlanner>
+              <PrimaryRoute CourseType="GreatCircle" Start="N511633.00 W0004634.00" Level="2000" Rules="Vfr">
+                <RoutePoint Type="Normal" Name="OCKHAM" Location="N511818.00 W0002650.00" Level="2400" />
+                <RoutePoint Type="Normal" Name="EGTF" Location="N512053.00 W0003332.00" />
+              </PrimaryRoute>
+              <Aircraft Name="G-ABCD" />
+            </DivelementsFlightPlanner>"#;
+
+        let imported = parse_skydemon(plan).unwrap();
+
+        let waypoints = &imported.routes[0].waypoints;
+        let names: Vec<&str> = waypoints.iter().map(|point| point.name.as_str()).collect();
+        assert_eq!(names, ["5117N00047W", "OCKHAM", "EGTF"]);
+        assert_eq!(waypoints[1].altitude, Some(2400));
+        assert_eq!(waypoints[2].altitude, Some(2400));
+        assert_eq!(
+            imported.unsupported,
+            ["<Aircraft> in <DivelementsFlightPlanner>"]
+        );
+    }
+}
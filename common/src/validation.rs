@@ -0,0 +1,317 @@
+use std::fmt;
+
+use core::upper_winds::parse_upper_winds;
+use core::weather::{parse_report, runway_heading};
+use definition::{Diversion, Hold, Leg, Plan, Velocity, WeatherReport};
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Odd but drawable, such as a leg that doesn't start where the previous one ended.
+    Warning,
+    /// The calculations for the field are meaningless.
+    Error,
+}
+
+/// A problem with one field of a plan.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Where the field sits in the plan JSON, such as `routes[0].legs[2].distance`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}: {}", self.path, self.message)
+    }
+}
+
+/// Checks the fields of a plan that would otherwise render as nonsense, in plan order.
+pub fn validate_plan(plan: &Plan) -> Vec<Diagnostic> {
+    let mut diagnostics = Diagnostics::default();
+
+    for (idx, diversion) in plan.diversions.iter().enumerate() {
+        diagnostics.diversion(&format!("diversions[{idx}]"), diversion);
+    }
+
+    for (route_idx, route) in plan.routes.iter().enumerate() {
+        let mut previous: Option<&Leg> = None;
+        for (leg_idx, leg) in route.legs.iter().enumerate() {
+            let path = format!("routes[{route_idx}].legs[{leg_idx}]");
+            diagnostics.leg(&path, leg, previous);
+            previous = Some(leg);
+        }
+    }
+
+    for (idx, hold) in plan.holds.iter().enumerate() {
+        diagnostics.hold(&format!("holds[{idx}]"), hold);
+    }
+
+    for (idx, report) in plan.weather.iter().enumerate() {
+        diagnostics.weather(&format!("weather[{idx}]"), report);
+    }
+
+    if !plan.upper_winds.trim().is_empty() {
+        if let Err(e) = parse_upper_winds(&plan.upper_winds) {
+            diagnostics.error("upper_winds".to_owned(), e);
+        }
+    }
+
+    diagnostics.0
+}
+
+/// Whether any of `diagnostics` should stop the plan being used.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+#[derive(Default)]
+struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    fn push(&mut self, severity: Severity, path: String, message: String) {
+        self.0.push(Diagnostic {
+            severity,
+            path,
+            message,
+        });
+    }
+
+    fn error(&mut self, path: String, message: String) {
+        self.push(Severity::Error, path, message);
+    }
+
+    fn leg(&mut self, path: &str, leg: &Leg, previous: Option<&Leg>) {
+        if let Some(previous) = previous {
+            if !same_waypoint(&previous.to, &leg.from) {
+                self.push(
+                    Severity::Warning,
+                    format!("{path}.from"),
+                    format!(
+                        "Starts at '{}' but the previous leg ends at '{}'",
+                        leg.from, previous.to
+                    ),
+                );
+            }
+        }
+
+        if leg.distance <= 0 {
+            self.error(
+                format!("{path}.distance"),
+                format!("Distance must be more than zero, not {}", leg.distance),
+            );
+        }
+        self.track(&format!("{path}.course"), "Course", leg.course);
+        self.variation(&format!("{path}.variation"), leg.variation);
+        self.speeds(
+            &format!("{path}.speed"),
+            leg.speed,
+            (&format!("{path}.wind_direction"), leg.wind_direction),
+            (&format!("{path}.wind_speed"), leg.wind_speed),
+        );
+    }
+
+    fn hold(&mut self, path: &str, hold: &Hold) {
+        self.track(
+            &format!("{path}.in_bound_track"),
+            "Inbound track",
+            hold.in_bound_track,
+        );
+        self.variation(&format!("{path}.variation"), hold.variation);
+        self.velocity(path, hold.aircraft_speed, &hold.wind);
+    }
+
+    fn diversion(&mut self, path: &str, diversion: &Diversion) {
+        self.variation(&format!("{path}.variation"), diversion.variation);
+        self.velocity(path, diversion.aircraft_speed, &diversion.wind);
+    }
+
+    fn weather(&mut self, path: &str, report: &WeatherReport) {
+        if let Err(e) = parse_report(&report.text) {
+            self.error(format!("{path}.text"), e);
+        }
+
+        for (idx, runway) in report.runways.iter().enumerate() {
+            if runway_heading(runway).is_none() {
+                self.push(
+                    Severity::Warning,
+                    format!("{path}.runways[{idx}]"),
+                    format!("'{runway}' isn't a runway designator such as 07 or 25L"),
+                );
+            }
+        }
+    }
+
+    fn velocity(&mut self, path: &str, aircraft_speed: i64, wind: &Velocity) {
+        self.speeds(
+            &format!("{path}.aircraft_speed"),
+            aircraft_speed,
+            (&format!("{path}.wind.angle"), wind.angle),
+            (&format!("{path}.wind.speed"), wind.speed),
+        );
+    }
+
+    /// Checks a TAS and the wind blowing against it.
+    fn speeds(&mut self, speed_path: &str, speed: i64, direction: (&str, i64), wind: (&str, i64)) {
+        let (direction_path, direction) = direction;
+        let (wind_path, wind) = wind;
+
+        if speed <= 0 {
+            self.error(
+                speed_path.to_owned(),
+                format!("TAS must be more than zero, not {speed}"),
+            );
+        }
+
+        // 360 is how north winds are reported
+        if !(0..=360).contains(&direction) {
+            self.error(
+                direction_path.to_owned(),
+                format!("Wind direction must be between 0 and 360, not {direction}"),
+            );
+        }
+
+        if wind < 0 {
+            self.error(
+                wind_path.to_owned(),
+                format!("Wind speed can't be negative, not {wind}"),
+            );
+        } else if speed > 0 && wind >= speed {
+            self.error(
+                wind_path.to_owned(),
+                format!("Wind speed {wind} kt must be less than the TAS of {speed} kt"),
+            );
+        }
+    }
+
+    fn track(&mut self, path: &str, name: &str, track: i64) {
+        if !(0..360).contains(&track) {
+            self.error(
+                path.to_owned(),
+                format!("{name} must be between 0 and 359, not {track}"),
+            );
+        }
+    }
+
+    fn variation(&mut self, path: &str, variation: i64) {
+        if !(-180..=180).contains(&variation) {
+            self.error(
+                path.to_owned(),
+                format!("Variation must be between -180 and 180, not {variation}"),
+            );
+        }
+    }
+}
+
+/// Waypoint names as typed, ignoring case and surrounding space.
+fn same_waypoint(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_template_hold, create_template_plan};
+
+    use definition::WeatherReport;
+
+    use super::{has_errors, validate_plan, Severity};
+
+    #[test]
+    fn template_plan_is_valid() {
+        assert_eq!(validate_plan(&create_template_plan()), vec![]);
+    }
+
+    #[test]
+    fn bad_leg_fields_are_reported_by_path() {
+        let mut plan = create_template_plan();
+        let legs = &mut plan.routes[0].legs;
+        legs[0].distance = 0;
+        legs[1].course = 360;
+        legs[1].wind_speed = legs[1].speed + 5;
+        legs[1].from = "Elsewhere".to_owned();
+
+        let diagnostics = validate_plan(&plan);
+        let paths: Vec<(Severity, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            paths,
+            [
+                (Severity::Error, "routes[0].legs[0].distance"),
+                (Severity::Warning, "routes[0].legs[1].from"),
+                (Severity::Error, "routes[0].legs[1].course"),
+                (Severity::Error, "routes[0].legs[1].wind_speed"),
+            ]
+        );
+        assert!(has_errors(&diagnostics));
+    }
+
+    #[test]
+    fn hold_speed_must_be_positive() {
+        let mut plan = create_template_plan();
+        let mut hold = create_template_hold();
+        hold.aircraft_speed = -90;
+        hold.wind.speed = 20;
+        plan.holds = vec![hold];
+
+        let diagnostics = validate_plan(&plan);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "holds[0].aircraft_speed");
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: holds[0].aircraft_speed: TAS must be more than zero, not -90"
+        );
+    }
+
+    #[test]
+    fn weather_must_decode() {
+        let mut plan = create_template_plan();
+        plan.weather = vec![
+            WeatherReport {
+                text: "METAR EGKA 281150Z 27012KT 9999 FEW030 15/09 Q1013".to_owned(),
+                runways: vec!["20".to_owned(), "Grass".to_owned()],
+            },
+            WeatherReport {
+                text: "".to_owned(),
+                runways: vec![],
+            },
+        ];
+
+        let diagnostics = validate_plan(&plan);
+        let paths: Vec<(Severity, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            paths,
+            [
+                (Severity::Warning, "weather[0].runways[1]"),
+                (Severity::Error, "weather[1].text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn upper_winds_must_read() {
+        let mut plan = create_template_plan();
+        plan.upper_winds = "270 15 +03".to_owned();
+
+        let diagnostics = validate_plan(&plan);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "upper_winds");
+    }
+}
@@ -0,0 +1,604 @@
+use std::collections::HashMap;
+
+use definition::{Coordinate, Route};
+use roxmltree::{Document, Node};
+use serde_json::Value;
+
+use crate::import::{child_text, children, set_positions};
+use crate::KneeboardError;
+
+/// Least similarity, from 0 to 1, for a name to match a query that isn't exact.
+const FUZZY_THRESHOLD: f64 = 0.75;
+
+/// OpenAIP gives elevations in metres unless the unit says otherwise.
+const FEET_PER_METRE: f64 = 3.28084;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaypointKind {
+    Airfield,
+    /// Visual reporting point.
+    Vrp,
+    Navaid,
+    Other,
+}
+
+impl WaypointKind {
+    /// Reads the type column of a CSV file or the category of an OpenAIP entry.
+    fn from_text(text: &str) -> WaypointKind {
+        let text = text.trim().to_lowercase();
+        if text == "ad"
+            || [
+                "airport",
+                "airfield",
+                "aerodrome",
+                "heliport",
+                "airstrip",
+                "glider",
+            ]
+            .iter()
+            .any(|kind| text.contains(kind))
+        {
+            WaypointKind::Airfield
+        } else if text == "vrp" || text.contains("reporting") {
+            WaypointKind::Vrp
+        } else if ["navaid", "vor", "ndb", "dme", "tacan"]
+            .iter()
+            .any(|kind| text.contains(kind))
+        {
+            WaypointKind::Navaid
+        } else {
+            WaypointKind::Other
+        }
+    }
+}
+
+/// A radio frequency of a waypoint, such as `TOWER 122.500`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frequency {
+    pub name: String,
+    /// As written in the source, so trailing zeros of channels are kept.
+    pub megahertz: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DatabaseEntry {
+    /// ICAO location indicator of an airfield or identifier of a navaid.
+    pub ident: Option<String>,
+    pub name: String,
+    pub kind: WaypointKind,
+    pub position: Coordinate,
+    /// Elevation in feet.
+    pub elevation: Option<i64>,
+    pub frequencies: Vec<Frequency>,
+}
+
+/// Airfields, reporting points and navaids loaded from local files, indexed by identifier
+/// and name.
+#[derive(Clone, Debug, Default)]
+pub struct WaypointDatabase {
+    entries: Vec<DatabaseEntry>,
+    by_ident: HashMap<String, Vec<usize>>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl WaypointDatabase {
+    pub fn new(entries: Vec<DatabaseEntry>) -> Self {
+        let mut database = WaypointDatabase::default();
+        database.extend(entries);
+        database
+    }
+
+    /// Reads a CSV, OpenAIP JSON or OpenAIP XML file, chosen by the extension of
+    /// `file_name` or, failing that, by what the text starts with.
+    pub fn load(file_name: &str, text: &str) -> Result<Self, KneeboardError> {
+        let file_name = file_name.to_lowercase();
+        let start = text.trim_start();
+        let entries = if file_name.ends_with(".json") || start.starts_with(['{', '[']) {
+            parse_openaip_json(text)?
+        } else if file_name.ends_with(".xml")
+            || file_name.ends_with(".aip")
+            || start.starts_with('<')
+        {
+            parse_openaip_xml(text)?
+        } else {
+            parse_csv(text)?
+        };
+        Ok(WaypointDatabase::new(entries))
+    }
+
+    /// Adds entries, such as those of another file, to the index.
+    pub fn extend(&mut self, entries: Vec<DatabaseEntry>) {
+        for entry in entries {
+            let idx = self.entries.len();
+            if let Some(ident) = &entry.ident {
+                self.by_ident.entry(normalise(ident)).or_default().push(idx);
+            }
+            self.by_name
+                .entry(normalise(&entry.name))
+                .or_default()
+                .push(idx);
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn entries(&self) -> &[DatabaseEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry for an identifier or name, as typed in a route, ignoring case and
+    /// punctuation. Identifiers win over names. Nothing close is guessed at, as a code one
+    /// letter out is usually a different place; `search` gives those as suggestions.
+    pub fn lookup(&self, query: &str) -> Option<&DatabaseEntry> {
+        let key = normalise(query);
+        let exact = self.by_ident.get(&key).or_else(|| self.by_name.get(&key))?;
+        exact.first().map(|&idx| &self.entries[idx])
+    }
+
+    /// Entries whose identifier or name is near enough to `query`, best first, with how
+    /// similar they are from 0 to 1. For suggesting what a name that wasn't found meant.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(&DatabaseEntry, f64)> {
+        let key = normalise(query);
+        if key.is_empty() {
+            return vec![];
+        }
+
+        let mut found: Vec<(&DatabaseEntry, f64)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let ident = entry
+                    .ident
+                    .as_deref()
+                    .map_or(0., |ident| similarity(&key, &normalise(ident)));
+                let score = ident.max(similarity(&key, &normalise(&entry.name)));
+                (score >= FUZZY_THRESHOLD).then_some((entry, score))
+            })
+            .collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1));
+        found.truncate(limit);
+        found
+    }
+
+    /// Sets the positions of the legs of `route` whose ends are both found, along with
+    /// their course and distance, returning the names that weren't found.
+    pub fn fill_route(&self, route: &mut Route) -> Vec<String> {
+        let mut missing: Vec<String> = vec![];
+        for leg in &mut route.legs {
+            let from = self.lookup(&leg.from);
+            let to = self.lookup(&leg.to);
+            for (name, entry) in [(&leg.from, from), (&leg.to, to)] {
+                if entry.is_none() && !missing.contains(name) {
+                    missing.push(name.clone());
+                }
+            }
+            if let (Some(from), Some(to)) = (from, to) {
+                set_positions(leg, from.position, to.position);
+            }
+        }
+        missing
+    }
+}
+
+/// Upper case letters and digits, with runs of anything else as a single space.
+fn normalise(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_uppercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// How alike a query is to a name, from 0 to 1. A query that starts the name or one of its
+/// words, such as `FARNBOROUGH` for `FARNBOROUGH EGLF`, scores almost as high as an exact
+/// match; otherwise it is the edit distance relative to the longer of the two.
+fn similarity(query: &str, name: &str) -> f64 {
+    if query == name {
+        return 1.;
+    }
+    if query.len() >= 3 && name.split(' ').any(|word| word.starts_with(query)) {
+        return 0.9;
+    }
+
+    let longest = query.chars().count().max(name.chars().count());
+    1. - edit_distance(query, name) as f64 / longest as f64
+}
+
+/// Levenshtein distance in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Reads a CSV file with a header row. Columns are found by name: `ident` (or `icao`,
+/// `code`, `id`), `name`, `type`, `lat` and `lon` (or `latitude`, `longitude`), `elevation`
+/// in feet and `frequencies` as `NAME 123.450` separated by semicolons.
+pub fn parse_csv(text: &str) -> Result<Vec<DatabaseEntry>, KneeboardError> {
+    let mut records = csv_records(text).into_iter();
+    let header: Vec<String> = records
+        .next()
+        .ok_or_else(|| KneeboardError::String("The waypoint file is empty".to_owned()))?
+        .iter()
+        .map(|heading| heading.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+
+    let ident = column(&["ident", "icao", "code", "id", "identifier"]);
+    let name = column(&["name"]);
+    let kind = column(&["type", "kind", "category"]);
+    let (Some(lat), Some(lon)) = (
+        column(&["lat", "latitude"]),
+        column(&["lon", "lng", "long", "longitude"]),
+    ) else {
+        return Err(KneeboardError::String(
+            "The waypoint file needs lat and lon columns".to_owned(),
+        ));
+    };
+    let elevation = column(&["elevation", "elev", "elevation_ft"]);
+    let frequencies = column(&["frequencies", "frequency", "freq"]);
+
+    let mut entries = vec![];
+    for (line, record) in records.enumerate() {
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let field = |idx: Option<usize>| {
+            idx.and_then(|idx| record.get(idx))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+
+        let coordinate = |idx| field(Some(idx)).and_then(|value| value.parse::<f64>().ok());
+        let position = match (coordinate(lat), coordinate(lon)) {
+            (Some(lat), Some(lon)) => Coordinate::new(lat, lon),
+            _ => {
+                return Err(KneeboardError::String(format!(
+                    "Row {} of the waypoint file has no valid lat and lon",
+                    line + 2
+                )))
+            }
+        };
+        let ident = field(ident).map(str::to_owned);
+        let Some(name) = field(name).or(ident.as_deref()).map(str::to_owned) else {
+            continue;
+        };
+
+        entries.push(DatabaseEntry {
+            ident,
+            name,
+            kind: field(kind).map_or(WaypointKind::Other, WaypointKind::from_text),
+            position,
+            elevation: field(elevation).and_then(|value| value.parse::<f64>().ok().map(feet)),
+            frequencies: field(frequencies)
+                .map(|value| value.split(';').filter_map(parse_frequency).collect())
+                .unwrap_or_default(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Splits CSV text into records, allowing quoted fields with commas, quotes and line breaks.
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            ('\r', false) => {}
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// `TOWER 122.500`, or just the frequency.
+fn parse_frequency(text: &str) -> Option<Frequency> {
+    let text = text.trim();
+    let (name, megahertz) = text.rsplit_once(' ').unwrap_or(("", text));
+    megahertz.parse::<f64>().ok()?;
+    Some(Frequency {
+        name: name.trim().to_owned(),
+        megahertz: megahertz.to_owned(),
+    })
+}
+
+fn feet(value: f64) -> i64 {
+    value.round() as i64
+}
+
+/// Reads an OpenAIP JSON export of airports, reporting points or navaids: a list of
+/// entries, or an object holding them in `items`, each with a GeoJSON point.
+pub fn parse_openaip_json(text: &str) -> Result<Vec<DatabaseEntry>, KneeboardError> {
+    let value: Value = serde_json::from_str(text)?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(object) => match object.get("items") {
+            Some(Value::Array(items)) => items,
+            _ => {
+                return Err(KneeboardError::String(
+                    "Not an OpenAIP export, expected a list of items".to_owned(),
+                ))
+            }
+        },
+        _ => {
+            return Err(KneeboardError::String(
+                "Not an OpenAIP export, expected a list of items".to_owned(),
+            ))
+        }
+    };
+
+    let mut entries = vec![];
+    for (idx, item) in items.iter().enumerate() {
+        let text = |key: &str| {
+            item.get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+        };
+
+        let coordinates = item
+            .pointer("/geometry/coordinates")
+            .and_then(Value::as_array);
+        let position = match coordinates.map(|pair| pair.as_slice()) {
+            Some([lon, lat, ..]) => match (lat.as_f64(), lon.as_f64()) {
+                (Some(lat), Some(lon)) => Coordinate::new(lat, lon),
+                _ => return Err(openaip_point_error(idx)),
+            },
+            _ => return Err(openaip_point_error(idx)),
+        };
+
+        let ident = text("icaoCode").or_else(|| text("identifier"));
+        let Some(name) = text("name").or(ident.clone()) else {
+            continue;
+        };
+
+        let kind = if item.get("icaoCode").is_some() || item.get("runways").is_some() {
+            WaypointKind::Airfield
+        } else if item.get("identifier").is_some() {
+            WaypointKind::Navaid
+        } else if item.get("compulsory").is_some() || item.get("airports").is_some() {
+            WaypointKind::Vrp
+        } else {
+            WaypointKind::Other
+        };
+
+        // A unit of 1 is feet, otherwise metres
+        let elevation = item.get("elevation").and_then(|elevation| {
+            let value = elevation.get("value")?.as_f64()?;
+            match elevation.get("unit").and_then(Value::as_i64) {
+                Some(1) => Some(feet(value)),
+                _ => Some(feet(value * FEET_PER_METRE)),
+            }
+        });
+
+        let mut frequencies = vec![];
+        let listed = item.get("frequencies").and_then(Value::as_array);
+        for frequency in listed.into_iter().flatten().chain(item.get("frequency")) {
+            if let Some(megahertz) = frequency.get("value").and_then(Value::as_str) {
+                frequencies.push(Frequency {
+                    name: frequency
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_owned(),
+                    megahertz: megahertz.to_owned(),
+                });
+            }
+        }
+
+        entries.push(DatabaseEntry {
+            ident,
+            name,
+            kind,
+            position,
+            elevation,
+            frequencies,
+        });
+    }
+    Ok(entries)
+}
+
+fn openaip_point_error(idx: usize) -> KneeboardError {
+    KneeboardError::String(format!("OpenAIP item {} has no point geometry", idx + 1))
+}
+
+/// Reads an OpenAIP XML file, as `.aip` files were published. Every element with a
+/// `GEOLOCATION` is an entry, its kind coming from the element name, such as `AIRPORT`.
+pub fn parse_openaip_xml(text: &str) -> Result<Vec<DatabaseEntry>, KneeboardError> {
+    let doc = Document::parse(text)
+        .map_err(|err| KneeboardError::String(format!("Not an OpenAIP file: {err}")))?;
+
+    let mut entries = vec![];
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        let Some(location) = children(node, "GEOLOCATION").next() else {
+            continue;
+        };
+        let coordinate = |name| child_text(location, name).and_then(|value| value.parse().ok());
+        let position = match (coordinate("LAT"), coordinate("LON")) {
+            (Some(lat), Some(lon)) => Coordinate::new(lat, lon),
+            _ => {
+                return Err(KneeboardError::String(format!(
+                    "<{}> has no valid LAT and LON",
+                    node.tag_name().name()
+                )))
+            }
+        };
+
+        let ident = child_text(node, "ICAO").or_else(|| child_text(node, "ID"));
+        let Some(name) = child_text(node, "NAME").or(ident.clone()) else {
+            continue;
+        };
+
+        entries.push(DatabaseEntry {
+            ident,
+            name,
+            kind: WaypointKind::from_text(node.tag_name().name()),
+            position,
+            elevation: children(location, "ELEV").next().and_then(xml_elevation),
+            frequencies: children(node, "RADIO")
+                .filter_map(|radio| {
+                    Some(Frequency {
+                        name: child_text(radio, "TYPE").unwrap_or_default(),
+                        megahertz: child_text(radio, "FREQUENCY")?,
+                    })
+                })
+                .collect(),
+        });
+    }
+    Ok(entries)
+}
+
+fn xml_elevation(elev: Node) -> Option<i64> {
+    let value: f64 = elev.text()?.trim().parse().ok()?;
+    match elev.attribute("UNIT") {
+        Some(unit) if unit.eq_ignore_ascii_case("ft") => Some(feet(value)),
+        _ => Some(feet(value * FEET_PER_METRE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::DefaultLegValues;
+
+    use crate::create_leg_with_defaults;
+    use crate::create_template_route;
+
+    use super::{parse_openaip_json, parse_openaip_xml, WaypointDatabase, WaypointKind};
+
+    const CSV: &str = "ident,name,type,lat,lon,elevation,frequencies\n\
+        EGLF,Farnborough,Airport,51.2758,-0.7763,238,\"TOWER 122.500;RADAR 125.250\"\n\
+        EGTF,Fairoaks,Airfield,51.3481,-0.5589,80,\n\
+        OCK,Ockham,VOR,51.3050,-0.4473,,OCK 115.300\n\
+        ,Bagshot Mast,VRP,51.3600,-0.6800,,\n";
+
+    #[test]
+    fn codes_and_names_are_matched() {
+        let database = WaypointDatabase::load("uk.csv", CSV).unwrap();
+
+        assert_eq!(database.len(), 4);
+        let farnborough = database.lookup("eglf").unwrap();
+        assert_eq!(farnborough.name, "Farnborough");
+        assert_eq!(farnborough.elevation, Some(238));
+        assert_eq!(farnborough.frequencies[1].megahertz, "125.250");
+        assert_eq!(
+            database.lookup("Fairoaks").unwrap().kind,
+            WaypointKind::Airfield
+        );
+        assert_eq!(
+            database.lookup("ockham").unwrap().kind,
+            WaypointKind::Navaid
+        );
+
+        assert!(database.lookup("Biggin Hill").is_none());
+    }
+
+    #[test]
+    fn near_misses_are_only_suggested() {
+        let database = WaypointDatabase::load("uk.csv", CSV).unwrap();
+
+        // One letter away from Fairoaks, but Oxford is somewhere else entirely
+        assert!(database.lookup("EGTK").is_none());
+        assert!(database.lookup("Farnboro").is_none());
+
+        let suggestion = |query| {
+            database
+                .search(query, 1)
+                .first()
+                .map(|(entry, _)| entry.name.clone())
+        };
+        assert_eq!(suggestion("EGTK").as_deref(), Some("Fairoaks"));
+        assert_eq!(suggestion("Farnboro").as_deref(), Some("Farnborough"));
+        assert_eq!(suggestion("Fairoks").as_deref(), Some("Fairoaks"));
+        assert_eq!(suggestion("bagshot").as_deref(), Some("Bagshot Mast"));
+    }
+
+    #[test]
+    fn routes_are_filled_in() {
+        let database = WaypointDatabase::load("uk.csv", CSV).unwrap();
+        let defaults = DefaultLegValues::default();
+        let mut route = create_template_route();
+        route.legs = vec![
+            create_leg_with_defaults("EGLF".to_owned(), "Ockham".to_owned(), &defaults),
+            create_leg_with_defaults("Ockham".to_owned(), "Nowhere".to_owned(), &defaults),
+        ];
+
+        let missing = database.fill_route(&mut route);
+
+        assert_eq!(missing, ["Nowhere"]);
+        assert_eq!(route.legs[0].course, 82);
+        assert_eq!(route.legs[0].distance, 12);
+        assert!(route.legs[0].to_position.is_some());
+        assert!(route.legs[1].from_position.is_none());
+    }
+
+    #[test]
+    fn openaip_exports() {
+        let json = r#"{"items": [
+            {"name": "FARNBOROUGH", "icaoCode": "EGLF", "type": 3,
+             "geometry": {"type": "Point", "coordinates": [-0.7763, 51.2758]},
+             "elevation": {"value": 72, "unit": 0},
+             "frequencies": [{"value": "122.500", "name": "TOWER"}]},
+            {"name": "OCKHAM", "identifier": "OCK",
+             "geometry": {"type": "Point", "coordinates": [-0.4473, 51.305]},
+             "frequency": {"value": "115.300", "unit": 2}}
+        ]}"#;
+        let xml = r#"<OPENAIP VERSION="1" DATAFORMAT="1.1">
+            <WAYPOINTS>
+              <AIRPORT TYPE="AD_CLOSED">
+                <NAME>FAIROAKS</NAME>
+                <ICAO>EGTF</ICAO>
+                <GEOLOCATION><LAT>51.3481</LAT><LON>-0.5589</LON><ELEV UNIT="M">24</ELEV></GEOLOCATION>
+                <RADIO CATEGORY="COMMUNICATION"><FREQUENCY>123.430</FREQUENCY><TYPE>INFO</TYPE></RADIO>
+              </AIRPORT>
+            </WAYPOINTS>
+          </OPENAIP>"#;
+
+        let mut database = WaypointDatabase::new(parse_openaip_json(json).unwrap());
+        database.extend(parse_openaip_xml(xml).unwrap());
+
+        let farnborough = database.lookup("EGLF").unwrap();
+        assert_eq!(farnborough.elevation, Some(236));
+        assert_eq!(farnborough.frequencies[0].name, "TOWER");
+        let ockham = database.lookup("OCK").unwrap();
+        assert_eq!(ockham.kind, WaypointKind::Navaid);
+        assert_eq!(ockham.frequencies[0].megahertz, "115.300");
+        let fairoaks = database.lookup("egtf").unwrap();
+        assert_eq!(fairoaks.kind, WaypointKind::Airfield);
+        assert_eq!(fairoaks.elevation, Some(79));
+        assert_eq!(fairoaks.frequencies[0].megahertz, "123.430");
+    }
+}
@@ -0,0 +1,422 @@
+use pdf::{ContentBuilder, FontStyle, ImageRef, Matrix, TextAlign, ToMM};
+
+type Coord = (f64, f64);
+
+/// Drawing operations needed by the page layouts. Positions and sizes are in mm from
+/// the top left of the page, as with `ContentBuilder`.
+pub trait Canvas {
+    /// Page width and height in mm.
+    fn page_size(&self) -> Coord;
+
+    fn save_graphics_state(&mut self);
+    fn restore_graphics_state(&mut self);
+    /// Applies `matrix` to everything drawn until the graphics state is restored.
+    fn transform(&mut self, matrix: &Matrix);
+    fn line_width(&mut self, width: f64);
+    fn set_colour(&mut self, r: f64, g: f64, b: f64);
+    fn set_colour_non_stroking(&mut self, r: f64, g: f64, b: f64);
+
+    fn begin_subpath(&mut self, point: Coord);
+    fn line(&mut self, to: Coord);
+    fn curve_to(&mut self, ctrl1: Coord, ctrl2: Coord, end: Coord);
+    fn rectangle(&mut self, point: Coord, width: f64, height: f64);
+    fn close_path(&mut self);
+    fn stroke_path(&mut self);
+    fn fill(&mut self);
+
+    fn start_text_block(&mut self);
+    fn end_text_block(&mut self);
+    fn set_font(&mut self, style: FontStyle, size: f64);
+    fn set_leading(&mut self, leading: f64);
+    /// Starts a line of text at `position`, the left end of its baseline.
+    fn print_at(&mut self, text: &str, position: Coord);
+    /// Continues the current line of text.
+    fn print(&mut self, text: String);
+    /// Prints `text` aligned within a box `width` mm wide, shrinking it to fit and
+    /// truncating what still overflows, as `FontStyle::fit` does.
+    fn print_fit(&mut self, text: &str, position: Coord, width: f64, align: TextAlign);
+    fn next_line(&mut self);
+    /// Width in mm of `text` set in the current font.
+    fn text_width(&self, text: &str) -> f64;
+
+    /// Draws `image` as large as possible within the `size` rectangle at `position`.
+    fn place_image(&mut self, image: &ImageRef, position: Coord, size: Coord);
+
+    /// Runs `draw` between a save and restore of the graphics state.
+    fn with_saved_state<F: FnOnce(&mut Self)>(&mut self, draw: F)
+    where
+        Self: Sized,
+    {
+        self.save_graphics_state();
+        draw(self);
+        self.restore_graphics_state();
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.transform(&Matrix::IDENTITY.translate(dx, dy));
+    }
+
+    /// Rotates clockwise by `degrees`, as a compass bearing turns.
+    fn rotate(&mut self, degrees: f64) {
+        self.transform(&Matrix::IDENTITY.rotate(degrees));
+    }
+}
+
+/// Resets the colours and opens the graphics state that each page is drawn in.
+pub fn init_page(canvas: &mut impl Canvas) {
+    canvas.save_graphics_state();
+    canvas.set_colour(0., 0., 0.);
+    canvas.set_colour_non_stroking(0., 0., 0.);
+    canvas.stroke_path();
+    canvas.save_graphics_state();
+}
+
+impl Canvas for ContentBuilder<'_> {
+    fn page_size(&self) -> Coord {
+        ContentBuilder::page_size(self)
+    }
+
+    fn save_graphics_state(&mut self) {
+        ContentBuilder::save_graphics_state(self)
+    }
+
+    fn restore_graphics_state(&mut self) {
+        ContentBuilder::restore_graphics_state(self)
+    }
+
+    fn transform(&mut self, matrix: &Matrix) {
+        ContentBuilder::transform(self, matrix)
+    }
+
+    fn line_width(&mut self, width: f64) {
+        ContentBuilder::line_width(self, width)
+    }
+
+    fn set_colour(&mut self, r: f64, g: f64, b: f64) {
+        ContentBuilder::set_colour(self, r, g, b)
+    }
+
+    fn set_colour_non_stroking(&mut self, r: f64, g: f64, b: f64) {
+        ContentBuilder::set_colour_non_stroking(self, r, g, b)
+    }
+
+    fn begin_subpath(&mut self, point: Coord) {
+        ContentBuilder::begin_subpath(self, point)
+    }
+
+    fn line(&mut self, to: Coord) {
+        ContentBuilder::line(self, to)
+    }
+
+    fn curve_to(&mut self, ctrl1: Coord, ctrl2: Coord, end: Coord) {
+        ContentBuilder::curve_to(self, ctrl1, ctrl2, end)
+    }
+
+    fn rectangle(&mut self, point: Coord, width: f64, height: f64) {
+        ContentBuilder::rectangle(self, point, width, height)
+    }
+
+    fn close_path(&mut self) {
+        ContentBuilder::close_path(self)
+    }
+
+    fn stroke_path(&mut self) {
+        ContentBuilder::stroke_path(self)
+    }
+
+    fn fill(&mut self) {
+        ContentBuilder::fill(self)
+    }
+
+    fn start_text_block(&mut self) {
+        ContentBuilder::start_text_block(self)
+    }
+
+    fn end_text_block(&mut self) {
+        ContentBuilder::end_text_block(self)
+    }
+
+    fn set_font(&mut self, style: FontStyle, size: f64) {
+        ContentBuilder::set_font(self, style, size)
+    }
+
+    fn set_leading(&mut self, leading: f64) {
+        ContentBuilder::set_leading(self, leading)
+    }
+
+    fn print_at(&mut self, text: &str, position: Coord) {
+        ContentBuilder::print_at(self, text, position)
+    }
+
+    fn print(&mut self, text: String) {
+        ContentBuilder::print(self, text)
+    }
+
+    fn print_fit(&mut self, text: &str, position: Coord, width: f64, align: TextAlign) {
+        ContentBuilder::print_fit(self, text, position, width, align)
+    }
+
+    fn next_line(&mut self) {
+        ContentBuilder::next_line(self)
+    }
+
+    fn text_width(&self, text: &str) -> f64 {
+        ContentBuilder::text_width(self, text)
+    }
+
+    fn place_image(&mut self, image: &ImageRef, position: Coord, size: Coord) {
+        ContentBuilder::place_image(self, image, position, size)
+    }
+}
+
+/// A drawing call captured by `RecordingCanvas`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    SaveState,
+    RestoreState,
+    Transform(Matrix),
+    LineWidth(f64),
+    StrokeColour(f64, f64, f64),
+    FillColour(f64, f64, f64),
+    MoveTo(Coord),
+    LineTo(Coord),
+    CurveTo {
+        ctrl1: Coord,
+        ctrl2: Coord,
+        end: Coord,
+    },
+    Rectangle {
+        point: Coord,
+        width: f64,
+        height: f64,
+    },
+    ClosePath,
+    Stroke,
+    Fill,
+    BeginText,
+    EndText,
+    Leading(f64),
+    /// Text placed on the page with the font in use, after any shrinking to fit. A
+    /// `position` of `None` continues from the previous text.
+    Text {
+        text: String,
+        position: Option<Coord>,
+        font: Option<(FontStyle, f64)>,
+    },
+    NextLine,
+    Image {
+        position: Coord,
+        size: Coord,
+    },
+}
+
+/// Records drawing calls instead of rendering them, so layouts can be checked in tests
+/// or replayed onto another back-end.
+pub struct RecordingCanvas {
+    page_size: Coord,
+    font: Option<(FontStyle, f64)>,
+    commands: Vec<Command>,
+}
+
+impl RecordingCanvas {
+    /// Creates a canvas for a page `page_size` mm in size.
+    pub fn new(page_size: Coord) -> Self {
+        Self {
+            page_size,
+            font: None,
+            commands: vec![],
+        }
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn into_commands(self) -> Vec<Command> {
+        self.commands
+    }
+
+    fn record_text(&mut self, text: String, position: Option<Coord>) {
+        let font = self.font;
+        self.commands.push(Command::Text {
+            text,
+            position,
+            font,
+        });
+    }
+}
+
+impl Canvas for RecordingCanvas {
+    fn page_size(&self) -> Coord {
+        self.page_size
+    }
+
+    fn save_graphics_state(&mut self) {
+        self.commands.push(Command::SaveState);
+    }
+
+    fn restore_graphics_state(&mut self) {
+        self.commands.push(Command::RestoreState);
+    }
+
+    fn transform(&mut self, matrix: &Matrix) {
+        self.commands.push(Command::Transform(*matrix));
+    }
+
+    fn line_width(&mut self, width: f64) {
+        self.commands.push(Command::LineWidth(width));
+    }
+
+    fn set_colour(&mut self, r: f64, g: f64, b: f64) {
+        self.commands.push(Command::StrokeColour(r, g, b));
+    }
+
+    fn set_colour_non_stroking(&mut self, r: f64, g: f64, b: f64) {
+        self.commands.push(Command::FillColour(r, g, b));
+    }
+
+    fn begin_subpath(&mut self, point: Coord) {
+        self.commands.push(Command::MoveTo(point));
+    }
+
+    fn line(&mut self, to: Coord) {
+        self.commands.push(Command::LineTo(to));
+    }
+
+    fn curve_to(&mut self, ctrl1: Coord, ctrl2: Coord, end: Coord) {
+        self.commands.push(Command::CurveTo { ctrl1, ctrl2, end });
+    }
+
+    fn rectangle(&mut self, point: Coord, width: f64, height: f64) {
+        self.commands.push(Command::Rectangle {
+            point,
+            width,
+            height,
+        });
+    }
+
+    fn close_path(&mut self) {
+        self.commands.push(Command::ClosePath);
+    }
+
+    fn stroke_path(&mut self) {
+        self.commands.push(Command::Stroke);
+    }
+
+    fn fill(&mut self) {
+        self.commands.push(Command::Fill);
+    }
+
+    fn start_text_block(&mut self) {
+        self.commands.push(Command::BeginText);
+    }
+
+    fn end_text_block(&mut self) {
+        self.commands.push(Command::EndText);
+    }
+
+    fn set_font(&mut self, style: FontStyle, size: f64) {
+        self.font = Some((style, size));
+    }
+
+    fn set_leading(&mut self, leading: f64) {
+        self.commands.push(Command::Leading(leading));
+    }
+
+    fn print_at(&mut self, text: &str, position: Coord) {
+        self.record_text(text.to_owned(), Some(position));
+    }
+
+    fn print(&mut self, text: String) {
+        self.record_text(text, None);
+    }
+
+    /// Records the text as `ContentBuilder::print_fit` would print it, at its aligned
+    /// position.
+    fn print_fit(&mut self, text: &str, position: Coord, width: f64, align: TextAlign) {
+        let saved = self.font;
+        let text = match saved {
+            Some((style, size)) => {
+                let (fitted, text) = style.fit(text, size, width);
+                self.font = Some((style, fitted));
+                text
+            }
+            None => text.to_owned(),
+        };
+
+        let text_width = self.text_width(&text);
+        let (x, y) = position;
+        let x = match align {
+            TextAlign::Left => x,
+            TextAlign::Centre => x + (width - text_width) / 2.,
+            TextAlign::Right => x + width - text_width,
+        };
+        self.record_text(text, Some((x, y)));
+        self.font = saved;
+    }
+
+    fn next_line(&mut self) {
+        self.commands.push(Command::NextLine);
+    }
+
+    fn text_width(&self, text: &str) -> f64 {
+        match self.font {
+            Some((style, size)) => style.text_width(text, size).to_mm(),
+            None => 0.,
+        }
+    }
+
+    fn place_image(&mut self, _image: &ImageRef, position: Coord, size: Coord) {
+        self.commands.push(Command::Image { position, size });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf::{FontStyle, TextAlign};
+
+    use super::{Canvas, Command, RecordingCanvas};
+
+    #[test]
+    fn print_fit_shrinks_and_aligns() {
+        let mut canvas = RecordingCanvas::new((148.5, 210.));
+        canvas.set_font(FontStyle::Normal, 10.);
+        canvas.print_fit("Farnborough", (10., 20.), 10., TextAlign::Right);
+        canvas.print_fit("EGLF", (10., 30.), 20., TextAlign::Right);
+
+        let texts: Vec<_> = canvas
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                Command::Text { position, font, .. } => Some((*position, *font)),
+                _ => None,
+            })
+            .collect();
+
+        let (_, Some((_, size))) = texts[0] else {
+            panic!("text without a font");
+        };
+        assert!(size < 10.);
+
+        let (Some((x, _)), _) = texts[1] else {
+            panic!("text without a position");
+        };
+        let width = FontStyle::Normal.text_width("EGLF", 10.) * 25.4 / 72.;
+        assert!((x + width - 30.).abs() < 0.01);
+    }
+
+    #[test]
+    fn print_fit_truncates_as_pdf_does() {
+        let mut canvas = RecordingCanvas::new((148.5, 210.));
+        canvas.set_font(FontStyle::Normal, 10.);
+        canvas.print_fit("Farnborough", (10., 20.), 5., TextAlign::Left);
+
+        let Command::Text { text, font, .. } = &canvas.commands()[0] else {
+            panic!("expected text");
+        };
+        let (size, fitted) = FontStyle::Normal.fit("Farnborough", 10., 5.);
+        assert!(fitted.ends_with('…'));
+        assert_eq!(text, &fitted);
+        assert_eq!(*font, Some((FontStyle::Normal, size)));
+    }
+}
@@ -1,8 +1,9 @@
-use pdf::{init_page, ContentBuilder, FontStyle, PDFPageBuilder};
+use pdf::{FontStyle, PDFPageBuilder, TextAlign};
 
 use crate::{
     calc::{calc_aircraft, Degree, Velocity},
-    draw_utils::{disclaimer, write},
+    canvas::{init_page, Canvas},
+    draw_utils::{disclaimer, write_fit},
 };
 
 const MARGIN_SIDE: f64 = 5.;
@@ -12,17 +13,30 @@ const FONT_SIZE: f64 = 11.;
 
 const LINE_WIDTH: f64 = 0.25;
 
+const COLUMN_SHIFT: f64 = 36.1;
+const CELL_PADDING: f64 = 0.5;
+
 pub fn create_wind_table(
     builder: &mut PDFPageBuilder,
     air_speed: f64,
     variation: Degree,
     wind: &Velocity,
+) {
+    let mut layer = builder.content_builder();
+    draw_wind_table(&mut layer, air_speed, variation, wind);
+}
+
+/// Draws the diversion table for every track at this air speed and wind onto a fresh page.
+pub fn draw_wind_table(
+    layer: &mut impl Canvas,
+    air_speed: f64,
+    variation: Degree,
+    wind: &Velocity,
 ) {
     let rows = calc_diversions(air_speed, variation, wind);
 
-    let mut layer = builder.content_builder();
-    init_page(&mut layer);
-    disclaimer(&mut layer);
+    init_page(layer);
+    disclaimer(layer);
 
     let (page_width, _) = layer.page_size();
 
@@ -38,10 +52,18 @@ pub fn create_wind_table(
         );
 
         let font = (FontStyle::Bold, FONT_SIZE);
-        write(&mut layer, &details, (MARGIN_SIDE, 20.), &font)
+        let width = page_width - (MARGIN_SIDE * 2.);
+        write_fit(
+            layer,
+            &details,
+            (MARGIN_SIDE, 20.),
+            width,
+            TextAlign::Left,
+            &font,
+        )
     }
 
-    let shift = 36.1;
+    let shift = COLUMN_SHIFT;
     let left_start = MARGIN_SIDE;
     for (count, [h1, h2, h3, h4]) in rows.iter().enumerate() {
         let y = MARGIN_TOP + count as f64 * (name_height + 2.);
@@ -57,18 +79,18 @@ pub fn create_wind_table(
             layer.restore_graphics_state();
         }
 
-        column_line(&mut layer, y, left_start, h1);
-        column_line(&mut layer, y, left_start + shift, h2);
-        column_line(&mut layer, y, left_start + shift * 2., h3);
-        column_line(&mut layer, y, left_start + shift * 3., h4);
+        column_line(layer, y, left_start, h1);
+        column_line(layer, y, left_start + shift, h2);
+        column_line(layer, y, left_start + shift * 2., h3);
+        column_line(layer, y, left_start + shift * 3., h4);
 
         let distances = [60, 70, 80, 90, 100, 110, 120, 130, 140];
         let speeds = [5, 10, 15, 20, 25, 30, 35, 40, 45, 50, 55, 60, 65, 70];
-        dist_time(&mut layer, &distances, &speeds);
+        dist_time(layer, &distances, &speeds);
     }
 
     fn column_line(
-        text: &mut ContentBuilder,
+        text: &mut impl Canvas,
         y: f64,
         x_offset: f64,
         Diversion {
@@ -82,25 +104,31 @@ pub fn create_wind_table(
         let x = x_offset;
 
         let name_height = 2.5;
-        write(
+        write_fit(
             text,
             &format!("{track}\u{00b0}"),
             (x, y + name_height),
+            10. - CELL_PADDING,
+            TextAlign::Left,
             &font_bold,
         );
 
-        write(
+        write_fit(
             text,
             &format!("{fly_heading}\u{00b0}"),
             (x + 10., y + name_height),
+            10. - CELL_PADDING,
+            TextAlign::Left,
             &font,
         );
 
         let ground_speed = format!("{speed_overground}kt");
-        write(
+        write_fit(
             text,
             ground_speed.as_str(),
             (x + 10. + 10., y + name_height),
+            COLUMN_SHIFT - 20. - CELL_PADDING,
+            TextAlign::Left,
             &font,
         );
     }
@@ -151,7 +179,7 @@ impl Diversion {
     }
 }
 
-pub fn dist_time(layer: &mut ContentBuilder, degrees: &[i32], speed: &[i32]) {
+pub fn dist_time(layer: &mut impl Canvas, degrees: &[i32], speed: &[i32]) {
     let (page_width, _) = layer.page_size();
 
     let _font = (FontStyle::Normal, FONT_SIZE);
@@ -183,7 +211,15 @@ pub fn dist_time(layer: &mut ContentBuilder, degrees: &[i32], speed: &[i32]) {
         let x_pos = width_inc + x + (idx as f64 * width_inc);
         let font = (FontStyle::Bold, 11.);
         let location = (x_pos, y);
-        write(layer, &value.to_string(), location, &font);
+        let width = width_inc - CELL_PADDING;
+        write_fit(
+            layer,
+            &value.to_string(),
+            location,
+            width,
+            TextAlign::Left,
+            &font,
+        );
     }
 
     for (y_step, degree) in degrees.iter().enumerate() {
@@ -204,7 +240,15 @@ pub fn dist_time(layer: &mut ContentBuilder, degrees: &[i32], speed: &[i32]) {
 
         let location = (x, height);
         let font = (FontStyle::Bold, FONT_SIZE);
-        write(layer, &degree.to_string(), location, &font);
+        let width = width_inc - CELL_PADDING;
+        write_fit(
+            layer,
+            &degree.to_string(),
+            location,
+            width,
+            TextAlign::Left,
+            &font,
+        );
 
         for (idx, value) in speed.iter().enumerate() {
             let x_pos = width_inc + x + (idx as f64 * width_inc);
@@ -213,7 +257,14 @@ pub fn dist_time(layer: &mut ContentBuilder, degrees: &[i32], speed: &[i32]) {
 
             let location = (x_pos, height);
             let font = (FontStyle::Bold, FONT_SIZE);
-            write(layer, &cross_wind.to_string(), location, &font);
+            write_fit(
+                layer,
+                &cross_wind.to_string(),
+                location,
+                width_inc - CELL_PADDING,
+                TextAlign::Left,
+                &font,
+            );
         }
     }
 }
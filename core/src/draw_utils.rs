@@ -1,7 +1,9 @@
-use pdf::{ContentBuilder, FontStyle};
+use pdf::{FontStyle, TextAlign, ToMM};
+
+use crate::canvas::Canvas;
 
 pub fn write(
-    builder: &mut ContentBuilder,
+    builder: &mut impl Canvas,
     msg: &str,
     location: (f64, f64),
     (style, font_size): &(FontStyle, f64),
@@ -12,21 +14,114 @@ pub fn write(
     builder.end_text_block();
 }
 
-pub fn horizontal_line(layer: &mut ContentBuilder, from: (f64, f64), length: f64) {
+/// Writes `msg` aligned within a cell `width` mm wide, shrinking it to fit if needed.
+pub fn write_fit(
+    builder: &mut impl Canvas,
+    msg: &str,
+    location: (f64, f64),
+    width: f64,
+    align: TextAlign,
+    (style, font_size): &(FontStyle, f64),
+) {
+    builder.start_text_block();
+    builder.set_font(*style, *font_size);
+    builder.print_fit(msg, location, width, align);
+    builder.end_text_block();
+}
+
+pub fn horizontal_line(layer: &mut impl Canvas, from: (f64, f64), length: f64) {
     layer.begin_subpath(from);
     let (x, y) = from;
     layer.line((x + length, y));
     layer.stroke_path();
 }
 
-pub fn vertical_line(layer: &mut ContentBuilder, from: (f64, f64), length: f64) {
+pub fn vertical_line(layer: &mut impl Canvas, from: (f64, f64), length: f64) {
     layer.begin_subpath(from);
     let (x, y) = from;
     layer.line((x, y + length));
     layer.stroke_path();
 }
 
-pub fn disclaimer(builder: &mut ContentBuilder) {
+/// Draws a line from `from` to `to` finished with a filled arrowhead `head` mm long.
+pub fn arrow(layer: &mut impl Canvas, from: (f64, f64), to: (f64, f64), head: f64) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = dx.hypot(dy);
+
+    layer.with_saved_state(|layer| {
+        layer.translate(from.0, from.1);
+        layer.rotate(dy.atan2(dx).to_degrees());
+
+        layer.begin_subpath((0., 0.));
+        layer.line((length - head, 0.));
+        layer.stroke_path();
+
+        layer.begin_subpath((length, 0.));
+        layer.line((length - head, head / 3.));
+        layer.line((length - head, -head / 3.));
+        layer.close_path();
+        layer.fill();
+    });
+}
+
+/// Splits `text` between words into lines no wider than `width` mm when set in `font`. A
+/// value with nothing in it still gets a line, left blank for writing in.
+pub fn wrap(text: &str, width: f64, font: &(FontStyle, f64)) -> Vec<String> {
+    let mut words = reversed_words(text);
+    let mut lines = vec![take_line(&mut words, width, font)];
+    while !words.is_empty() {
+        lines.push(take_line(&mut words, width, font));
+    }
+    lines
+}
+
+/// The words of `text` last first, ready for `take_line` to pop off the end.
+pub fn reversed_words(text: &str) -> Vec<String> {
+    text.split_whitespace().rev().map(str::to_owned).collect()
+}
+
+/// Takes words off the end of `words` to fill a line no wider than `width` mm when set in
+/// `font`. A single word wider than the line is broken, leaving the rest for the next line.
+pub fn take_line(
+    words: &mut Vec<String>,
+    width: f64,
+    (style, font_size): &(FontStyle, f64),
+) -> String {
+    let fits = |text: &str| style.text_width(text, *font_size).to_mm() <= width;
+
+    let mut line = String::new();
+    while let Some(word) = words.pop() {
+        let candidate = if line.is_empty() {
+            word.clone()
+        } else {
+            format!("{line} {word}")
+        };
+
+        if fits(&candidate) {
+            line = candidate;
+        } else if line.is_empty() {
+            let mut split = word.chars().next().map_or(0, char::len_utf8);
+            for (idx, ch) in word.char_indices().skip(1) {
+                if !fits(&word[..idx + ch.len_utf8()]) {
+                    break;
+                }
+                split = idx + ch.len_utf8();
+            }
+            line = word[..split].to_owned();
+            if split < word.len() {
+                words.push(word[split..].to_owned());
+            }
+            break;
+        } else {
+            words.push(word);
+            break;
+        }
+    }
+
+    line
+}
+
+pub fn disclaimer(builder: &mut impl Canvas) {
     let font = FontStyle::Italics;
     let font_size = 6.;
     let (page_size_x, _) = builder.page_size();
@@ -44,3 +139,35 @@ pub fn disclaimer(builder: &mut ContentBuilder) {
     builder.print("License: Apache-2.0".to_owned());
     builder.end_text_block();
 }
+
+#[cfg(test)]
+mod tests {
+    use pdf::{FontStyle, ToMM};
+
+    use super::wrap;
+
+    #[test]
+    fn long_routes_wrap() {
+        let route = "N0100A025 DCT OCK DCT MID DCT SAM DCT 5030N00130W DCT ".repeat(4);
+        let font = (FontStyle::Normal, 10.);
+
+        let lines = wrap(&route, 60., &font);
+
+        assert!(lines.len() > 2);
+        assert_eq!(lines.join(" "), route.trim());
+        assert_eq!(wrap("", 60., &font), [""]);
+    }
+
+    #[test]
+    fn long_words_are_broken_in_their_font() {
+        let font = (FontStyle::Bold, 10.);
+
+        let lines = wrap("5030N00130W5031N00131W", 20., &font);
+
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), "5030N00130W5031N00131W");
+        for line in &lines {
+            assert!(FontStyle::Bold.text_width(line, 10.).to_mm() <= 20.);
+        }
+    }
+}
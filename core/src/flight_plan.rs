@@ -0,0 +1,113 @@
+use crate::canvas::{init_page, Canvas};
+use crate::draw_utils::{disclaimer, horizontal_line, wrap, write};
+
+use pdf::{FontStyle, PDFPageBuilder};
+
+const MARGIN_SIDE: f64 = 5.;
+const TITLE_Y: f64 = 14.;
+const ITEMS_TOP: f64 = 20.;
+const FONT_SIZE: f64 = 10.;
+const FONT_TITLE_SIZE: f64 = 12.;
+const FONT_LABEL_SIZE: f64 = 7.;
+const LABEL_HEIGHT: f64 = 4.;
+const LINE_HEIGHT: f64 = 4.5;
+const ITEM_GAP: f64 = 2.5;
+
+/// Adds a page with the items of an ICAO flight plan, each labelled, for reading out or
+/// copying into a filing form. The fields that couldn't be filled in are listed below.
+pub fn create_flight_plan(
+    builder: &mut PDFPageBuilder,
+    title: &str,
+    items: &[(String, String)],
+    missing: &[String],
+) {
+    let mut layer = builder.content_builder();
+    draw_flight_plan(&mut layer, title, items, missing);
+}
+
+/// Draws the flight plan items onto a fresh page, wrapping long values such as the route.
+pub fn draw_flight_plan(
+    layer: &mut impl Canvas,
+    title: &str,
+    items: &[(String, String)],
+    missing: &[String],
+) {
+    init_page(layer);
+    disclaimer(layer);
+
+    let (page_width, _) = layer.page_size();
+    let width = page_width - 2. * MARGIN_SIDE;
+
+    write(
+        layer,
+        title,
+        (MARGIN_SIDE, TITLE_Y),
+        &(FontStyle::Bold, FONT_TITLE_SIZE),
+    );
+
+    layer.save_graphics_state();
+    layer.line_width(0.25);
+
+    let mut y = ITEMS_TOP;
+    for (label, value) in items {
+        horizontal_line(layer, (MARGIN_SIDE, y), width);
+        y += LABEL_HEIGHT;
+        write(
+            layer,
+            label,
+            (MARGIN_SIDE, y),
+            &(FontStyle::Bold, FONT_LABEL_SIZE),
+        );
+
+        let font = (FontStyle::Normal, FONT_SIZE);
+        for line in wrap(value, width, &font) {
+            y += LINE_HEIGHT;
+            write(layer, &line, (MARGIN_SIDE, y), &font);
+        }
+        y += ITEM_GAP;
+    }
+    horizontal_line(layer, (MARGIN_SIDE, y), width);
+
+    if !missing.is_empty() {
+        let text = format!("Missing: {}", missing.join(", "));
+        let font = (FontStyle::Italics, FONT_SIZE);
+        for line in wrap(&text, width, &font) {
+            y += LINE_HEIGHT;
+            write(layer, &line, (MARGIN_SIDE, y), &font);
+        }
+    }
+
+    layer.restore_graphics_state();
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf::{ToMM, A5};
+
+    use crate::canvas::{Command, RecordingCanvas};
+
+    use super::draw_flight_plan;
+
+    #[test]
+    fn every_item_is_printed() {
+        let items = vec![
+            ("7 Aircraft ID".to_owned(), "GABCD".to_owned()),
+            ("9 Type / Wake".to_owned(), "C172/L".to_owned()),
+        ];
+        let mut canvas = RecordingCanvas::new(A5.to_mm());
+
+        draw_flight_plan(&mut canvas, "FPL", &items, &["10 Equipment".to_owned()]);
+
+        let texts: Vec<&str> = canvas
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                Command::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        for expected in ["7 Aircraft ID", "GABCD", "C172/L", "Missing: 10 Equipment"] {
+            assert!(texts.contains(&expected), "{expected} in {texts:?}");
+        }
+    }
+}
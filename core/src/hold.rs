@@ -1,9 +1,10 @@
 use definition::Hold as HoldDef;
-use pdf::{init_page, ContentBuilder, FontStyle, PDFPageBuilder};
+use pdf::{FontStyle, Matrix, PDFPageBuilder, TextAlign, ToMM};
 
 use crate::{
     calc::{calc_aircraft, Degree, Velocity},
-    draw_utils::{disclaimer, write},
+    canvas::{init_page, Canvas},
+    draw_utils::{arrow, disclaimer, write_fit},
 };
 
 // Geometry constants matching ppl_nav exactly (local space in mm)
@@ -23,20 +24,52 @@ const DISP: f64 = 2.0;
 const BX: f64 = 40.0;
 const BY: f64 = 110.0;
 
+// Display scale when rotated, smaller so the diagram fits the page at any angle.
+const DISP_ROTATED: f64 = 1.6;
+
 // Line width matching ppl_nav's set_outline_thickness(0.5) in PDF points.
 // 0.5pt × (25.4mm/72pt) ≈ 0.176mm
 const LW: f64 = 0.5 * 25.4 / 72.0;
 
-/// Convert ppl_nav local coords (beacon at origin, y-up) to kneeboard screen mm (y-down from top).
-#[inline]
-fn lp(lx: f64, ly: f64) -> (f64, f64) {
-    (BX + lx * DISP, BY - ly * DISP)
+// Helvetica cap height as a fraction of the font size, used to centre labels.
+const CAP_HEIGHT: f64 = 0.718;
+
+// North arrow position in screen mm and its length.
+const NORTH_ARROW_Y: f64 = 60.0;
+const NORTH_ARROW_LENGTH: f64 = 15.0;
+
+// Side margin of the page, in mm, that labels must stay within.
+const MARGIN_SIDE: f64 = 5.0;
+
+/// Maps ppl_nav local coords (beacon at origin, y-up) onto the page in mm (y-down from top).
+/// Unrotated, the inbound track points to the left of the page; when rotated to the
+/// track, the diagram turns about the middle of the racetrack so north is up.
+fn local_transform(hold: &HoldDef, page_width: f64) -> Matrix {
+    if hold.rotate_to_track {
+        Matrix::IDENTITY
+            .translate(page_width / 2., BY)
+            .rotate(hold.in_bound_track as f64 - 270.)
+            .scale(DISP_ROTATED, -DISP_ROTATED)
+            .translate(-LINE_LENGTH / 2., 0.)
+    } else {
+        Matrix::IDENTITY.translate(BX, BY).scale(DISP, -DISP)
+    }
+}
+
+/// Uniform scale of local units onto the page.
+fn local_scale(local: &Matrix) -> f64 {
+    local.a.hypot(local.b)
 }
 
 pub fn create_hold(builder: &mut PDFPageBuilder, hold: &HoldDef) {
     let mut layer = builder.content_builder();
-    init_page(&mut layer);
-    disclaimer(&mut layer);
+    draw_hold(&mut layer, hold);
+}
+
+/// Draws the hold diagram and its labels onto a fresh page.
+pub fn draw_hold(layer: &mut impl Canvas, hold: &HoldDef) {
+    init_page(layer);
+    disclaimer(layer);
 
     let right_hand = hold.right_hand;
     let in_bound_track = Degree::new(hold.in_bound_track as f64);
@@ -51,15 +84,27 @@ pub fn create_hold(builder: &mut PDFPageBuilder, hold: &HoldDef) {
     // offset_y: hold is above track for right-hand (+SCALE), below for left-hand (-SCALE)
     let oy = if right_hand { SCALE } else { -SCALE };
 
-    draw_racetrack(&mut layer, oy);
-    draw_beacon(&mut layer);
-    draw_inbound_line(&mut layer);
-    draw_divide_line(&mut layer, right_hand);
-    draw_gate_line(&mut layer, right_hand);
-    draw_ten_deg_tick(&mut layer, right_hand);
+    let (page_width, _) = layer.page_size();
+    let local = local_transform(hold, page_width);
+    layer.with_saved_state(|layer| {
+        layer.transform(&local);
+        layer.line_width(LW / local_scale(&local));
+        draw_racetrack(layer, oy);
+        draw_beacon(layer);
+        draw_inbound_line(layer);
+        draw_divide_line(layer, right_hand);
+        draw_gate_line(layer, right_hand);
+        draw_ten_deg_tick(layer, right_hand);
+    });
+
+    if hold.rotate_to_track {
+        draw_north_arrow(layer);
+    }
 
+    // Labels are placed at transformed points so that the text itself stays upright
     draw_labels(
-        &mut layer,
+        layer,
+        &local,
         right_hand,
         in_bound_track,
         variation,
@@ -76,62 +121,55 @@ pub fn create_hold(builder: &mut PDFPageBuilder, hold: &HoldDef) {
 ///   right semicircle centred at (LINE_LENGTH, oy)
 ///   left  semicircle centred at (0,           oy)
 ///   straight edges at y = oy ± A  (inner/track edge and outer edge)
-fn draw_racetrack(layer: &mut ContentBuilder, oy: f64) {
+fn draw_racetrack(layer: &mut impl Canvas, oy: f64) {
     let ox = LINE_LENGTH; // = 34
 
     layer.save_graphics_state();
-    layer.line_width(LW);
 
     // Start at top of right semicircle
-    layer.begin_subpath(lp(ox, A + oy));
+    layer.begin_subpath((ox, A + oy));
     // Right semicircle: top → rightmost  (quarter-circle via bezier)
-    layer.curve_to(lp(B + ox, C_K + oy), lp(C_K + ox, B + oy), lp(A + ox, oy));
+    layer.curve_to((B + ox, C_K + oy), (C_K + ox, B + oy), (A + ox, oy));
     // Right semicircle: rightmost → bottom  (quarter-circle)
-    layer.curve_to(
-        lp(C_K + ox, -B + oy),
-        lp(B + ox, -C_K + oy),
-        lp(ox, -A + oy),
-    );
+    layer.curve_to((C_K + ox, -B + oy), (B + ox, -C_K + oy), (ox, -A + oy));
     // Bottom straight edge: right → left (back to beacon side)
-    layer.line(lp(0.0, -A + oy));
+    layer.line((0.0, -A + oy));
     // Left semicircle: bottom → leftmost  (quarter-circle)
-    layer.curve_to(lp(-B, -A + oy), lp(-C_K, -B + oy), lp(-A, oy));
+    layer.curve_to((-B, -A + oy), (-C_K, -B + oy), (-A, oy));
     // Left semicircle: leftmost → top  (quarter-circle)
-    layer.curve_to(lp(-A, B + oy), lp(-B, C_K + oy), lp(0.0, A + oy));
+    layer.curve_to((-A, B + oy), (-B, C_K + oy), (0.0, A + oy));
     // Top straight edge: left → right
-    layer.line(lp(ox, A + oy));
+    layer.line((ox, A + oy));
     layer.close_path();
     layer.stroke_path();
     layer.restore_graphics_state();
 }
 
 /// Small square at beacon (local origin).
-fn draw_beacon(layer: &mut ContentBuilder) {
+fn draw_beacon(layer: &mut impl Canvas) {
     let bs = 0.25 * SCALE; // half-size = 1.25mm local → 2.5mm displayed
     layer.save_graphics_state();
-    layer.line_width(LW);
-    layer.begin_subpath(lp(0.5 * bs, 0.5 * bs));
-    layer.line(lp(0.5 * bs, -0.5 * bs));
-    layer.line(lp(-0.5 * bs, -0.5 * bs));
-    layer.line(lp(-0.5 * bs, 0.5 * bs));
+    layer.begin_subpath((0.5 * bs, 0.5 * bs));
+    layer.line((0.5 * bs, -0.5 * bs));
+    layer.line((-0.5 * bs, -0.5 * bs));
+    layer.line((-0.5 * bs, 0.5 * bs));
     layer.close_path();
     layer.stroke_path();
     layer.restore_graphics_state();
 }
 
 /// Inbound track line passing through the beacon.
-fn draw_inbound_line(layer: &mut ContentBuilder) {
+fn draw_inbound_line(layer: &mut impl Canvas) {
     layer.save_graphics_state();
-    layer.line_width(LW);
-    layer.begin_subpath(lp(-15.0, 0.0));
-    layer.line(lp(LINE_LENGTH * 1.5, 0.0));
+    layer.begin_subpath((-15.0, 0.0));
+    layer.line((LINE_LENGTH * 1.5, 0.0));
     layer.stroke_path();
     layer.restore_graphics_state();
 }
 
 /// Dividing line separating the offset-entry sector from the parallel-entry sector.
 /// Ported directly from ppl_nav's divide logic.
-fn draw_divide_line(layer: &mut ContentBuilder, right_hand: bool) {
+fn draw_divide_line(layer: &mut impl Canvas, right_hand: bool) {
     let (top, bottom) = if right_hand {
         (3.0_f64, 5.0_f64)
     } else {
@@ -145,38 +183,53 @@ fn draw_divide_line(layer: &mut ContentBuilder, right_hand: bool) {
     let y1 = (LINE_LENGTH / top) * 2.75;
 
     layer.save_graphics_state();
-    layer.line_width(LW);
-    layer.begin_subpath(lp(x0, y0));
-    layer.line(lp(x1, y1));
+    layer.begin_subpath((x0, y0));
+    layer.line((x1, y1));
     layer.stroke_path();
     layer.restore_graphics_state();
 }
 
 /// Gate entry line at 30° from inbound track (tan 30° ≈ 0.577).
-fn draw_gate_line(layer: &mut ContentBuilder, right_hand: bool) {
+fn draw_gate_line(layer: &mut impl Canvas, right_hand: bool) {
     let flip: f64 = if right_hand { 1.0 } else { -1.0 };
     layer.save_graphics_state();
-    layer.line_width(LW);
-    layer.begin_subpath(lp(0.0, 0.0));
-    layer.line(lp(LINE_LENGTH * 1.2, flip * LINE_LENGTH * 1.2 * 0.577));
+    layer.begin_subpath((0.0, 0.0));
+    layer.line((LINE_LENGTH * 1.2, flip * LINE_LENGTH * 1.2 * 0.577));
     layer.stroke_path();
     layer.restore_graphics_state();
 }
 
 /// Short tick mark at 10° from inbound track (tan 10° ≈ 0.176).
-fn draw_ten_deg_tick(layer: &mut ContentBuilder, right_hand: bool) {
+fn draw_ten_deg_tick(layer: &mut impl Canvas, right_hand: bool) {
     let flip: f64 = if right_hand { 1.0 } else { -1.0 };
     layer.save_graphics_state();
-    layer.line_width(LW);
-    layer.begin_subpath(lp(LINE_LENGTH * 1.25, flip * LINE_LENGTH * 1.25 * 0.176));
-    layer.line(lp(LINE_LENGTH * 1.32, flip * LINE_LENGTH * 1.32 * 0.176));
+    layer.begin_subpath((LINE_LENGTH * 1.25, flip * LINE_LENGTH * 1.25 * 0.176));
+    layer.line((LINE_LENGTH * 1.32, flip * LINE_LENGTH * 1.32 * 0.176));
     layer.stroke_path();
     layer.restore_graphics_state();
 }
 
+/// Arrow pointing to true north, in the top right corner of the page.
+fn draw_north_arrow(layer: &mut impl Canvas) {
+    let (page_width, _) = layer.page_size();
+    let x = page_width - 3. * MARGIN_SIDE;
+
+    layer.with_saved_state(|layer| {
+        layer.line_width(LW);
+        let from = (x, NORTH_ARROW_Y);
+        let to = (x, NORTH_ARROW_Y - NORTH_ARROW_LENGTH);
+        arrow(layer, from, to, 3.);
+    });
+
+    let font = (FontStyle::Bold, 10.);
+    let location = (x - 5., NORTH_ARROW_Y - NORTH_ARROW_LENGTH - 2.);
+    write_fit(layer, "N", location, 10., TextAlign::Centre, &font);
+}
+
 #[allow(clippy::too_many_arguments)]
 fn draw_labels(
-    layer: &mut ContentBuilder,
+    layer: &mut impl Canvas,
+    local: &Matrix,
     right_hand: bool,
     in_bound_track: Degree,
     variation: Degree,
@@ -198,17 +251,17 @@ fn draw_labels(
             wind_speed,
             aircraft_speed
         );
-        write(layer, &text, (5.0, 25.0), &font_hdr);
+        label(layer, &text, (5.0, 25.0), &font_hdr);
     }
 
     // Description — absolute screen position (matches ppl_nav Mm(5.), Mm(180.))
-    write(layer, description, (5.0, 35.0), &font_hdr);
+    label(layer, description, (5.0, 35.0), &font_hdr);
 
     // Inbound track label
     {
         let (trk, hdg, gs, t) = calc_inbound(air_speed, in_bound_track, variation, wind);
         let text = format!("{} ({}) {}kt [{}]", trk, hdg, gs, t);
-        write(layer, &text, lp(LINE_LENGTH / 3.8, 1.0), &font);
+        diagram_label(layer, local, &text, (LINE_LENGTH / 3.8, 1.0), &font);
     }
 
     // Outbound track label (triple WCA heading)
@@ -217,7 +270,7 @@ fn draw_labels(
         let (trk, hdg, gs, t) = calc_outbound(air_speed, out_track, variation, wind);
         let text = format!("{} ({}) {}kt [{}]", trk, hdg, gs, t);
         let local_y = if right_hand { 21.0 } else { -22.5 };
-        write(layer, &text, lp(LINE_LENGTH / 3.8, local_y), &font);
+        diagram_label(layer, local, &text, (LINE_LENGTH / 3.8, local_y), &font);
     }
 
     // Gate entry heading label (30° sector boundary)
@@ -230,7 +283,7 @@ fn draw_labels(
         let (trk, hdg, gs, t) = calc_inbound(air_speed, gate_track, variation, wind);
         let text = format!("{} ({}) {}kt [{}]", trk, hdg, gs, t);
         let local_y = if right_hand { 26.0 } else { -26.5 };
-        write(layer, &text, lp(LINE_LENGTH * 0.8, local_y), &font);
+        diagram_label(layer, local, &text, (LINE_LENGTH * 0.8, local_y), &font);
     }
 
     // 10-degree sector label
@@ -241,10 +294,11 @@ fn draw_labels(
             Degree::new(in_bound_track.degrees + 60.)
         };
         let local_y = if right_hand { 7.0 } else { -8.5 };
-        write(
+        diagram_label(
             layer,
+            local,
             &ten_deg.as_heading(),
-            lp(LINE_LENGTH * 1.35, local_y),
+            (LINE_LENGTH * 1.35, local_y),
             &font,
         );
     }
@@ -252,7 +306,7 @@ fn draw_labels(
     // Outbound track number on inbound line extension (left of beacon)
     {
         let out_bound = in_bound_track.reciprocal();
-        write(layer, &out_bound.as_heading(), lp(-15.0, 1.0), &font);
+        diagram_label(layer, local, &out_bound.as_heading(), (-15.0, 1.0), &font);
     }
 
     // Divide sector boundary headings
@@ -264,9 +318,9 @@ fn draw_labels(
         } else {
             (20.0, -35.0)
         };
-        write(layer, &div1.as_heading(), lp(-10.0, ly1), &font);
+        diagram_label(layer, local, &div1.as_heading(), (-10.0, ly1), &font);
         let div2 = Degree::new(in_bound_track.degrees + adjust);
-        write(layer, &div2.as_heading(), lp(10.0, ly2), &font);
+        diagram_label(layer, local, &div2.as_heading(), (10.0, ly2), &font);
     }
 
     // Entry sector labels
@@ -275,10 +329,37 @@ fn draw_labels(
     } else {
         (10.0, -20.0, -30.0, 15.0)
     };
-    write(layer, "OE", lp(-15.0, oe_ly), &font);
-    write(layer, "PE", lp(-15.0, pe_ly), &font);
-    write(layer, "DE", lp(LINE_LENGTH, de1_ly), &font);
-    write(layer, "DE", lp(LINE_LENGTH * 0.5, de2_ly), &font);
+    diagram_label(layer, local, "OE", (-15.0, oe_ly), &font);
+    diagram_label(layer, local, "PE", (-15.0, pe_ly), &font);
+    diagram_label(layer, local, "DE", (LINE_LENGTH, de1_ly), &font);
+    diagram_label(layer, local, "DE", (LINE_LENGTH * 0.5, de2_ly), &font);
+}
+
+/// Writes a label, shrinking it if it would run past the right margin of the page.
+fn label(layer: &mut impl Canvas, text: &str, location: (f64, f64), font: &(FontStyle, f64)) {
+    let (page_width, _) = layer.page_size();
+    let (x, y) = location;
+    let x = x.max(MARGIN_SIDE);
+    let width = page_width - MARGIN_SIDE - x;
+    write_fit(layer, text, (x, y), width, TextAlign::Left, font);
+}
+
+/// Writes a label given the local position of its left baseline in the unrotated diagram.
+/// The centre of the label follows the transform, so the text stays upright when rotated.
+fn diagram_label(
+    layer: &mut impl Canvas,
+    local: &Matrix,
+    text: &str,
+    (x, y): (f64, f64),
+    font: &(FontStyle, f64),
+) {
+    let (style, size) = *font;
+    let width = style.text_width(text, size).to_mm();
+    let height = size.to_mm() * CAP_HEIGHT;
+
+    let scale = local_scale(local);
+    let (cx, cy) = local.apply((x + width / scale / 2., y + height / scale / 2.));
+    label(layer, text, (cx - width / 2., cy + height / 2.), font);
 }
 
 fn calc_inbound(
@@ -322,3 +403,55 @@ fn to_time(secs: i64) -> String {
     let s = secs.abs();
     format!("{}:{:02}", s / 60, s % 60)
 }
+
+#[cfg(test)]
+mod tests {
+    use definition::Hold as HoldDef;
+
+    use crate::canvas::{Command, RecordingCanvas};
+
+    use super::{draw_hold, local_transform, LINE_LENGTH};
+
+    #[test]
+    fn rotated_inbound_leg_follows_track() {
+        let hold = HoldDef {
+            in_bound_track: 45,
+            rotate_to_track: true,
+            ..Default::default()
+        };
+        let local = local_transform(&hold, 148.);
+
+        // The inbound leg runs from the far end of the racetrack to the beacon
+        let (x1, y1) = local.apply((LINE_LENGTH, 0.));
+        let (x2, y2) = local.apply((0., 0.));
+        let bearing = (x2 - x1).atan2(y1 - y2).to_degrees();
+        assert!((bearing - 45.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rotated_labels_stay_on_the_page() {
+        for in_bound_track in (0..360).step_by(15) {
+            let hold = HoldDef {
+                description: "OCK".to_owned(),
+                in_bound_track,
+                aircraft_speed: 100,
+                rotate_to_track: true,
+                ..Default::default()
+            };
+            let mut canvas = RecordingCanvas::new((148.5, 210.));
+            draw_hold(&mut canvas, &hold);
+
+            for command in canvas.commands() {
+                if let Command::Text {
+                    text,
+                    position: Some((x, y)),
+                    ..
+                } = command
+                {
+                    let on_page = (0. ..=148.5).contains(x) && (0. ..=210.).contains(y);
+                    assert!(on_page, "{text} at ({x}, {y}) for {in_bound_track}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::canvas::init_page;
+use crate::draw_utils::{disclaimer, write, write_fit};
+
+use definition::PlanImage;
+use pdf::{FontStyle, PDFDocumentBuilder, TextAlign, A5};
+
+const MARGIN_SIDE: f64 = 5.;
+const MARGIN_BOTTOM: f64 = 5.;
+const CAPTION_Y: f64 = 16.;
+const IMAGE_TOP: f64 = 20.;
+const FONT_SIZE: f64 = 10.;
+
+/// Adds a page showing the image scaled to fill the space below its caption. An image
+/// that can't be decoded gets a page explaining why, so the pack is still produced.
+pub fn create_image_page(doc: &mut PDFDocumentBuilder, image: &PlanImage) {
+    let added = decode_base64(&image.data)
+        .map_err(|_| "Image data is not valid base64".to_owned())
+        .and_then(|data| doc.add_image(&data).map_err(|err| err.to_string()));
+
+    let mut page = doc.create_page(A5);
+    let mut layer = page.content_builder();
+    init_page(&mut layer);
+    disclaimer(&mut layer);
+
+    let (page_width, page_height) = layer.page_size();
+    let width = page_width - 2. * MARGIN_SIDE;
+
+    let font = (FontStyle::Bold, FONT_SIZE);
+    let location = (MARGIN_SIDE, CAPTION_Y);
+    write_fit(
+        &mut layer,
+        &image.caption,
+        location,
+        width,
+        TextAlign::Left,
+        &font,
+    );
+
+    match added {
+        Ok(image_ref) => {
+            let size = (width, page_height - IMAGE_TOP - MARGIN_BOTTOM);
+            layer.place_image(&image_ref, (MARGIN_SIDE, IMAGE_TOP), size);
+        }
+        Err(msg) => {
+            let font = (FontStyle::Italics, FONT_SIZE);
+            write(&mut layer, &msg, (MARGIN_SIDE, IMAGE_TOP + 5.), &font);
+        }
+    }
+}
+
+/// Accepts plain base64 as well as a `data:` URL.
+fn decode_base64(data: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let data = match data.strip_prefix("data:") {
+        Some(url) => url.split_once(',').map_or(url, |(_, data)| data),
+        None => data,
+    };
+    let data: String = data.split_whitespace().collect();
+    STANDARD.decode(data)
+}
@@ -1,7 +1,12 @@
 pub mod calc;
+pub mod canvas;
 pub mod diversion;
 pub mod draw_utils;
+pub mod flight_plan;
 pub mod hold;
+pub mod image;
 pub mod planner;
 pub mod route;
+pub mod upper_winds;
 pub mod vector;
+pub mod weather;
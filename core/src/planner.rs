@@ -2,26 +2,71 @@ use crate::calc::{convert_velocity, Degree};
 
 use crate::diversion::create_wind_table;
 use crate::hold::create_hold;
+use crate::image::create_image_page;
 use crate::route::{convert_leg, create_plog, Leg};
-use definition::Plan;
+use crate::upper_winds::plan_upper_winds;
+use crate::weather::{create_weather_page, parse_report};
+use definition::{Plan, PlanImage, Route};
 use pdf::{PDFDocument, PDFDocumentBuilder, A5};
 
+const PRODUCER: &str = "Kneeboard Notes";
+
+/// Creates the kneeboard document. The creation date is left for the caller to set through
+/// `PDFDocument::info_mut`, as there is no clock available when running in the browser.
 pub fn create_planning(plan: &Plan) -> PDFDocument {
+    build_planning(plan).to_doc()
+}
+
+/// Lays out every page of the kneeboard, leaving the builder open so pages can also be
+/// rendered with `PDFDocumentBuilder::page_svg`.
+pub fn build_planning(plan: &Plan) -> PDFDocumentBuilder {
     let mut doc_builder = PDFDocumentBuilder::new();
 
     let details = &plan.detail;
 
-    for route in &plan.routes {
+    {
+        let info = doc_builder.info_mut();
+        info.title = plan_title(plan);
+        info.author = details.pic.clone();
+        info.producer = Some(PRODUCER.to_owned());
+    }
+
+    let winds = plan_upper_winds(plan);
+    for (idx, route) in plan.routes.iter().enumerate() {
+        let name = route_name(route, idx);
+
+        let page = doc_builder.page_count();
+        doc_builder.add_bookmark(&name, page);
         let legs: Vec<Leg> = route.legs.iter().map(convert_leg).collect();
-        {
-            let mut current_layer = doc_builder.create_page(A5);
-            create_plog(&legs, &route.notes, details, &mut current_layer);
-        }
+        let prefix = format!("route{}", idx + 1);
+        create_plog(
+            &legs,
+            winds.as_ref(),
+            &route.notes,
+            details,
+            &prefix,
+            &mut doc_builder,
+        );
 
+        let page = doc_builder.page_count();
+        doc_builder.add_bookmark(&format!("{name} (return)"), page);
+        let reverse_legs = return_legs(legs);
+        let prefix = format!("route{}_return", idx + 1);
+        create_plog(
+            &reverse_legs,
+            winds.as_ref(),
+            &route.notes,
+            details,
+            &prefix,
+            &mut doc_builder,
+        );
+
+        for image in plan
+            .images
+            .iter()
+            .filter(|image| image.after_route == Some(idx))
         {
-            let mut current_layer = doc_builder.create_page(A5);
-            let reverse_legs: Vec<Leg> = legs.into_iter().map(rev_leg).rev().collect();
-            create_plog(&reverse_legs, &route.notes, details, &mut current_layer);
+            add_image_page(&mut doc_builder, image);
         }
     }
 
@@ -29,16 +74,93 @@ pub fn create_planning(plan: &Plan) -> PDFDocument {
         let speed = diversion.aircraft_speed as f64;
         let wind = convert_velocity(&diversion.wind);
         let variation = Degree::new(diversion.variation as f64);
+
+        let page = doc_builder.page_count();
+        let title = format!(
+            "Diversions {}kt, wind {:03}/{}",
+            diversion.aircraft_speed, diversion.wind.angle, diversion.wind.speed
+        );
+        doc_builder.add_bookmark(&title, page);
+
         let mut current_layer = doc_builder.create_page(A5);
         create_wind_table(&mut current_layer, speed, variation, &wind);
     }
 
-    for hold in &plan.holds {
+    for (idx, hold) in plan.holds.iter().enumerate() {
+        let page = doc_builder.page_count();
+        let title = match hold.description.trim() {
+            "" => format!("Hold {}", idx + 1),
+            description => format!("Hold: {description}"),
+        };
+        doc_builder.add_bookmark(&title, page);
+
         let mut current_layer = doc_builder.create_page(A5);
         create_hold(&mut current_layer, hold);
     }
 
-    doc_builder.to_doc()
+    // Reports that don't decode are left out; validation points them out.
+    for weather in &plan.weather {
+        let Ok(report) = parse_report(&weather.text) else {
+            continue;
+        };
+        let page = doc_builder.page_count();
+        doc_builder.add_bookmark(&report.title(), page);
+
+        let mut current_layer = doc_builder.create_page(A5);
+        create_weather_page(&mut current_layer, &report, &weather.text, &weather.runways);
+    }
+
+    let routes = plan.routes.len();
+    for image in &plan.images {
+        if image.after_route.is_none_or(|idx| idx >= routes) {
+            add_image_page(&mut doc_builder, image);
+        }
+    }
+
+    doc_builder
+}
+
+fn add_image_page(doc_builder: &mut PDFDocumentBuilder, image: &PlanImage) {
+    let page = doc_builder.page_count();
+    let title = match image.caption.trim() {
+        "" => "Image",
+        caption => caption,
+    };
+    doc_builder.add_bookmark(title, page);
+    create_image_page(doc_builder, image);
+}
+
+/// The route's name or, when it has none, where it goes.
+pub fn route_name(route: &Route, idx: usize) -> String {
+    let name = route.name.trim();
+    if !name.is_empty() {
+        return name.to_owned();
+    }
+
+    match (route.legs.first(), route.legs.last()) {
+        (Some(first), Some(last)) => format!("{} - {}", first.from, last.to),
+        _ => format!("Route {}", idx + 1),
+    }
+}
+
+fn plan_title(plan: &Plan) -> Option<String> {
+    let names: Vec<String> = plan
+        .routes
+        .iter()
+        .enumerate()
+        .map(|(idx, route)| route_name(route, idx))
+        .collect();
+
+    if names.is_empty() {
+        None
+    } else {
+        Some(names.join(", "))
+    }
+}
+
+/// The legs flown back from the end of the route, in the order flown.
+pub fn return_legs(legs: Vec<Leg>) -> Vec<Leg> {
+    legs.into_iter().map(rev_leg).rev().collect()
 }
 
 fn rev_leg(mut leg: Leg) -> Leg {
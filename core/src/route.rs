@@ -1,53 +1,160 @@
 use crate::{
     calc::{calc_aircraft, Degree, Velocity},
-    draw_utils::{disclaimer, horizontal_line, vertical_line, write},
+    canvas::{init_page, Canvas},
+    draw_utils::{
+        disclaimer, horizontal_line, reversed_words, take_line, vertical_line, write, write_fit,
+    },
+    upper_winds::UpperWinds,
 };
 
 use definition::Leg as JSonLeg;
-use definition::{Detail, FontType};
+use definition::{Coordinate, Detail, FontType};
 
-use pdf::{init_page, ContentBuilder, FontStyle, PDFPageBuilder};
+use pdf::{FontStyle, PDFDocumentBuilder, PDFPageBuilder, TextAlign, ToMM, A5};
 
 const MARGIN_SIDE: f64 = 2.5;
 const FONT_SIZE: f64 = 10.;
 const FONT_NOTES_SIZE: f64 = 9.;
 const FONT_HEADER_SIZE: f64 = 7.;
+const CELL_PADDING: f64 = 0.5;
+const NOTES_LEADING: f64 = 3.5;
+const NOTES_COLUMN_WIDTH: f64 = 52.5;
+const NOTES_CONTINUATION_TOP: f64 = 20.;
+const MARGIN_BOTTOM: f64 = 5.;
+const TABLE_TOP: f64 = 20.;
+const ROW_HEIGHT: f64 = 10.;
+
+// Offset of each column from the previous one, with its heading
+const COLUMNS: [(f64, Option<&str>); 14] = [
+    (25., Some("Safe")),
+    (7., Some("Plan")),
+    (7., Some("Spd")),
+    (7., Some("Track")),
+    (7., Some("Dist")),
+    (7., Some("Wind")),
+    (15., Some("G/S")),
+    (7., Some("HD(T)")),
+    (8., Some("HD(M)")),
+    (0.5, None),
+    (9., Some("Time")),
+    (7., Some("S/C")),
+    (14., Some("ETA")),
+    (14., Some("ATA")),
+];
+
+// Columns left empty for the pilot, with the name of their fillable field
+const LEG_FIELDS: [(usize, &str); 3] = [(11, "sc"), (12, "eta"), (13, "ata")];
+
+// Footer labels with the name of their fillable field and the offset to the next label
+const FUEL_FIELDS: [(&str, &str, f64); 6] = [
+    ("Oil:", "oil", 15.),
+    ("Fuel:", "fuel", 25.),
+    ("B/Off:", "b_off", 25.),
+    ("T/Off:", "t_off", 25.),
+    ("Lnd:", "lnd", 25.),
+    ("B/On:", "b_on", 25.),
+];
+
+/// Creates the plog pages for one direction of a route. Fillable fields are named from
+/// `field_prefix`, which should be unique for each plog in the document.
+pub fn create_plog(
+    legs: &[Leg],
+    winds: Option<&UpperWinds>,
+    notes: &[FontType],
+    detail: &Detail,
+    field_prefix: &str,
+    doc: &mut PDFDocumentBuilder,
+) {
+    let calc_legs = calc_legs(legs, winds);
+    let rows = rows_per_page(A5.to_mm().1);
+
+    // Always emit at least one page, even for a route without legs
+    let mut chunks: Vec<&[(&Leg, LegCalc)]> = calc_legs.chunks(rows).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+
+    let mut carried = None;
+    let mut continuation_pages = vec![];
+    let last = chunks.len() - 1;
+    for (n, chunk) in chunks.into_iter().enumerate() {
+        let mut page = doc.create_page(A5);
+        let mut layer = page.content_builder();
+        let y = draw_plog(chunk, detail, carried, &mut layer);
+        carried = chunk.last().map(|(_, leg_calc)| leg_calc.total).or(carried);
+
+        if n != last {
+            add_leg_fields(field_prefix, n * rows, chunk.len(), &mut page);
+            continue;
+        }
 
-pub fn create_plog(legs: &[Leg], notes: &[FontType], detail: &Detail, page: &mut PDFPageBuilder) {
-    let calc_legs = calc_legs(legs);
+        let y = draw_fuel(y, &mut layer);
 
-    let mut layer = page.content_builder();
-    init_page(&mut layer);
-    disclaimer(&mut layer);
+        let (page_width, page_height) = layer.page_size();
+        let start = (page_width - NOTES_COLUMN_WIDTH, y + 5.);
+        let first = NoteArea {
+            width: NOTES_COLUMN_WIDTH - 2. * MARGIN_SIDE,
+            lines: note_lines(start.1, page_height),
+        };
+        let continuation = NoteArea {
+            width: page_width - 4. * MARGIN_SIDE,
+            lines: note_lines(NOTES_CONTINUATION_TOP, page_height),
+        };
+
+        let mut pages = layout_notes(notes, first, continuation).into_iter();
+        if let Some(lines) = pages.next() {
+            write_notes(start, &lines, &mut layer);
+        }
+        continuation_pages = pages.collect::<Vec<_>>();
+
+        add_leg_fields(field_prefix, n * rows, chunk.len(), &mut page);
+        add_fuel_fields(field_prefix, y, &mut page);
+    }
+
+    for lines in continuation_pages {
+        let mut page = doc.create_page(A5);
+        let mut layer = page.content_builder();
+        init_page(&mut layer);
+        disclaimer(&mut layer);
+
+        let font = (FontStyle::Bold, FONT_SIZE);
+        write(&mut layer, "Notes (cont.)", (2. * MARGIN_SIDE, 14.), &font);
+        write_notes(
+            (2. * MARGIN_SIDE, NOTES_CONTINUATION_TOP),
+            &lines,
+            &mut layer,
+        );
+    }
+}
+
+/// Number of legs that fit on one plog page while leaving room for the fuel footer.
+fn rows_per_page(page_height: f64) -> usize {
+    let available = page_height - MARGIN_BOTTOM - TABLE_TOP - ROW_HEIGHT;
+    ((available / ROW_HEIGHT) as usize).max(1)
+}
+
+/// Draws one page of the plog table, returning the y position of the bottom line.
+/// `carried` holds the cumulative time brought forward when this is a continuation page.
+fn draw_plog(
+    calc_legs: &[(&Leg, LegCalc)],
+    detail: &Detail,
+    carried: Option<f64>,
+    layer: &mut impl Canvas,
+) -> f64 {
+    init_page(layer);
+    disclaimer(layer);
 
     layer.save_graphics_state();
     layer.line_width(0.25);
 
     let x = MARGIN_SIDE;
-    let mut y = 20.;
+    let mut y = TABLE_TOP;
 
     let name_height = 4.;
 
-    let columns = [
-        (25., Some("Safe")),
-        (7., Some("Plan")),
-        (7., Some("Spd")),
-        (7., Some("Track")),
-        (7., Some("Dist")),
-        (7., Some("Wind")),
-        (15., Some("G/S")),
-        (7., Some("HD(T)")),
-        (8., Some("HD(M)")),
-        (0.5, None),
-        (9., Some("Time")),
-        (7., Some("S/C")),
-        (14., Some("ETA")),
-        (14., Some("ATA")),
-    ];
-
     let (page_width, _) = layer.page_size();
+    let right_edge = page_width - 2. * MARGIN_SIDE;
 
-    let line_inc = |y: f64| y + 2. + name_height * 2.;
     for (leg, leg_calc) in calc_legs {
         layer.begin_subpath((x, y));
         layer.line((page_width - (2. * MARGIN_SIDE), y));
@@ -60,18 +167,29 @@ pub fn create_plog(legs: &[Leg], notes: &[FontType], detail: &Detail, page: &mut
 
         let y_middle_text = (y_top_text + y_bottom_text) / 2.;
 
-        layer.start_text_block();
-        layer.set_font(FontStyle::Normal, FONT_SIZE);
-        layer.set_leading(4.5);
-        layer.print_at(from, (x, y_top_text));
-        layer.next_line();
-        layer.print(to.to_owned());
-        layer.end_text_block();
+        let name_font = (FontStyle::Normal, FONT_SIZE);
+        let name_width = COLUMNS[0].0 - x - 2. * CELL_PADDING;
+        write_fit(
+            layer,
+            from,
+            (x, y_top_text),
+            name_width,
+            TextAlign::Left,
+            &name_font,
+        );
+        write_fit(
+            layer,
+            to,
+            (x, y_top_text + 4.5),
+            name_width,
+            TextAlign::Left,
+            &name_font,
+        );
 
         let wind = format!(
             "{}@{}",
-            leg.wind_direction.as_heading().as_str(),
-            leg.wind_speed
+            leg_calc.wind_direction.as_heading().as_str(),
+            leg_calc.wind_speed
         );
 
         let heading = leg_calc.heading.as_heading();
@@ -91,123 +209,241 @@ pub fn create_plog(legs: &[Leg], notes: &[FontType], detail: &Detail, page: &mut
         ];
 
         let mut x = 0.;
-        for ((value, adjust, font), (x_offset, heading)) in values.iter().zip(columns.iter()) {
+        for (idx, ((value, adjust, font), (x_offset, heading))) in
+            values.iter().zip(COLUMNS.iter()).enumerate()
+        {
             let calc_font = (*font, FONT_SIZE);
 
             x += x_offset;
 
             if heading.is_some() {
-                write(&mut layer, value, (x + adjust, y_middle_text), &calc_font);
+                let width = cell_width(&COLUMNS, idx, right_edge) - adjust;
+                write_fit(
+                    layer,
+                    value,
+                    (x + adjust, y_middle_text),
+                    width,
+                    TextAlign::Left,
+                    &calc_font,
+                );
             }
         }
 
-        y = line_inc(y);
+        y += ROW_HEIGHT;
     }
 
     {
-        layer.start_text_block();
-        layer.set_font(FontStyle::Normal, FONT_SIZE);
-        layer.set_leading(4.);
+        let font = (FontStyle::Normal, FONT_SIZE);
+        let detail_width = (right_edge - x) / 2.;
+
         for (n, id) in [&detail.field1, &detail.field2, &detail.field3]
             .into_iter()
             .flatten()
             .enumerate()
         {
-            if n == 0 {
-                layer.print_at(id, (x, 6.));
-            } else {
-                layer.next_line();
-                layer.print(id.to_owned());
-            }
+            let location = (x, 6. + n as f64 * 4.);
+            write_fit(layer, id, location, detail_width, TextAlign::Left, &font);
         }
-        layer.end_text_block();
-    }
 
-    {
-        layer.start_text_block();
-        layer.set_font(FontStyle::Normal, FONT_SIZE);
-        layer.set_leading(4.);
         for (n, id) in [&detail.tail, &detail.call_sign, &detail.pic]
             .into_iter()
             .flatten()
             .enumerate()
         {
-            if n == 0 {
-                layer.print_at(id, (page_width - 25., 6.));
-            } else {
-                layer.next_line();
-                layer.print(id.to_owned());
-            }
+            let location = (x + detail_width, 6. + n as f64 * 4.);
+            write_fit(layer, id, location, detail_width, TextAlign::Right, &font);
         }
-        layer.end_text_block();
+    }
+
+    let header_y = TABLE_TOP - 1.;
+    if let Some(total) = carried {
+        let font = (FontStyle::Bold, FONT_HEADER_SIZE);
+        let width = COLUMNS[0].0 - x - 2. * CELL_PADDING;
+        let text = format!("cont. b/f {}", total.as_string());
+        write_fit(layer, &text, (x, header_y), width, TextAlign::Left, &font);
     }
 
     let mut divider_x = 0.;
-    for (x_offset, heading) in columns {
+    for (idx, (x_offset, heading)) in COLUMNS.iter().enumerate() {
         divider_x += x_offset;
         if let Some(head) = heading {
-            layer.start_text_block();
-            layer.set_font(FontStyle::Bold, FONT_HEADER_SIZE);
-            layer.print_at(head, (divider_x, 19.));
-            layer.end_text_block()
+            let font = (FontStyle::Bold, FONT_HEADER_SIZE);
+            let width = cell_width(&COLUMNS, idx, right_edge);
+            write_fit(
+                layer,
+                head,
+                (divider_x, header_y),
+                width,
+                TextAlign::Left,
+                &font,
+            );
         }
-        vertical_line(&mut layer, (divider_x - 0.5, 20.), -(20. - y));
+        vertical_line(layer, (divider_x - 0.5, TABLE_TOP), y - TABLE_TOP);
     }
 
-    horizontal_line(&mut layer, (x, y), page_width - (2.0 * MARGIN_SIDE));
+    horizontal_line(layer, (x, y), page_width - (2.0 * MARGIN_SIDE));
 
-    {
-        y = line_inc(y);
-        horizontal_line(&mut layer, (x, y), page_width - (2. * MARGIN_SIDE));
+    y
+}
 
-        let y_txt = y - 4.;
-        let mut x_txt = x;
+/// Draws the oil and fuel footer row below `y`, returning the y position of its bottom line.
+fn draw_fuel(y: f64, layer: &mut impl Canvas) -> f64 {
+    let (page_width, _) = layer.page_size();
+    let x = MARGIN_SIDE;
+    let y = y + ROW_HEIGHT;
+    horizontal_line(layer, (x, y), page_width - (2. * MARGIN_SIDE));
 
-        let font = (FontStyle::Normal, FONT_SIZE);
+    let y_txt = y - 4.;
+    let mut x_txt = x;
 
-        write(&mut layer, "Oil:", (x_txt, y_txt), &font);
-        x_txt += 15.;
-        write(&mut layer, "Fuel:", (x_txt, y_txt), &font);
-        x_txt += 25.;
-        write(&mut layer, "B/Off:", (x_txt, y_txt), &font);
-        x_txt += 25.;
-        write(&mut layer, "T/Off:", (x_txt, y_txt), &font);
-        x_txt += 25.;
-        write(&mut layer, "Lnd:", (x_txt, y_txt), &font);
-        x_txt += 25.;
-        write(&mut layer, "B/On:", (x_txt, y_txt), &font);
+    let font = (FontStyle::Normal, FONT_SIZE);
+    for (text, _, offset) in FUEL_FIELDS {
+        write(layer, text, (x_txt, y_txt), &font);
+        x_txt += offset;
     }
 
-    write_notes((page_width - 52.5, y + 5.), notes, &mut layer);
+    y
 }
 
-fn write_notes(start: (f64, f64), notes: &[FontType], layer: &mut ContentBuilder) {
-    let (x, y) = start;
+/// Adds the S/C, ETA and ATA fields for `count` rows, the first being leg `first` of the route.
+fn add_leg_fields(prefix: &str, first: usize, count: usize, page: &mut PDFPageBuilder) {
+    for (name, position, size) in leg_fields(prefix, first, count) {
+        page.add_text_field(&name, position, size);
+    }
+}
 
-    layer.start_text_block();
-    layer.set_leading(3.5);
-
-    let empty = "".to_owned();
-    for (n, note) in notes.iter().enumerate() {
-        let current = match note {
-            FontType::Normal(ref txt) => Some((FontStyle::Normal, txt)),
-            FontType::Bold(ref txt) => Some((FontStyle::Bold, txt)),
-            FontType::Italics(ref txt) => Some((FontStyle::Italics, txt)),
-            FontType::Blank => None,
-        };
+/// Name, top left position and size in mm of a fillable field.
+type FieldCell = (String, (f64, f64), (f64, f64));
+
+/// Cells of each leg field, numbering legs from one across pages.
+fn leg_fields(prefix: &str, first: usize, count: usize) -> Vec<FieldCell> {
+    let (page_width, _) = A5.to_mm();
+    let right_edge = page_width - 2. * MARGIN_SIDE;
+
+    let mut fields = vec![];
+    for row in 0..count {
+        let y = TABLE_TOP + row as f64 * ROW_HEIGHT;
+        for (idx, field) in LEG_FIELDS {
+            let x: f64 = COLUMNS[..=idx].iter().map(|(x_offset, _)| x_offset).sum();
+            let width = cell_width(&COLUMNS, idx, right_edge) + CELL_PADDING;
+            let name = format!("{prefix}_leg{}_{field}", first + row + 1);
+            fields.push((name, (x, y), (width, ROW_HEIGHT)));
+        }
+    }
+    fields
+}
 
-        let (font, txt) = if let Some((font, txt)) = current {
-            (font, txt)
-        } else {
-            (FontStyle::Normal, &empty)
+/// Adds the oil and fuel footer fields, each filling the space after its label.
+fn add_fuel_fields(prefix: &str, y: f64, page: &mut PDFPageBuilder) {
+    let (page_width, _) = A5.to_mm();
+    let right_edge = page_width - 2. * MARGIN_SIDE;
+
+    let mut x = MARGIN_SIDE;
+    for (text, field, offset) in FUEL_FIELDS {
+        let start = x + FontStyle::Normal.text_width(text, FONT_SIZE).to_mm() + CELL_PADDING;
+        let end = (x + offset).min(right_edge) - CELL_PADDING;
+        let name = format!("{prefix}_{field}");
+        page.add_text_field(&name, (start, y - ROW_HEIGHT), (end - start, ROW_HEIGHT));
+        x += offset;
+    }
+}
+
+/// Usable width of the cell starting at column `idx`, up to the next divider or `right_edge`.
+fn cell_width(columns: &[(f64, Option<&str>)], idx: usize, right_edge: f64) -> f64 {
+    let next = match columns.get(idx + 1) {
+        Some((x_offset, _)) => *x_offset,
+        None => right_edge - columns.iter().map(|(x_offset, _)| x_offset).sum::<f64>(),
+    };
+    next - 0.5 - CELL_PADDING
+}
+
+/// A single wrapped line of notes; an empty `text` is a deliberately blank line.
+#[derive(Debug, PartialEq)]
+pub struct NoteLine {
+    pub font: FontStyle,
+    pub text: String,
+}
+
+/// Space available for notes on a page: the column width in mm and the number of lines.
+#[derive(Debug, Clone, Copy)]
+pub struct NoteArea {
+    pub width: f64,
+    pub lines: usize,
+}
+
+/// Number of note lines that fit between the first baseline at `top` and the bottom margin.
+fn note_lines(top: f64, page_height: f64) -> usize {
+    let bottom = page_height - MARGIN_BOTTOM;
+    if top > bottom {
+        0
+    } else {
+        ((bottom - top) / NOTES_LEADING) as usize + 1
+    }
+}
+
+/// Word-wraps the notes into lines and splits them across pages. The first page uses
+/// `first`, and any overflow goes onto as many `continuation` pages as needed. When the
+/// first page has no room the notes start on a continuation page, leaving it empty.
+pub fn layout_notes(
+    notes: &[FontType],
+    first: NoteArea,
+    continuation: NoteArea,
+) -> Vec<Vec<NoteLine>> {
+    // Every continuation page takes at least one line so the notes always make progress
+    let continuation = NoteArea {
+        lines: continuation.lines.max(1),
+        ..continuation
+    };
+    let mut pages = vec![vec![]];
+    let mut area = first;
+
+    for note in notes {
+        let (font, text) = match note {
+            FontType::Normal(txt) => (FontStyle::Normal, txt.as_str()),
+            FontType::Bold(txt) => (FontStyle::Bold, txt.as_str()),
+            FontType::Italics(txt) => (FontStyle::Italics, txt.as_str()),
+            FontType::Blank => (FontStyle::Normal, ""),
         };
 
-        layer.set_font(font, FONT_NOTES_SIZE);
+        let mut words = reversed_words(text);
+
+        loop {
+            if pages.last().map_or(0, Vec::len) >= area.lines {
+                pages.push(vec![]);
+                area = continuation;
+            }
+
+            let text = take_line(&mut words, area.width, &(font, FONT_NOTES_SIZE));
+            if let Some(page) = pages.last_mut() {
+                page.push(NoteLine { font, text });
+            }
+
+            if words.is_empty() {
+                break;
+            }
+        }
+    }
+
+    pages
+}
+
+fn write_notes(start: (f64, f64), lines: &[NoteLine], layer: &mut impl Canvas) {
+    if lines.is_empty() {
+        return;
+    }
+
+    let (x, y) = start;
+
+    layer.start_text_block();
+    layer.set_leading(NOTES_LEADING);
+
+    for (n, NoteLine { font, text }) in lines.iter().enumerate() {
+        layer.set_font(*font, FONT_NOTES_SIZE);
         if n == 0 {
-            layer.print_at(txt, (x, y));
+            layer.print_at(text, (x, y));
         } else {
             layer.next_line();
-            layer.print(txt.to_owned())
+            layer.print(text.to_owned())
         }
     }
     layer.end_text_block();
@@ -223,14 +459,19 @@ impl FloatToString for f64 {
     }
 }
 
-pub fn calc_legs(legs: &[Leg]) -> Vec<(&Leg, LegCalc)> {
+/// Works out each leg's heading, ground speed and time. With a forecast, legs with a planned
+/// altitude it covers are flown in its wind rather than their own.
+pub fn calc_legs<'a>(legs: &'a [Leg], winds: Option<&UpperWinds>) -> Vec<(&'a Leg, LegCalc)> {
     let mut result = vec![];
 
     let mut total = 0.;
     for leg in legs {
+        let (wind_direction, wind_speed) = winds
+            .and_then(|winds| winds.wind_for(leg))
+            .unwrap_or((leg.wind_direction, leg.wind_speed));
         let wind = Velocity {
-            speed: leg.wind_speed,
-            bearing: leg.wind_direction.reciprocal(),
+            speed: wind_speed,
+            bearing: wind_direction.reciprocal(),
         };
 
         let destination_bearing = leg.course;
@@ -250,6 +491,8 @@ pub fn calc_legs(legs: &[Leg]) -> Vec<(&Leg, LegCalc)> {
             heading_magnetic,
             time,
             total,
+            wind_direction,
+            wind_speed,
         };
 
         result.push((leg, leg_calc));
@@ -265,6 +508,9 @@ pub struct LegCalc {
     pub heading_magnetic: Degree,
     pub time: f64,
     pub total: f64,
+    /// The wind the leg was worked out with, from the forecast or the leg.
+    pub wind_direction: Degree,
+    pub wind_speed: f64,
 }
 
 #[derive(Debug)]
@@ -279,6 +525,9 @@ pub struct Leg {
 
     pub wind_direction: Degree,
     pub wind_speed: f64,
+
+    /// Halfway along the leg, when it was planned from coordinates.
+    pub midpoint: Option<Coordinate>,
 }
 
 pub fn convert_leg(json_leg: &JSonLeg) -> Leg {
@@ -293,14 +542,27 @@ pub fn convert_leg(json_leg: &JSonLeg) -> Leg {
 
         wind_direction: (json_leg.wind_direction as f64).into(),
         wind_speed: json_leg.wind_speed as f64,
+
+        midpoint: json_leg
+            .from_position
+            .zip(json_leg.to_position)
+            .map(|(from, to)| Coordinate {
+                latitude: (from.latitude + to.latitude) / 2.,
+                longitude: (from.longitude + to.longitude) / 2.,
+            }),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::calc::Degree;
+    use definition::{Detail, FontType};
+    use pdf::{FontStyle, PDFDocumentBuilder, ToMM};
 
-    use super::{calc_legs, Leg, LegCalc};
+    use super::{
+        calc_legs, create_plog, layout_notes, leg_fields, rows_per_page, Leg, LegCalc, NoteArea,
+        NoteLine, FONT_NOTES_SIZE,
+    };
 
     #[test]
     pub fn regression() {
@@ -315,6 +577,7 @@ mod tests {
                 variation: 1_f64.into(),
                 wind_direction: 260_f64.into(),
                 wind_speed: 20.,
+                midpoint: None,
             },
             Leg {
                 name: ("Place2".to_owned(), "Place3".to_owned()),
@@ -326,10 +589,11 @@ mod tests {
                 variation: 1_f64.into(),
                 wind_direction: 250_f64.into(),
                 wind_speed: 25.,
+                midpoint: None,
             },
         ];
 
-        let actual = calc_legs(&legs);
+        let actual = calc_legs(&legs, None);
 
         let expected = [
             LegCalc {
@@ -342,6 +606,8 @@ mod tests {
                 },
                 time: 15.554400039120518,
                 total: 15.554400039120518,
+                wind_direction: 260_f64.into(),
+                wind_speed: 20.,
             },
             LegCalc {
                 ground_speed: 77.56503907031117,
@@ -353,6 +619,8 @@ mod tests {
                 },
                 time: 11.60316568891518,
                 total: 27.1575657280357,
+                wind_direction: 250_f64.into(),
+                wind_speed: 25.,
             },
         ];
 
@@ -360,4 +628,183 @@ mod tests {
             assert_eq!(actual_leg, expected_leg);
         }
     }
+
+    #[test]
+    pub fn notes_wrap_to_width() {
+        let area = NoteArea {
+            width: 30.,
+            lines: 20,
+        };
+        let notes = [FontType::Bold(
+            "Contact Farnborough Radar on 125.250 passing abeam Guildford".to_owned(),
+        )];
+
+        let pages = layout_notes(&notes, area, area);
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].len() > 1);
+        for line in &pages[0] {
+            assert_eq!(line.font, FontStyle::Bold);
+            let width = line.font.text_width(&line.text, FONT_NOTES_SIZE).to_mm();
+            assert!(width <= 30., "{} is {width}mm wide", line.text);
+        }
+
+        let rejoined: Vec<&str> = pages[0].iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(
+            rejoined.join(" "),
+            "Contact Farnborough Radar on 125.250 passing abeam Guildford"
+        );
+    }
+
+    #[test]
+    pub fn notes_keep_blank_lines() {
+        let area = NoteArea {
+            width: 40.,
+            lines: 20,
+        };
+        let notes = [
+            FontType::Normal("ATIS".to_owned()),
+            FontType::Blank,
+            FontType::Italics("".to_owned()),
+            FontType::Normal("QNH".to_owned()),
+        ];
+
+        let pages = layout_notes(&notes, area, area);
+
+        let expected = vec![
+            NoteLine {
+                font: FontStyle::Normal,
+                text: "ATIS".to_owned(),
+            },
+            NoteLine {
+                font: FontStyle::Normal,
+                text: "".to_owned(),
+            },
+            NoteLine {
+                font: FontStyle::Italics,
+                text: "".to_owned(),
+            },
+            NoteLine {
+                font: FontStyle::Normal,
+                text: "QNH".to_owned(),
+            },
+        ];
+        assert_eq!(pages, vec![expected]);
+    }
+
+    #[test]
+    pub fn notes_continue_on_next_page() {
+        let first = NoteArea {
+            width: 40.,
+            lines: 2,
+        };
+        let continuation = NoteArea {
+            width: 120.,
+            lines: 3,
+        };
+        let notes: Vec<FontType> = (1..=6)
+            .map(|n| FontType::Normal(format!("Note {n}")))
+            .collect();
+
+        let pages = layout_notes(&notes, first, continuation);
+
+        let lengths: Vec<usize> = pages.iter().map(Vec::len).collect();
+        assert_eq!(lengths, vec![2, 3, 1]);
+        assert_eq!(pages[2][0].text, "Note 6");
+    }
+
+    #[test]
+    pub fn full_first_page_starts_notes_on_continuation() {
+        let first = NoteArea {
+            width: 40.,
+            lines: 0,
+        };
+        let continuation = NoteArea {
+            width: 120.,
+            lines: 3,
+        };
+        let notes: Vec<FontType> = (1..=4)
+            .map(|n| FontType::Normal(format!("Note {n}")))
+            .collect();
+
+        let pages = layout_notes(&notes, first, continuation);
+
+        let lengths: Vec<usize> = pages.iter().map(Vec::len).collect();
+        assert_eq!(lengths, vec![0, 3, 1]);
+        assert_eq!(pages[1][0].text, "Note 1");
+    }
+
+    #[test]
+    pub fn long_word_is_broken() {
+        let area = NoteArea {
+            width: 10.,
+            lines: 20,
+        };
+        let notes = [FontType::Normal("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_owned())];
+
+        let pages = layout_notes(&notes, area, area);
+
+        assert!(pages[0].len() > 1);
+        let rejoined: String = pages[0].iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(rejoined, "ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+    }
+
+    #[test]
+    pub fn long_route_splits_across_pages() {
+        let legs: Vec<Leg> = (0..20)
+            .map(|n| Leg {
+                name: (format!("Place{n}"), format!("Place{}", n + 1)),
+                safe: "1.8".to_owned(),
+                planned: "2.2".to_owned(),
+                speed: 100.,
+                course: 90_f64.into(),
+                distance: 10.,
+                variation: 0_f64.into(),
+                wind_direction: 0_f64.into(),
+                wind_speed: 0.,
+                midpoint: None,
+            })
+            .collect();
+
+        let mut doc = PDFDocumentBuilder::new();
+        create_plog(
+            &legs,
+            None,
+            &[],
+            &Detail::default(),
+            "route1_outbound",
+            &mut doc,
+        );
+
+        assert_eq!(rows_per_page(210.), 17);
+        assert_eq!(doc.page_count(), 2);
+        assert!(!doc.page_svg(0).unwrap().contains("cont. b/f"));
+
+        // The second page carries on from the cumulative time of the first 17 legs
+        let second = doc.page_svg(1).unwrap();
+        assert!(second.contains("cont. b/f 102"), "{second}");
+    }
+
+    #[test]
+    pub fn leg_fields_are_named_per_leg() {
+        let fields = leg_fields("route2_return", 17, 2);
+
+        let names: Vec<&str> = fields.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "route2_return_leg18_sc",
+                "route2_return_leg18_eta",
+                "route2_return_leg18_ata",
+                "route2_return_leg19_sc",
+                "route2_return_leg19_eta",
+                "route2_return_leg19_ata",
+            ]
+        );
+
+        // The ATA column runs up to the right hand edge of the table
+        let (_, (x, y), (width, _)) = &fields[5];
+        assert!((x + width - 143.5).abs() < 1.);
+        assert_eq!(*y, 30.);
+    }
 }
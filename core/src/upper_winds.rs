@@ -0,0 +1,551 @@
+use crate::calc::Degree;
+use crate::route::Leg;
+
+use definition::{Coordinate, Plan};
+
+/// Forecast wind and temperature at one altitude.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpperWind {
+    /// Feet above mean sea level.
+    pub altitude: f64,
+    /// Degrees true the wind blows from, or `None` when light and variable.
+    pub direction: Option<f64>,
+    pub speed: f64,
+    pub temperature: Option<i32>,
+}
+
+/// The forecast for one place, lowest altitude first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpperWindStation {
+    /// The station identifier or spot location as given.
+    pub name: String,
+    pub position: Option<Coordinate>,
+    pub winds: Vec<UpperWind>,
+}
+
+/// A table of forecast winds and temperatures by place and altitude.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpperWinds {
+    pub stations: Vec<UpperWindStation>,
+}
+
+impl UpperWinds {
+    /// Every altitude forecast for any station, lowest first.
+    pub fn altitudes(&self) -> Vec<f64> {
+        let mut altitudes: Vec<f64> = self
+            .stations
+            .iter()
+            .flat_map(|station| station.winds.iter().map(|wind| wind.altitude))
+            .collect();
+        altitudes.sort_by(f64::total_cmp);
+        altitudes.dedup();
+        altitudes
+    }
+
+    /// The wind at the leg's planned altitude, as a direction and speed in whole degrees and
+    /// knots. The station is the one named after either end of the leg, else the one nearest
+    /// the leg, else the only one. Legs without a planned altitude in thousands of feet, or
+    /// outside the altitudes forecast for their station, get none.
+    pub fn wind_for(&self, leg: &Leg) -> Option<(Degree, f64)> {
+        let altitude = planned_altitude(&leg.planned)?;
+        let (from, to) = &leg.name;
+
+        let named = self.stations.iter().find(|station| {
+            [from, to]
+                .iter()
+                .any(|name| name.trim().eq_ignore_ascii_case(&station.name))
+        });
+        let nearest = || {
+            let midpoint = leg.midpoint?;
+            self.stations
+                .iter()
+                .filter_map(|station| {
+                    let position = station.position?;
+                    Some((distance(midpoint, position), station))
+                })
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, station)| station)
+        };
+
+        let only = || match self.stations.as_slice() {
+            [station] => Some(station),
+            _ => None,
+        };
+
+        let station = named.or_else(nearest).or_else(only)?;
+        interpolate(&station.winds, altitude)
+    }
+}
+
+/// The plan's pasted upper-wind forecast, when it has one that reads.
+pub fn plan_upper_winds(plan: &Plan) -> Option<UpperWinds> {
+    match plan.upper_winds.trim() {
+        "" => None,
+        text => parse_upper_winds(text).ok(),
+    }
+}
+
+/// A planned altitude in thousands of feet, such as `2.5`, in feet.
+pub fn planned_altitude(planned: &str) -> Option<f64> {
+    let thousands: f64 = planned.trim().parse().ok()?;
+    (thousands.is_finite() && thousands >= 0.).then_some(thousands * 1000.)
+}
+
+/// Reads a pasted upper-wind forecast: US FB winds aloft, found by their `FT` heading line,
+/// or UK spot winds.
+///
+/// Spot winds are given as blocks, each a location line (coordinates such as `5230N 00130W`,
+/// or a name) followed by a line per altitude: thousands of feet, direction, speed and
+/// temperature, such as `05 270 15 +03`. Altitudes may also be in feet (`2000FT`) or a
+/// flight level (`FL050`), and the wind written `270/15` or `27015`.
+pub fn parse_upper_winds(text: &str) -> Result<UpperWinds, String> {
+    let is_fb_heading = |line: &&str| {
+        let mut tokens = line.split_whitespace();
+        tokens.next() == Some("FT") && tokens.all(|token| token.chars().all(|c| c.is_ascii_digit()))
+    };
+
+    let winds = match text.lines().any(|line| is_fb_heading(&line)) {
+        true => parse_fb(text, is_fb_heading)?,
+        false => parse_spot(text)?,
+    };
+    if winds
+        .stations
+        .iter()
+        .all(|station| station.winds.is_empty())
+    {
+        return Err("No winds were found in the forecast".to_owned());
+    }
+    Ok(winds)
+}
+
+fn parse_fb(text: &str, is_heading: impl Fn(&&str) -> bool) -> Result<UpperWinds, String> {
+    let mut lines = text.lines().skip_while(|line| !is_heading(line));
+    let heading = lines.next().unwrap_or_default();
+
+    // Groups are right aligned under their altitude
+    let altitudes: Vec<(usize, f64)> = tokens_with_ends(heading)
+        .skip(1)
+        .filter_map(|(end, token)| Some((end, token.parse().ok()?)))
+        .collect();
+
+    let mut stations = vec![];
+    for line in lines {
+        let mut tokens = tokens_with_ends(line);
+        let Some((_, name)) = tokens.next() else {
+            continue;
+        };
+
+        let mut winds = vec![];
+        for (end, group) in tokens {
+            let Some(&(_, altitude)) = altitudes
+                .iter()
+                .find(|(column, _)| column.abs_diff(end) <= 2)
+            else {
+                return Err(format!(
+                    "'{group}' for {name} isn't under an altitude in the FT line"
+                ));
+            };
+            let Some((direction, speed, temperature)) = parse_fb_group(group) else {
+                return Err(format!("'{group}' for {name} isn't a wind group"));
+            };
+            winds.push(UpperWind {
+                altitude,
+                direction,
+                speed,
+                temperature,
+            });
+        }
+
+        stations.push(UpperWindStation {
+            name: name.to_owned(),
+            position: None,
+            winds,
+        });
+    }
+
+    Ok(UpperWinds { stations })
+}
+
+/// Tokens of the line with the column just past each.
+fn tokens_with_ends(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |token| {
+        let start = token.as_ptr() as usize - line.as_ptr() as usize;
+        (start + token.len(), token)
+    })
+}
+
+/// An FB group: `DDSS`, `DDSS±TT`, or `DDSSTT` above 24000 ft where temperatures are all
+/// below zero. Directions over 500 carry 100 kt more speed, and 9900 is light and variable.
+fn parse_fb_group(group: &str) -> Option<(Option<f64>, f64, Option<i32>)> {
+    if group.len() < 4 || !group.is_ascii() {
+        return None;
+    }
+    let (wind, temperature) = group.split_at(4);
+
+    let temperature = match temperature {
+        "" => None,
+        temperature if temperature.len() == 2 => Some(-(digits(temperature)? as i32)),
+        temperature => {
+            let (sign, value) = temperature.split_at(1);
+            let value = digits(value)? as i32;
+            match sign {
+                "+" => Some(value),
+                "-" => Some(-value),
+                _ => return None,
+            }
+        }
+    };
+
+    let tens = digits(&wind[..2])?;
+    let speed = digits(&wind[2..])? as f64;
+    let (direction, speed) = match tens {
+        99 => (None, 0.),
+        51..=86 => (Some((tens - 50) as f64 * 10.), speed + 100.),
+        0..=36 => (Some(tens as f64 * 10.), speed),
+        _ => return None,
+    };
+    Some((direction, speed, temperature))
+}
+
+fn parse_spot(text: &str) -> Result<UpperWinds, String> {
+    let mut stations: Vec<UpperWindStation> = vec![];
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(wind) = parse_spot_row(line) {
+            match stations.last_mut() {
+                Some(station) => station.winds.push(wind),
+                None => return Err(format!("'{line}' comes before any location")),
+            }
+        } else {
+            stations.push(UpperWindStation {
+                name: line.to_owned(),
+                position: parse_coordinates(line),
+                winds: vec![],
+            });
+        }
+    }
+
+    for station in &mut stations {
+        station
+            .winds
+            .sort_by(|a, b| a.altitude.total_cmp(&b.altitude));
+    }
+    Ok(UpperWinds { stations })
+}
+
+fn parse_spot_row(line: &str) -> Option<UpperWind> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (level, rest) = tokens.split_first()?;
+    let altitude = parse_level(level)?;
+
+    let (direction, speed, rest) = match rest {
+        [wind, rest @ ..] if wind.contains('/') => {
+            let (direction, speed) = wind.split_once('/')?;
+            (direction, speed, rest)
+        }
+        [wind, rest @ ..] if wind.len() >= 5 && wind.is_char_boundary(3) => {
+            let (direction, speed) = wind.split_at(3);
+            (direction, speed, rest)
+        }
+        [direction, speed, rest @ ..] => (*direction, *speed, rest),
+        _ => return None,
+    };
+    let direction = match direction {
+        "VRB" => None,
+        direction => Some(digits(direction)? as f64),
+    };
+    let speed = digits(speed)? as f64;
+
+    let temperature = match rest {
+        [] => None,
+        [temperature] => Some(parse_temperature(temperature)?),
+        _ => return None,
+    };
+
+    Some(UpperWind {
+        altitude,
+        direction,
+        speed,
+        temperature,
+    })
+}
+
+/// `05` as 5000 ft, `2000FT` as 2000 ft and `FL050` as 5000 ft.
+fn parse_level(level: &str) -> Option<f64> {
+    if let Some(hundreds) = level.strip_prefix("FL") {
+        return Some(digits(hundreds)? as f64 * 100.);
+    }
+    let level = level.strip_suffix("FT").unwrap_or(level);
+    let value = digits(level)? as f64;
+    match level.len() {
+        1..=2 => Some(value * 1000.),
+        4..=5 => Some(value),
+        _ => None,
+    }
+}
+
+fn parse_temperature(temperature: &str) -> Option<i32> {
+    let (negative, value) = match temperature.split_at_checked(1)? {
+        ("+" | "P", value) => (false, value),
+        ("-" | "M", value) => (true, value),
+        _ => (false, temperature),
+    };
+    let value = digits(value)? as i32;
+    Some(if negative { -value } else { value })
+}
+
+/// `5230N 00130W` or `5230N00130W`, with or without minutes.
+fn parse_coordinates(text: &str) -> Option<Coordinate> {
+    let text: String = text.split_whitespace().collect();
+    if !text.is_ascii() {
+        return None;
+    }
+    let split = text.find(['N', 'S'])?;
+    let (latitude, longitude) = text.split_at(split + 1);
+
+    let angle = |text: &str, degree_digits: usize| {
+        let (value, hemisphere) = text.split_at(text.len().checked_sub(1)?);
+        if value.len() != degree_digits && value.len() != degree_digits + 2 {
+            return None;
+        }
+        let degrees = digits(&value[..degree_digits])? as f64;
+        let minutes = match &value[degree_digits..] {
+            "" => 0.,
+            minutes => digits(minutes)? as f64,
+        };
+        let angle = degrees + minutes / 60.;
+        Some(match hemisphere {
+            "S" | "W" => -angle,
+            _ => angle,
+        })
+    };
+
+    if !longitude.ends_with(['E', 'W']) {
+        return None;
+    }
+    Some(Coordinate {
+        latitude: angle(latitude, 2)?,
+        longitude: angle(longitude, 3)?,
+    })
+}
+
+fn digits(text: &str) -> Option<u32> {
+    match !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+        true => text.parse().ok(),
+        false => None,
+    }
+}
+
+/// Rough distance for picking the nearest station, in degrees of latitude.
+fn distance(a: Coordinate, b: Coordinate) -> f64 {
+    let latitude = (a.latitude + b.latitude).to_radians() / 2.;
+    let x = (b.longitude - a.longitude) * latitude.cos();
+    let y = b.latitude - a.latitude;
+    x.hypot(y)
+}
+
+/// The wind at `altitude`, between the forecasts either side of it. Altitudes below or above
+/// those forecast have none.
+fn interpolate(winds: &[UpperWind], altitude: f64) -> Option<(Degree, f64)> {
+    let components = |wind: &UpperWind| match wind.direction {
+        Some(direction) => {
+            let angle = direction.to_radians();
+            (wind.speed * angle.sin(), wind.speed * angle.cos())
+        }
+        None => (0., 0.),
+    };
+
+    let above = winds.iter().position(|wind| wind.altitude >= altitude)?;
+    let (east, north) = match above {
+        0 if winds[0].altitude == altitude => components(&winds[0]),
+        0 => return None,
+        idx => {
+            let (lower, upper) = (&winds[idx - 1], &winds[idx]);
+            let fraction = (altitude - lower.altitude) / (upper.altitude - lower.altitude);
+            let (lower_east, lower_north) = components(lower);
+            let (upper_east, upper_north) = components(upper);
+            (
+                lower_east + (upper_east - lower_east) * fraction,
+                lower_north + (upper_north - lower_north) * fraction,
+            )
+        }
+    };
+
+    let speed = east.hypot(north).round();
+    let direction = match speed {
+        0. => 0.,
+        _ => east.atan2(north).to_degrees().round(),
+    };
+    Some((Degree::new(direction), speed))
+}
+
+#[cfg(test)]
+mod tests {
+    use definition::Coordinate;
+
+    use crate::calc::Degree;
+    use crate::route::{calc_legs, Leg};
+
+    use super::{parse_upper_winds, planned_altitude};
+
+    const FB: &str = "\
+DATA BASED ON 010000Z
+VALID 010600Z   FOR USE 0200-0900Z. TEMPS NEG ABV 24000
+
+FT  3000    6000    9000   12000   18000   24000  30000  34000  39000
+ABI      2114+16 2315+12 2619+04 2541-15 2558-27 257142 268149 268857
+DEN              2418+08 2626+02 2746-14 2762-26 770841 771050 771059
+";
+
+    const SPOT: &str = "\
+5230N 00130W
+01 240 10 +10
+02 250 15 +08
+05 260 20 +03
+10 270 25 -06
+
+5400N 00300W
+02 300/20 +06
+05 31025 +01
+";
+
+    fn leg(planned: &str, midpoint: Option<Coordinate>) -> Leg {
+        Leg {
+            name: ("Here".to_owned(), "There".to_owned()),
+            safe: "1.8".to_owned(),
+            planned: planned.to_owned(),
+            speed: 100.,
+            course: 90_f64.into(),
+            distance: 20.,
+            variation: 0_f64.into(),
+            wind_direction: 0_f64.into(),
+            wind_speed: 0.,
+            midpoint,
+        }
+    }
+
+    #[test]
+    fn fb_groups_decode() {
+        let winds = parse_upper_winds(FB).unwrap();
+
+        assert_eq!(winds.stations.len(), 2);
+        let abi = &winds.stations[0];
+        assert_eq!(abi.name, "ABI");
+        assert_eq!(abi.winds[0].altitude, 6000.);
+        assert_eq!(abi.winds[0].direction, Some(210.));
+        assert_eq!(abi.winds[0].speed, 14.);
+        assert_eq!(abi.winds[0].temperature, Some(16));
+        assert_eq!(abi.winds[5].temperature, Some(-42));
+
+        let den = &winds.stations[1];
+        assert_eq!(den.winds[0].altitude, 9000.);
+        // 7708 is 270° at 108 kt
+        assert_eq!(den.winds[4].direction, Some(270.));
+        assert_eq!(den.winds[4].speed, 108.);
+    }
+
+    #[test]
+    fn spot_winds_decode() {
+        let winds = parse_upper_winds(SPOT).unwrap();
+
+        assert_eq!(winds.stations.len(), 2);
+        let position = winds.stations[1].position.unwrap();
+        assert_eq!((position.latitude, position.longitude), (54., -3.));
+        assert_eq!(winds.stations[0].winds.len(), 4);
+        assert_eq!(winds.stations[0].winds[2].altitude, 5000.);
+        assert_eq!(winds.stations[1].winds[1].direction, Some(310.));
+        assert_eq!(winds.stations[1].winds[1].temperature, Some(1));
+        assert_eq!(winds.altitudes(), [1000., 2000., 5000., 10000.]);
+        assert!(parse_upper_winds("05 270 15 +03").is_err());
+        assert!(parse_upper_winds("").is_err());
+    }
+
+    #[test]
+    fn pasted_symbols_are_rejected_not_sliced() {
+        assert!(parse_upper_winds("FT 3000\nABC 1é2").is_err());
+
+        let winds = parse_upper_winds("5é1N 00130W\n05 270 15 +03").unwrap();
+        assert_eq!(winds.stations[0].position, None);
+        assert!(parse_upper_winds("5230N 00130W\n05 27°15 +03").is_err());
+    }
+
+    #[test]
+    fn legs_take_the_nearest_forecast_at_their_altitude() {
+        let winds = parse_upper_winds(SPOT).unwrap();
+
+        // Halfway between 2000 and 5000 ft, the stronger wind pulling the direction round
+        let south = leg(
+            "3.5",
+            Some(Coordinate {
+                latitude: 52.4,
+                longitude: -1.4,
+            }),
+        );
+        let (direction, speed) = winds.wind_for(&south).unwrap();
+        assert_eq!((direction, speed), (Degree::new(256.), 17.));
+
+        let north = leg(
+            "2.0",
+            Some(Coordinate {
+                latitude: 53.9,
+                longitude: -2.8,
+            }),
+        );
+        assert_eq!(winds.wind_for(&north), Some((Degree::new(300.), 20.)));
+
+        // No position, and more than one place it could be
+        assert_eq!(winds.wind_for(&leg("5", None)), None);
+        assert_eq!(winds.wind_for(&leg("", None)), None);
+        assert_eq!(planned_altitude("2.2"), Some(2200.));
+    }
+
+    #[test]
+    fn legs_outside_the_forecast_keep_their_own_wind() {
+        let winds = parse_upper_winds(FB).unwrap();
+
+        let mut den = leg("2", None);
+        den.name.1 = "DEN".to_owned();
+        assert_eq!(winds.wind_for(&den), None);
+        den.planned = "9".to_owned();
+        assert_eq!(winds.wind_for(&den), Some((Degree::new(240.), 18.)));
+        den.planned = "40".to_owned();
+        assert_eq!(winds.wind_for(&den), None);
+
+        // A UK leg against a US forecast
+        let mut farnborough = leg("6", None);
+        farnborough.name.0 = "EGLF".to_owned();
+        assert_eq!(winds.wind_for(&farnborough), None);
+
+        // With a single place it's used whatever the legs are called
+        let single = parse_upper_winds("5230N 00130W\n02 250 15 +08\n05 260 20 +03").unwrap();
+        assert_eq!(
+            single.wind_for(&farnborough),
+            None,
+            "6000 ft is above the forecast"
+        );
+        farnborough.planned = "2".to_owned();
+        assert_eq!(
+            single.wind_for(&farnborough),
+            Some((Degree::new(250.), 15.))
+        );
+    }
+
+    #[test]
+    fn calc_legs_applies_the_forecast() {
+        let winds = parse_upper_winds(SPOT).unwrap();
+        let here = Coordinate {
+            latitude: 52.5,
+            longitude: -1.5,
+        };
+        let legs = [leg("5", Some(here)), leg("n/a", Some(here))];
+
+        let calcs = calc_legs(&legs, Some(&winds));
+
+        let (_, forecast) = &calcs[0];
+        assert_eq!(forecast.wind_direction, Degree::new(260.));
+        assert_eq!(forecast.wind_speed, 20.);
+        // Without a planned altitude the leg keeps its own wind
+        let (_, typed) = &calcs[1];
+        assert_eq!(typed.wind_speed, 0.);
+        assert_eq!(typed.ground_speed, 100.);
+    }
+}
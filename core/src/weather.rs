@@ -0,0 +1,1097 @@
+use crate::canvas::{init_page, Canvas};
+use crate::draw_utils::{disclaimer, horizontal_line, wrap, write};
+
+use pdf::{FontStyle, PDFPageBuilder};
+
+const MARGIN_SIDE: f64 = 5.;
+const MARGIN_BOTTOM: f64 = 8.;
+const INDENT: f64 = 3.;
+const TITLE_Y: f64 = 14.;
+const FONT_SIZE: f64 = 9.;
+const FONT_TITLE_SIZE: f64 = 12.;
+const LINE_HEIGHT: f64 = 4.;
+const SECTION_GAP: f64 = 2.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    Metar,
+    Speci,
+    Taf,
+}
+
+impl ReportKind {
+    fn name(self) -> &'static str {
+        match self {
+            ReportKind::Metar => "METAR",
+            ReportKind::Speci => "SPECI",
+            ReportKind::Taf => "TAF",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindUnit {
+    Knots,
+    MetresPerSecond,
+    KilometresPerHour,
+}
+
+impl WindUnit {
+    fn to_knots(self, speed: u32) -> f64 {
+        let speed = speed as f64;
+        match self {
+            WindUnit::Knots => speed,
+            WindUnit::MetresPerSecond => speed * 1.943_844,
+            WindUnit::KilometresPerHour => speed / 1.852,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            WindUnit::Knots => "kt",
+            WindUnit::MetresPerSecond => "m/s",
+            WindUnit::KilometresPerHour => "km/h",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wind {
+    /// Degrees true the wind blows from, or `None` when reported as variable (VRB).
+    pub direction: Option<u32>,
+    pub speed: u32,
+    pub gust: Option<u32>,
+    pub unit: WindUnit,
+    /// Extremes the direction varies between, such as 210V270.
+    pub varying: Option<(u32, u32)>,
+}
+
+impl Wind {
+    pub fn speed_knots(&self) -> f64 {
+        self.unit.to_knots(self.speed)
+    }
+
+    pub fn gust_knots(&self) -> Option<f64> {
+        self.gust.map(|gust| self.unit.to_knots(gust))
+    }
+
+    fn describe(&self) -> String {
+        let unit = self.unit.symbol();
+        if self.speed == 0 && self.gust.is_none() {
+            return "Wind calm".to_owned();
+        }
+
+        let mut text = match self.direction {
+            Some(direction) => format!("Wind {direction:03}° {} {unit}", self.speed),
+            None => format!("Wind variable {} {unit}", self.speed),
+        };
+        if let Some(gust) = self.gust {
+            text += &format!(", gusting {gust} {unit}");
+        }
+        if let Some((from, to)) = self.varying {
+            text += &format!(", varying between {from:03}° and {to:03}°");
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Visibility {
+    /// 9999 stands for 10 km or more.
+    Metres(u32),
+    StatuteMiles {
+        miles: f64,
+        or_more: bool,
+    },
+}
+
+impl Visibility {
+    fn describe(&self) -> String {
+        match *self {
+            Visibility::Metres(9999) => "Visibility 10 km or more".to_owned(),
+            Visibility::Metres(metres) if metres >= 5000 => {
+                format!("Visibility {} km", metres / 1000)
+            }
+            Visibility::Metres(metres) => format!("Visibility {metres} m"),
+            Visibility::StatuteMiles { miles, or_more } => {
+                let more = if or_more { " or more" } else { "" };
+                format!("Visibility {miles} SM{more}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudCover {
+    Few,
+    Scattered,
+    Broken,
+    Overcast,
+    /// The sky is obscured and the vertical visibility is given instead.
+    VerticalVisibility,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cloud {
+    pub cover: CloudCover,
+    /// Feet above the aerodrome, when reported.
+    pub base: Option<u32>,
+    /// CB or TCU.
+    pub convective: Option<String>,
+}
+
+impl Cloud {
+    fn describe(&self) -> String {
+        let base = match self.base {
+            Some(base) => format!("{base} ft"),
+            None => "unknown height".to_owned(),
+        };
+        let mut text = match self.cover {
+            CloudCover::Few => format!("few at {base}"),
+            CloudCover::Scattered => format!("scattered at {base}"),
+            CloudCover::Broken => format!("broken at {base}"),
+            CloudCover::Overcast => format!("overcast at {base}"),
+            CloudCover::VerticalVisibility => format!("sky obscured, vertical visibility {base}"),
+        };
+        match self.convective.as_deref() {
+            Some("CB") => text += " (cumulonimbus)",
+            Some("TCU") => text += " (towering cumulus)",
+            _ => {}
+        }
+        text
+    }
+}
+
+/// Wind, visibility, weather and cloud, as reported for the whole report or for a change group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Conditions {
+    pub wind: Option<Wind>,
+    pub visibility: Option<Visibility>,
+    /// Visibility 10 km or more, no cloud below 5000 ft and no significant weather.
+    pub cavok: bool,
+    /// Present weather groups as reported, such as "-RA" or "VCSH".
+    pub weather: Vec<String>,
+    pub clouds: Vec<Cloud>,
+    /// NSC, SKC, CLR or NCD.
+    pub no_cloud: bool,
+    /// NSW, the end of significant weather.
+    pub no_weather: bool,
+}
+
+impl Conditions {
+    /// Takes in the group if it belongs to the conditions, returning whether it did.
+    fn take(&mut self, group: &str) -> bool {
+        if group == "CAVOK" {
+            self.cavok = true;
+        } else if matches!(group, "NSC" | "SKC" | "CLR" | "NCD") {
+            self.no_cloud = true;
+        } else if group == "NSW" {
+            self.no_weather = true;
+        } else if let Some(wind) = parse_wind(group) {
+            self.wind = Some(wind);
+        } else if let Some(varying) = parse_varying(group) {
+            match &mut self.wind {
+                Some(wind) => wind.varying = Some(varying),
+                None => return false,
+            }
+        } else if let Some(visibility) = parse_visibility(group) {
+            // A second visibility group is the minimum in one direction.
+            if self.visibility.is_some() {
+                return false;
+            }
+            self.visibility = Some(visibility);
+        } else if let Some(cloud) = parse_cloud(group) {
+            self.clouds.push(cloud);
+        } else if is_weather(group) {
+            self.weather.push(group.to_owned());
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(wind) = &self.wind {
+            lines.push(wind.describe());
+        }
+        if self.cavok {
+            lines.push(
+                "CAVOK: visibility 10 km or more, no cloud below 5000 ft, no significant weather"
+                    .to_owned(),
+            );
+        }
+        if let Some(visibility) = &self.visibility {
+            lines.push(visibility.describe());
+        }
+        if !self.weather.is_empty() {
+            let weather: Vec<String> = self.weather.iter().map(|w| describe_weather(w)).collect();
+            lines.push(format!("Weather: {}", weather.join(", ")));
+        }
+        if self.no_weather {
+            lines.push("No significant weather".to_owned());
+        }
+        if !self.clouds.is_empty() {
+            let clouds: Vec<String> = self.clouds.iter().map(Cloud::describe).collect();
+            lines.push(format!("Cloud: {}", clouds.join(", ")));
+        }
+        if self.no_cloud {
+            lines.push("No significant cloud".to_owned());
+        }
+        lines
+    }
+}
+
+/// A BECMG, TEMPO, FM or PROB group, with the conditions it brings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// The group's keywords, such as "TEMPO" or "PROB30 TEMPO".
+    pub kind: String,
+    /// When it applies, as reported, such as "2812/2816" or "FM1130 TL1230".
+    pub period: String,
+    pub conditions: Conditions,
+}
+
+impl Change {
+    fn heading(&self) -> String {
+        let kind = match self.kind.as_str() {
+            "BECMG" => "Becoming".to_owned(),
+            "TEMPO" => "Temporarily".to_owned(),
+            "FM" => "From".to_owned(),
+            kind => match kind.strip_prefix("PROB") {
+                Some(rest) => {
+                    let (percent, tempo) = rest.split_once(' ').unwrap_or((rest, ""));
+                    match tempo {
+                        "" => format!("{percent}% probability"),
+                        _ => format!("{percent}% probability, temporarily"),
+                    }
+                }
+                None => kind.to_owned(),
+            },
+        };
+        match self.period.as_str() {
+            "" => kind,
+            period => format!("{kind} {}", describe_period(period)),
+        }
+    }
+}
+
+/// A decoded METAR, SPECI or TAF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub kind: ReportKind,
+    pub station: String,
+    /// Observation or issue time, such as "281150Z".
+    pub time: Option<String>,
+    /// Period a TAF is valid for, such as "2812/2912".
+    pub valid: Option<String>,
+    pub conditions: Conditions,
+    pub temperature: Option<i32>,
+    pub dew_point: Option<i32>,
+    /// Hectopascals, converted from inches of mercury for A groups.
+    pub qnh: Option<u32>,
+    pub no_change: bool,
+    /// Trend groups of a METAR and change groups of a TAF, in order.
+    pub changes: Vec<Change>,
+    /// Groups that weren't decoded, such as runway visual range or TAF temperatures.
+    pub undecoded: Vec<String>,
+    pub remarks: Option<String>,
+}
+
+impl Report {
+    /// The kind of report and the station, such as "METAR EGLL".
+    pub fn title(&self) -> String {
+        format!("{} {}", self.kind.name(), self.station)
+    }
+
+    /// The report in words, as sections of a heading and its lines.
+    pub fn decode(&self) -> Vec<(String, Vec<String>)> {
+        let mut heading = match (&self.time, self.kind) {
+            (Some(time), ReportKind::Taf) => format!("Issued {}", describe_time(time)),
+            (Some(time), _) => format!("Observed {}", describe_time(time)),
+            (None, _) => "Conditions".to_owned(),
+        };
+        if let Some(valid) = &self.valid {
+            heading += &format!(", valid {}", describe_period(valid));
+        }
+
+        let mut lines = self.conditions.describe();
+        match (self.temperature, self.dew_point) {
+            (Some(temperature), Some(dew_point)) => lines.push(format!(
+                "Temperature {temperature}°C, dew point {dew_point}°C"
+            )),
+            (Some(temperature), None) => lines.push(format!("Temperature {temperature}°C")),
+            _ => {}
+        }
+        if let Some(qnh) = self.qnh {
+            lines.push(format!("QNH {qnh} hPa"));
+        }
+        if self.no_change {
+            lines.push("No significant change expected".to_owned());
+        }
+        if !self.undecoded.is_empty() {
+            lines.push(format!("Not decoded: {}", self.undecoded.join(" ")));
+        }
+        if let Some(remarks) = &self.remarks {
+            lines.push(format!("Remarks: {remarks}"));
+        }
+
+        let mut sections = vec![(heading, lines)];
+        sections.extend(
+            self.changes
+                .iter()
+                .map(|change| (change.heading(), change.conditions.describe())),
+        );
+        sections
+    }
+
+    /// The surface wind's components on each of the runways, in words. Runways that aren't
+    /// designators, and reports without a steady wind direction, give nothing.
+    pub fn runway_winds(&self, runways: &[String]) -> Vec<String> {
+        let Some(wind) = &self.conditions.wind else {
+            return vec![];
+        };
+        runways
+            .iter()
+            .filter_map(|runway| runway_wind(wind, runway).map(|components| (runway, components)))
+            .map(|(runway, components)| describe_runway_wind(runway.trim(), &components))
+            .collect()
+    }
+}
+
+/// Head and crosswind components of a wind on a runway, in knots. A negative headwind is a
+/// tailwind; a positive crosswind comes from the right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunwayWind {
+    pub headwind: f64,
+    pub crosswind: f64,
+    pub gust_headwind: Option<f64>,
+    pub gust_crosswind: Option<f64>,
+}
+
+/// The heading of a runway from its designator, such as 250 for "25L".
+pub fn runway_heading(designator: &str) -> Option<u32> {
+    let designator = designator.trim().to_uppercase();
+    let number = designator.trim_end_matches(['L', 'R', 'C']);
+    if number.is_empty() || number.len() > 2 || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    match number.parse::<u32>().ok()? {
+        number @ 1..=36 => Some(number * 10),
+        _ => None,
+    }
+}
+
+/// Splits the wind into components along and across the runway. The designator is magnetic
+/// and a METAR's wind true, which is ignored: it's a few degrees either way in most places.
+pub fn runway_wind(wind: &Wind, runway: &str) -> Option<RunwayWind> {
+    let heading = runway_heading(runway)?;
+    let direction = match wind.direction {
+        Some(direction) => direction,
+        None if wind.speed == 0 => heading,
+        None => return None,
+    };
+
+    let angle = (direction as f64 - heading as f64).to_radians();
+    let components = |speed: f64| (speed * angle.cos(), speed * angle.sin());
+
+    let (headwind, crosswind) = components(wind.speed_knots());
+    let gust = wind.gust_knots().map(components);
+    Some(RunwayWind {
+        headwind,
+        crosswind,
+        gust_headwind: gust.map(|(headwind, _)| headwind),
+        gust_crosswind: gust.map(|(_, crosswind)| crosswind),
+    })
+}
+
+fn describe_runway_wind(runway: &str, wind: &RunwayWind) -> String {
+    let along = |headwind: f64| match headwind.round() {
+        headwind if headwind < 0. => format!("{} kt tailwind", -headwind),
+        headwind => format!("{headwind} kt headwind"),
+    };
+    let across = |crosswind: f64| match crosswind.round() {
+        crosswind if crosswind < 0. => format!("{} kt crosswind from the left", -crosswind),
+        crosswind if crosswind > 0. => format!("{crosswind} kt crosswind from the right"),
+        _ => "no crosswind".to_owned(),
+    };
+
+    let mut text = format!(
+        "Runway {runway}: {}, {}",
+        along(wind.headwind),
+        across(wind.crosswind)
+    );
+    if let (Some(headwind), Some(crosswind)) = (wind.gust_headwind, wind.gust_crosswind) {
+        text += &format!(" (gusts {}, {})", along(headwind), across(crosswind));
+    }
+    text
+}
+
+/// Decodes a METAR, SPECI or TAF as pasted, one report at a time. Reports without a
+/// METAR/SPECI/TAF keyword are read as METARs.
+pub fn parse_report(text: &str) -> Result<Report, String> {
+    let text = text.trim().trim_end_matches('=');
+    let mut groups = text.split_whitespace().peekable();
+
+    let kind = match groups.peek().copied() {
+        Some("METAR") => ReportKind::Metar,
+        Some("SPECI") => ReportKind::Speci,
+        Some("TAF") => ReportKind::Taf,
+        Some(_) => ReportKind::Metar,
+        None => return Err("The report is empty".to_owned()),
+    };
+    while groups
+        .peek()
+        .is_some_and(|group| matches!(*group, "METAR" | "SPECI" | "TAF" | "AMD" | "COR"))
+    {
+        groups.next();
+    }
+
+    let station = match groups.next() {
+        Some(station)
+            if station.len() == 4 && station.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            station.to_owned()
+        }
+        Some(group) => return Err(format!("Expected a station code, found '{group}'")),
+        None => return Err("The report has no station code".to_owned()),
+    };
+
+    let mut report = Report {
+        kind,
+        station,
+        time: None,
+        valid: None,
+        conditions: Conditions::default(),
+        temperature: None,
+        dew_point: None,
+        qnh: None,
+        no_change: false,
+        changes: vec![],
+        undecoded: vec![],
+        remarks: None,
+    };
+
+    if let Some(time) = groups.next_if(|group| is_time(group)) {
+        report.time = Some(time.to_owned());
+    }
+    if kind == ReportKind::Taf {
+        if let Some(valid) = groups.next_if(|group| is_period(group)) {
+            report.valid = Some(valid.to_owned());
+        }
+    }
+
+    while let Some(group) = groups.next() {
+        if group == "RMK" {
+            report.remarks = Some(groups.by_ref().collect::<Vec<_>>().join(" "));
+            break;
+        }
+
+        if let Some(kind) = change_kind(group) {
+            let mut kind = kind;
+            if kind.starts_with("PROB") && groups.next_if_eq(&"TEMPO").is_some() {
+                kind += " TEMPO";
+            }
+            let mut period = match group.strip_prefix("FM") {
+                Some(time) if time.len() == 6 => time.to_owned(),
+                _ => String::new(),
+            };
+            while let Some(time) = groups.next_if(|group| is_period(group) || is_trend_time(group))
+            {
+                if !period.is_empty() {
+                    period.push(' ');
+                }
+                period += time;
+            }
+            report.changes.push(Change {
+                kind,
+                period,
+                conditions: Conditions::default(),
+            });
+            continue;
+        }
+
+        if group == "NOSIG" {
+            report.no_change = true;
+        } else if group == "AUTO" {
+            // Observed without an observer; there's nothing more to decode.
+        } else if let Some((temperature, dew_point)) = parse_temperatures(group) {
+            report.temperature = Some(temperature);
+            report.dew_point = dew_point;
+        } else if let Some(qnh) = parse_qnh(group) {
+            report.qnh = Some(qnh);
+        } else {
+            // Fractions of a statute mile can follow a whole number: 1 1/2SM.
+            let joined;
+            let group = match groups.next_if(|next| {
+                group.chars().all(|c| c.is_ascii_digit())
+                    && group.len() == 1
+                    && next.ends_with("SM")
+            }) {
+                Some(next) => {
+                    joined = format!("{group} {next}");
+                    joined.as_str()
+                }
+                None => group,
+            };
+
+            let conditions = match report.changes.last_mut() {
+                Some(change) => &mut change.conditions,
+                None => &mut report.conditions,
+            };
+            if !conditions.take(group) {
+                report.undecoded.push(group.to_owned());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Adds a page with a METAR or TAF as reported and decoded, with the surface wind on the
+/// runways given.
+pub fn create_weather_page(
+    builder: &mut PDFPageBuilder,
+    report: &Report,
+    text: &str,
+    runways: &[String],
+) {
+    let mut layer = builder.content_builder();
+    draw_weather(&mut layer, report, text, runways);
+}
+
+/// Draws the report onto a fresh page, leaving off what doesn't fit.
+pub fn draw_weather(layer: &mut impl Canvas, report: &Report, text: &str, runways: &[String]) {
+    init_page(layer);
+    disclaimer(layer);
+
+    let (page_width, page_height) = layer.page_size();
+    let width = page_width - 2. * MARGIN_SIDE;
+    let bottom = page_height - MARGIN_BOTTOM;
+
+    write(
+        layer,
+        &report.title(),
+        (MARGIN_SIDE, TITLE_Y),
+        &(FontStyle::Bold, FONT_TITLE_SIZE),
+    );
+
+    let mut y = TITLE_Y + 2.;
+    let font = (FontStyle::Italics, FONT_SIZE);
+    for line in wrap(text.trim(), width, &font) {
+        y += LINE_HEIGHT;
+        write(layer, &line, (MARGIN_SIDE, y), &font);
+    }
+
+    let mut sections = report.decode();
+    let runway_winds = report.runway_winds(runways);
+    if !runway_winds.is_empty() {
+        sections.insert(1, ("Surface wind on the runways".to_owned(), runway_winds));
+    }
+
+    layer.save_graphics_state();
+    layer.line_width(0.25);
+
+    'sections: for (heading, lines) in sections {
+        y += SECTION_GAP;
+        horizontal_line(layer, (MARGIN_SIDE, y), width);
+        y += LINE_HEIGHT;
+        if y > bottom {
+            break;
+        }
+        write(
+            layer,
+            &heading,
+            (MARGIN_SIDE, y),
+            &(FontStyle::Bold, FONT_SIZE),
+        );
+
+        let font = (FontStyle::Normal, FONT_SIZE);
+        for line in lines {
+            for line in wrap(&line, width - INDENT, &font) {
+                y += LINE_HEIGHT;
+                if y > bottom {
+                    break 'sections;
+                }
+                write(layer, &line, (MARGIN_SIDE + INDENT, y), &font);
+            }
+        }
+    }
+
+    layer.restore_graphics_state();
+}
+
+fn is_time(group: &str) -> bool {
+    group.len() == 7 && group.ends_with('Z') && group[..6].chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_period(group: &str) -> bool {
+    match group.split_once('/') {
+        Some((from, to)) => [from, to]
+            .iter()
+            .all(|part| part.len() == 4 && part.chars().all(|c| c.is_ascii_digit())),
+        None => false,
+    }
+}
+
+fn is_trend_time(group: &str) -> bool {
+    ["FM", "TL", "AT"].iter().any(|prefix| {
+        group
+            .strip_prefix(prefix)
+            .is_some_and(|time| time.len() == 4 && time.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+fn change_kind(group: &str) -> Option<String> {
+    match group {
+        "BECMG" | "TEMPO" => Some(group.to_owned()),
+        "PROB30" | "PROB40" => Some(group.to_owned()),
+        _ => match group.strip_prefix("FM") {
+            Some(time) if time.len() == 6 && time.chars().all(|c| c.is_ascii_digit()) => {
+                Some("FM".to_owned())
+            }
+            _ => None,
+        },
+    }
+}
+
+/// "281150Z" as "day 28 at 1150Z".
+fn describe_time(time: &str) -> String {
+    format!("day {} at {}Z", &time[..2], &time[2..6])
+}
+
+/// "2812/2816" as "day 28 1200Z to day 28 1600Z", and "281200" as "day 28 1200Z".
+fn describe_period(period: &str) -> String {
+    let day_hour = |time: &str| format!("day {} {}00Z", &time[..2], &time[2..4]);
+    if is_period(period) {
+        let (from, to) = period.split_once('/').unwrap_or_default();
+        format!("{} to {}", day_hour(from), day_hour(to))
+    } else if period.len() == 6 && period.chars().all(|c| c.is_ascii_digit()) {
+        format!("day {} {}Z", &period[..2], &period[2..])
+    } else {
+        period.to_owned()
+    }
+}
+
+fn parse_number(digits: &str) -> Option<u32> {
+    match !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        true => digits.parse().ok(),
+        false => None,
+    }
+}
+
+fn parse_wind(group: &str) -> Option<Wind> {
+    let (body, unit) = [
+        ("KT", WindUnit::Knots),
+        ("MPS", WindUnit::MetresPerSecond),
+        ("KMH", WindUnit::KilometresPerHour),
+    ]
+    .into_iter()
+    .find_map(|(suffix, unit)| group.strip_suffix(suffix).map(|body| (body, unit)))?;
+
+    if body.len() < 5 || !body.is_char_boundary(3) {
+        return None;
+    }
+    let (direction, speed) = body.split_at(3);
+    let direction = match direction {
+        "VRB" => None,
+        direction => Some(parse_number(direction)?),
+    };
+    let (speed, gust) = match speed.split_once('G') {
+        Some((speed, gust)) => (speed, Some(parse_number(gust)?)),
+        None => (speed, None),
+    };
+
+    Some(Wind {
+        direction,
+        speed: parse_number(speed)?,
+        gust,
+        unit,
+        varying: None,
+    })
+}
+
+fn parse_varying(group: &str) -> Option<(u32, u32)> {
+    let (from, to) = group.split_once('V')?;
+    match (from.len(), to.len()) {
+        (3, 3) => Some((parse_number(from)?, parse_number(to)?)),
+        _ => None,
+    }
+}
+
+fn parse_visibility(group: &str) -> Option<Visibility> {
+    if let Some(miles) = group.strip_suffix("SM") {
+        let (miles, or_more) = match miles.strip_prefix('P') {
+            Some(miles) => (miles, true),
+            None => (miles.strip_prefix('M').unwrap_or(miles), false),
+        };
+        let miles = miles
+            .split(' ')
+            .map(|part| match part.split_once('/') {
+                Some((numerator, denominator)) => {
+                    let denominator = parse_number(denominator)?;
+                    (denominator != 0)
+                        .then(|| parse_number(numerator).map(|n| n as f64 / denominator as f64))?
+                }
+                None => parse_number(part).map(f64::from),
+            })
+            .sum::<Option<f64>>()?;
+        return Some(Visibility::StatuteMiles { miles, or_more });
+    }
+
+    if group.len() < 4 || !group.is_char_boundary(4) {
+        return None;
+    }
+    let (metres, direction) = group.split_at(4);
+    let direction_ok = direction == "NDV"
+        || (direction.len() <= 2 && direction.chars().all(|c| "NESW".contains(c)));
+    match direction_ok {
+        true => parse_number(metres).map(Visibility::Metres),
+        false => None,
+    }
+}
+
+fn parse_cloud(group: &str) -> Option<Cloud> {
+    let (cover, rest) = [
+        ("FEW", CloudCover::Few),
+        ("SCT", CloudCover::Scattered),
+        ("BKN", CloudCover::Broken),
+        ("OVC", CloudCover::Overcast),
+        ("VV", CloudCover::VerticalVisibility),
+    ]
+    .into_iter()
+    .find_map(|(prefix, cover)| group.strip_prefix(prefix).map(|rest| (cover, rest)))?;
+
+    if rest.len() < 3 || !rest.is_char_boundary(3) {
+        return None;
+    }
+    let (base, convective) = rest.split_at(3);
+    let base = match base {
+        "///" => None,
+        base => Some(parse_number(base)? * 100),
+    };
+    let convective = match convective {
+        "" | "///" => None,
+        "CB" | "TCU" => Some(convective.to_owned()),
+        _ => return None,
+    };
+
+    Some(Cloud {
+        cover,
+        base,
+        convective,
+    })
+}
+
+fn parse_temperature(text: &str) -> Option<i32> {
+    let (negative, digits) = match text.strip_prefix('M') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    if digits.len() != 2 {
+        return None;
+    }
+    let value = parse_number(digits)? as i32;
+    Some(if negative { -value } else { value })
+}
+
+fn parse_temperatures(group: &str) -> Option<(i32, Option<i32>)> {
+    let (temperature, dew_point) = group.split_once('/')?;
+    let temperature = parse_temperature(temperature)?;
+    let dew_point = match dew_point {
+        "" | "//" => None,
+        dew_point => Some(parse_temperature(dew_point)?),
+    };
+    Some((temperature, dew_point))
+}
+
+fn parse_qnh(group: &str) -> Option<u32> {
+    if let Some(hectopascals) = group.strip_prefix('Q') {
+        return (hectopascals.len() == 4)
+            .then(|| parse_number(hectopascals))
+            .flatten();
+    }
+    let inches = group.strip_prefix('A')?;
+    if inches.len() != 4 {
+        return None;
+    }
+    let inches = parse_number(inches)? as f64 / 100.;
+    Some((inches * 33.863_886).round() as u32)
+}
+
+const DESCRIPTORS: [(&str, &str); 8] = [
+    ("MI", "shallow"),
+    ("BC", "patches of"),
+    ("PR", "partial"),
+    ("DR", "low drifting"),
+    ("BL", "blowing"),
+    ("FZ", "freezing"),
+    ("SH", "showers"),
+    ("TS", "thunderstorm"),
+];
+
+const PHENOMENA: [(&str, &str); 22] = [
+    ("DZ", "drizzle"),
+    ("RA", "rain"),
+    ("SN", "snow"),
+    ("SG", "snow grains"),
+    ("IC", "ice crystals"),
+    ("PL", "ice pellets"),
+    ("GR", "hail"),
+    ("GS", "small hail"),
+    ("UP", "unknown precipitation"),
+    ("BR", "mist"),
+    ("FG", "fog"),
+    ("FU", "smoke"),
+    ("VA", "volcanic ash"),
+    ("DU", "dust"),
+    ("SA", "sand"),
+    ("HZ", "haze"),
+    ("PY", "spray"),
+    ("PO", "dust whirls"),
+    ("SQ", "squalls"),
+    ("FC", "funnel cloud"),
+    ("SS", "sandstorm"),
+    ("DS", "duststorm"),
+];
+
+fn split_intensity(group: &str) -> (&str, &str) {
+    for prefix in ["-", "+", "VC"] {
+        if let Some(rest) = group.strip_prefix(prefix) {
+            return (prefix, rest);
+        }
+    }
+    ("", group)
+}
+
+fn weather_codes(codes: &str) -> Option<Vec<&'static str>> {
+    if codes.is_empty() || !codes.len().is_multiple_of(2) || !codes.is_ascii() {
+        return None;
+    }
+    (0..codes.len())
+        .step_by(2)
+        .map(|idx| {
+            let code = &codes[idx..idx + 2];
+            DESCRIPTORS
+                .iter()
+                .chain(PHENOMENA.iter())
+                .find(|(known, _)| *known == code)
+                .map(|(known, _)| *known)
+        })
+        .collect()
+}
+
+fn is_weather(group: &str) -> bool {
+    let (_, codes) = split_intensity(group);
+    weather_codes(codes).is_some()
+}
+
+/// "-SHRA" as "light rain showers", "TSRA" as "thunderstorm with rain".
+fn describe_weather(group: &str) -> String {
+    let (intensity, codes) = split_intensity(group);
+    let Some(codes) = weather_codes(codes) else {
+        return group.to_owned();
+    };
+
+    let word = |code: &str| {
+        DESCRIPTORS
+            .iter()
+            .chain(PHENOMENA.iter())
+            .find(|(known, _)| *known == code)
+            .map_or("", |(_, word)| *word)
+    };
+    let descriptors: Vec<&str> = codes
+        .iter()
+        .filter(|code| DESCRIPTORS.iter().any(|(known, _)| known == *code))
+        .filter(|code| !matches!(**code, "SH" | "TS"))
+        .map(|code| word(code))
+        .collect();
+    let phenomena: Vec<&str> = codes
+        .iter()
+        .filter(|code| PHENOMENA.iter().any(|(known, _)| known == *code))
+        .map(|code| word(code))
+        .collect();
+
+    let mut text = descriptors.join(" ");
+    if !phenomena.is_empty() {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text += &phenomena.join(" and ");
+    }
+    if codes.contains(&"SH") {
+        text = match text.is_empty() {
+            true => "showers".to_owned(),
+            false => format!("{text} showers"),
+        };
+    }
+    if codes.contains(&"TS") {
+        text = match text.is_empty() {
+            true => "thunderstorm".to_owned(),
+            false => format!("thunderstorm with {text}"),
+        };
+    }
+
+    match intensity {
+        "-" => format!("light {text}"),
+        "+" => format!("heavy {text}"),
+        "VC" => format!("{text} in the vicinity"),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pdf::{ToMM, A5};
+
+    use crate::canvas::{Command, RecordingCanvas};
+
+    use super::*;
+
+    #[test]
+    fn metar_is_decoded() {
+        let report = parse_report(
+            "METAR EGLL 281150Z AUTO 24012G25KT 210V270 9999 -SHRA FEW025 BKN040CB 15/09 Q1013 NOSIG=",
+        )
+        .unwrap();
+
+        assert_eq!(report.kind, ReportKind::Metar);
+        assert_eq!(report.station, "EGLL");
+        assert_eq!(report.time.as_deref(), Some("281150Z"));
+        let wind = report.conditions.wind.clone().unwrap();
+        assert_eq!(wind.direction, Some(240));
+        assert_eq!((wind.speed, wind.gust), (12, Some(25)));
+        assert_eq!(wind.varying, Some((210, 270)));
+        assert_eq!(report.conditions.visibility, Some(Visibility::Metres(9999)));
+        assert_eq!(report.conditions.weather, ["-SHRA"]);
+        assert_eq!(report.conditions.clouds.len(), 2);
+        assert_eq!(report.conditions.clouds[1].base, Some(4000));
+        assert_eq!((report.temperature, report.dew_point), (Some(15), Some(9)));
+        assert_eq!(report.qnh, Some(1013));
+        assert!(report.no_change);
+        assert!(report.undecoded.is_empty());
+
+        let (_, lines) = &report.decode()[0];
+        assert_eq!(
+            lines[0],
+            "Wind 240° 12 kt, gusting 25 kt, varying between 210° and 270°"
+        );
+        assert!(lines.contains(&"Weather: light rain showers".to_owned()));
+        assert!(
+            lines.contains(&"Cloud: few at 2500 ft, broken at 4000 ft (cumulonimbus)".to_owned())
+        );
+    }
+
+    #[test]
+    fn us_metars_use_miles_and_inches() {
+        let report =
+            parse_report("KJFK 281151Z VRB03KT 1 1/2SM BR OVC008 M02/M04 A2992 RMK AO2").unwrap();
+
+        let wind = report.conditions.wind.as_ref().unwrap();
+        assert_eq!(wind.direction, None);
+        assert_eq!(
+            report.conditions.visibility,
+            Some(Visibility::StatuteMiles {
+                miles: 1.5,
+                or_more: false
+            })
+        );
+        assert_eq!((report.temperature, report.dew_point), (Some(-2), Some(-4)));
+        assert_eq!(report.qnh, Some(1013));
+        assert_eq!(report.remarks.as_deref(), Some("AO2"));
+    }
+
+    #[test]
+    fn taf_change_groups_are_separate() {
+        let report = parse_report(
+            "TAF EGKK 281100Z 2812/2912 23010KT 9999 SCT030 \
+             TEMPO 2814/2818 4000 RA BKN012 \
+             PROB30 TEMPO 2818/2822 TSRA \
+             BECMG 2900/2903 30015G28KT \
+             FM290900 CAVOK",
+        )
+        .unwrap();
+
+        assert_eq!(report.kind, ReportKind::Taf);
+        assert_eq!(report.valid.as_deref(), Some("2812/2912"));
+        assert_eq!(report.conditions.clouds.len(), 1);
+        let kinds: Vec<&str> = report.changes.iter().map(|c| c.kind.as_str()).collect();
+        assert_eq!(kinds, ["TEMPO", "PROB30 TEMPO", "BECMG", "FM"]);
+        assert_eq!(
+            report.changes[0].conditions.visibility,
+            Some(Visibility::Metres(4000))
+        );
+        assert_eq!(report.changes[3].period, "290900");
+        assert!(report.changes[3].conditions.cavok);
+
+        let sections = report.decode();
+        assert_eq!(
+            sections[0].0,
+            "Issued day 28 at 1100Z, valid day 28 1200Z to day 29 1200Z"
+        );
+        assert_eq!(
+            sections[2].0,
+            "30% probability, temporarily day 28 1800Z to day 28 2200Z"
+        );
+        assert_eq!(sections[2].1, ["Weather: thunderstorm with rain"]);
+    }
+
+    #[test]
+    fn unknown_groups_are_kept() {
+        let report =
+            parse_report("EGLL 281150Z 24012KT R27L/1200 9999 OVC010 15/09 Q1013").unwrap();
+
+        assert_eq!(report.undecoded, ["R27L/1200"]);
+        assert!(parse_report("").is_err());
+        assert!(parse_report("METAR 24012KT").is_err());
+    }
+
+    #[test]
+    fn runway_components() {
+        let wind = parse_wind("27020G30KT").unwrap();
+
+        let components = runway_wind(&wind, "24").unwrap();
+        assert_eq!(components.headwind.round(), 17.);
+        assert_eq!(components.crosswind.round(), 10.);
+        assert_eq!(components.gust_crosswind.map(f64::round), Some(15.));
+
+        let tailwind = runway_wind(&wind, "09").unwrap();
+        assert_eq!(tailwind.headwind.round(), -20.);
+        assert_eq!(runway_heading("25L"), Some(250));
+        assert_eq!(runway_heading("37"), None);
+        assert!(runway_wind(&parse_wind("VRB05KT").unwrap(), "24").is_none());
+
+        let report = parse_report("EGKA 281150Z 27020G30KT 9999 FEW030 15/09 Q1013").unwrap();
+        assert_eq!(
+            report.runway_winds(&["24".to_owned()]),
+            ["Runway 24: 17 kt headwind, 10 kt crosswind from the right \
+                 (gusts 26 kt headwind, 15 kt crosswind from the right)"]
+        );
+    }
+
+    #[test]
+    fn page_shows_decoded_report() {
+        let text = "METAR EGKA 281150Z 27020KT 9999 FEW030 15/09 Q1013";
+        let report = parse_report(text).unwrap();
+        let mut canvas = RecordingCanvas::new(A5.to_mm());
+
+        draw_weather(&mut canvas, &report, text, &["20".to_owned()]);
+
+        let texts: Vec<&str> = canvas
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                Command::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        for expected in [
+            "METAR EGKA",
+            "Wind 270° 20 kt",
+            "QNH 1013 hPa",
+            "Surface wind on the runways",
+        ] {
+            assert!(texts.contains(&expected), "{expected} in {texts:?}");
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Detail {
     pub tail: Option<String>,
@@ -11,20 +12,88 @@ pub struct Detail {
     pub field3: Option<String>,
 }
 
+/// Format version written to new plan files. Older files are upgraded on load by the
+/// migrations in `common`.
+pub const PLAN_VERSION: u32 = 5;
+
+/// Format version written to new profile files.
+pub const PROFILE_VERSION: u32 = 2;
+
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Plan {
-    #[serde(default)]
+    /// File format version. Older files are upgraded when loaded.
+    pub version: u32,
     pub detail: Detail,
-    #[serde(default)]
     pub diversions: Vec<Diversion>,
-    #[serde(default)]
     pub routes: Vec<Route>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = [])))]
     pub holds: Vec<Hold>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = [])))]
+    pub images: Vec<PlanImage>,
+    /// ICAO codes of the alternate aerodromes filed in flight plans.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = [])))]
+    pub alternates: Vec<String>,
+    /// METARs and TAFs pasted in for the flight, each decoded onto its own page.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = [])))]
+    pub weather: Vec<WeatherReport>,
+    /// A pasted upper-wind forecast, US FB or UK spot winds, whose winds replace those of
+    /// legs at the altitudes it covers.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = "")))]
+    pub upper_winds: String,
 }
 
+impl Default for Plan {
+    fn default() -> Self {
+        Plan {
+            version: PLAN_VERSION,
+            detail: Detail::default(),
+            diversions: vec![],
+            routes: vec![],
+            holds: vec![],
+            images: vec![],
+            alternates: vec![],
+            weather: vec![],
+            upper_winds: String::new(),
+        }
+    }
+}
+
+/// A photo, logo or diagram printed on its own page.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PlanImage {
+    #[serde(default)]
+    pub caption: String,
+    /// Base64 encoded PNG or JPEG file.
+    pub data: String,
+    /// Route whose plog pages the image follows; images without one go at the end.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = null)))]
+    pub after_route: Option<usize>,
+}
+
+/// A METAR or TAF as pasted, with the runways to work out the surface wind components for.
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct WeatherReport {
+    pub text: String,
+    /// Runway designators such as "07" or "25L".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = [])))]
+    pub runways: Vec<String>,
+}
+
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Hold {
     pub description: String,
@@ -33,9 +102,14 @@ pub struct Hold {
     pub wind: Velocity,
     pub aircraft_speed: i64,
     pub variation: i64,
+    /// Draw the diagram turned to the inbound track, with north up.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = false)))]
+    pub rotate_to_track: bool,
 }
 
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Velocity {
     pub angle: i64,
@@ -43,6 +117,7 @@ pub struct Velocity {
 }
 
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Diversion {
     pub wind: Velocity,
@@ -50,7 +125,9 @@ pub struct Diversion {
     pub variation: i64,
 }
 
+/// A line of route notes in the style it's printed with.
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum FontType {
     Bold(String),
@@ -86,15 +163,16 @@ impl FontType {
 }
 
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Route {
-    #[serde(default)]
     pub name: String,
     pub legs: Vec<Leg>,
     pub notes: Vec<FontType>,
 }
 
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Leg {
     pub from: String,
@@ -108,19 +186,96 @@ pub struct Leg {
 
     pub wind_direction: i64,
     pub wind_speed: i64,
+
+    /// Where the leg starts, when it was planned from coordinates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = null)))]
+    pub from_position: Option<Coordinate>,
+    /// Where the leg ends, when it was planned from coordinates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = null)))]
+    pub to_position: Option<Coordinate>,
+}
+
+/// A point on the earth in decimal degrees, north and east positive.
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct Coordinate {
+    pub latitude: f64,
+    pub longitude: f64,
 }
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+impl Coordinate {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+}
+
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ProfileConfig {
+    /// File format version. Older files are upgraded when loaded.
+    pub version: u32,
     pub aircraft_registrations: Vec<String>,
     pub pics: Vec<String>,
     pub call_signs: Vec<String>,
     pub saved_routes: Vec<SavedRoute>,
-    #[serde(default)]
     pub saved_holds: Vec<SavedHold>,
     pub default_leg_values: DefaultLegValues,
+    pub aircraft: AircraftDetails,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        ProfileConfig {
+            version: PROFILE_VERSION,
+            aircraft_registrations: vec![],
+            pics: vec![],
+            call_signs: vec![],
+            saved_routes: vec![],
+            saved_holds: vec![],
+            default_leg_values: DefaultLegValues::default(),
+            aircraft: AircraftDetails::default(),
+        }
+    }
+}
+
+/// What ICAO flight plans need to know about the aircraft and how it is flown.
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AircraftDetails {
+    /// ICAO type designator, such as `C172`.
+    pub aircraft_type: String,
+    /// Wake turbulence category, `L` for light aircraft.
+    pub wake_turbulence: String,
+    /// Equipment and capabilities as filed in Item 10, such as `SDFGY/S`.
+    pub equipment: String,
+    /// `V` for VFR, `I` for IFR, or `Y` or `Z` when they change en route.
+    pub flight_rules: String,
+    /// `G` for general aviation.
+    pub flight_type: String,
+    /// Item 18, such as `PBN/B2`. The flight plan adds `DEP/` and `DEST/` itself.
+    pub other_information: String,
+}
+
+impl Default for AircraftDetails {
+    fn default() -> Self {
+        AircraftDetails {
+            aircraft_type: String::new(),
+            wake_turbulence: "L".to_owned(),
+            equipment: String::new(),
+            flight_rules: "V".to_owned(),
+            flight_type: "G".to_owned(),
+            other_information: String::new(),
+        }
+    }
 }
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SavedHold {
     pub name: String,
@@ -133,6 +288,7 @@ pub struct SavedHold {
     pub wind_speed: i64,
 }
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SavedRoute {
     pub name: String,
@@ -141,6 +297,7 @@ pub struct SavedRoute {
     pub notes: Vec<FontType>,
 }
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DefaultLegValues {
     pub safe: String,
@@ -0,0 +1,66 @@
+use crate::{
+    ArrayObject, Coord, DictionaryObject, IndirectRef, NameObject, To72inch, TrimFraction,
+    HELVETICA,
+};
+
+// Print flag, so filled in values appear when the page is printed.
+const ANNOTATION_PRINT: usize = 4;
+
+/// A single line text field on a page, filled in by the pilot in a PDF viewer.
+pub(crate) struct TextField {
+    name: String,
+    rect: [f64; 4],
+}
+
+impl TextField {
+    /// Creates a field covering `size` mm from the top left `position`, on a page that
+    /// is `page_height` points high.
+    pub(crate) fn new(name: &str, (x, y): Coord, (width, height): Coord, page_height: f64) -> Self {
+        let rect = [
+            x.to_inch(),
+            page_height - (y + height).to_inch(),
+            (x + width).to_inch(),
+            page_height - y.to_inch(),
+        ];
+
+        Self {
+            name: name.to_owned(),
+            rect: rect.map(|value| value.trim_fraction()),
+        }
+    }
+
+    /// Field and widget annotation merged into one dictionary, as allowed for fields
+    /// with a single widget.
+    pub(crate) fn to_dictionary(&self, page: IndirectRef) -> DictionaryObject {
+        let mut dict = DictionaryObject::new();
+        dict.insert_strkey("Type", NameObject::new("Annot"));
+        dict.insert_strkey("Subtype", NameObject::new("Widget"));
+        dict.insert_strkey("FT", NameObject::new("Tx"));
+        dict.insert_strkey("T", self.name.as_str());
+        dict.insert_strkey("Rect", ArrayObject::new_from(self.rect));
+        dict.insert_strkey("F", ANNOTATION_PRINT);
+        dict.insert_strkey("P", page);
+        // The table lines are already drawn, so the widget itself has no border
+        dict.insert_strkey("Border", ArrayObject::new_from([0., 0., 0.]));
+        dict
+    }
+}
+
+/// The interactive form dictionary referenced from the catalog. Text is auto sized in
+/// Helvetica and viewers are asked to generate the appearance of each field.
+pub(crate) fn create_acro_form(fields: Vec<IndirectRef>, fonts: IndirectRef) -> DictionaryObject {
+    let mut field_array = ArrayObject::new();
+    for field in fields {
+        field_array.push(field);
+    }
+
+    let mut resources = DictionaryObject::new();
+    resources.insert_strkey("Font", fonts);
+
+    let mut dict = DictionaryObject::new();
+    dict.insert_strkey("Fields", field_array);
+    dict.insert_strkey("NeedAppearances", true);
+    dict.insert_strkey("DA", format!("/{HELVETICA} 0 Tf 0 g"));
+    dict.insert_strkey("DR", resources);
+    dict
+}
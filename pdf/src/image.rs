@@ -0,0 +1,441 @@
+use std::fmt::{Display, Formatter};
+
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_SIGNATURE: &[u8] = &[0xff, 0xd8];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImageError {
+    UnknownFormat,
+    Unsupported(&'static str),
+    Corrupt(&'static str),
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::UnknownFormat => write!(f, "Only PNG and JPEG images are supported"),
+            ImageError::Unsupported(msg) => write!(f, "Unsupported image: {msg}"),
+            ImageError::Corrupt(msg) => write!(f, "Corrupt image: {msg}"),
+        }
+    }
+}
+
+/// An image added to the document, used to place it on a page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageRef {
+    pub(crate) name: String,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl ImageRef {
+    /// Width divided by height.
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width as f64 / self.height as f64
+    }
+}
+
+/// Image samples ready to be written as an image XObject.
+pub(crate) struct DecodedImage {
+    pub width: usize,
+    pub height: usize,
+    pub colour_space: &'static str,
+    pub data: Vec<u8>,
+    /// Set when `data` is still encoded and must be written with this filter.
+    pub filter: Option<&'static str>,
+    /// Inverted CMYK, as written by Adobe applications.
+    pub inverted: bool,
+    pub alpha: Option<Vec<u8>>,
+}
+
+pub(crate) fn decode_image(data: &[u8]) -> Result<DecodedImage, ImageError> {
+    let image = if data.starts_with(PNG_SIGNATURE) {
+        decode_png(&data[PNG_SIGNATURE.len()..])?
+    } else if data.starts_with(JPEG_SIGNATURE) {
+        decode_jpeg(data)?
+    } else {
+        return Err(ImageError::UnknownFormat);
+    };
+
+    if image.width == 0 || image.height == 0 {
+        return Err(ImageError::Corrupt("empty image"));
+    }
+    Ok(image)
+}
+
+/// JPEG data is passed through untouched, only the header is read for the image size.
+fn decode_jpeg(data: &[u8]) -> Result<DecodedImage, ImageError> {
+    let truncated = ImageError::Corrupt("truncated JPEG");
+
+    let mut pos = JPEG_SIGNATURE.len();
+    let mut adobe = false;
+    loop {
+        // Markers may be preceded by any number of fill bytes
+        while data.get(pos) == Some(&0xff) && data.get(pos + 1) == Some(&0xff) {
+            pos += 1;
+        }
+
+        let (marker, len) = match data.get(pos..pos + 4) {
+            Some([0xff, marker, high, low]) => (*marker, u16::from_be_bytes([*high, *low])),
+            _ => return Err(truncated),
+        };
+        let segment = data
+            .get(pos + 4..pos + 2 + len as usize)
+            .ok_or(ImageError::Corrupt("truncated JPEG"))?;
+
+        match marker {
+            0xee if segment.starts_with(b"Adobe") => adobe = true,
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let [precision, h1, h0, w1, w0, components, ..] = segment else {
+                    return Err(truncated);
+                };
+                if *precision != 8 {
+                    return Err(ImageError::Unsupported("12 bit JPEG"));
+                }
+
+                let colour_space = match components {
+                    1 => "DeviceGray",
+                    3 => "DeviceRGB",
+                    4 => "DeviceCMYK",
+                    _ => return Err(ImageError::Unsupported("JPEG colour components")),
+                };
+
+                return Ok(DecodedImage {
+                    width: u16::from_be_bytes([*w1, *w0]) as usize,
+                    height: u16::from_be_bytes([*h1, *h0]) as usize,
+                    colour_space,
+                    data: data.to_vec(),
+                    filter: Some("DCTDecode"),
+                    inverted: adobe && *components == 4,
+                    alpha: None,
+                });
+            }
+            0xd9 | 0xda => return Err(ImageError::Corrupt("JPEG without a frame header")),
+            _ => {}
+        }
+
+        pos += 2 + len as usize;
+    }
+}
+
+struct PngHeader {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    colour_type: u8,
+}
+
+impl PngHeader {
+    fn channels(&self) -> usize {
+        match self.colour_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    fn stride(&self) -> Result<usize, ImageError> {
+        let bits = self
+            .width
+            .checked_mul(self.bits_per_pixel())
+            .ok_or(ImageError::Unsupported("PNG too large"))?;
+        Ok(bits.div_ceil(8))
+    }
+}
+
+fn decode_png(data: &[u8]) -> Result<DecodedImage, ImageError> {
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = vec![];
+
+    let mut pos = 0;
+    while let Some(length) = data.get(pos..pos + 4) {
+        let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize;
+        let kind = data
+            .get(pos + 4..pos + 8)
+            .ok_or(ImageError::Corrupt("truncated PNG chunk"))?;
+        let chunk = data
+            .get(pos + 8..(pos + 8).saturating_add(length))
+            .ok_or(ImageError::Corrupt("truncated PNG chunk"))?;
+
+        match kind {
+            b"IHDR" => {
+                let [w3, w2, w1, w0, h3, h2, h1, h0, bit_depth, colour_type, _, _, interlace] =
+                    chunk
+                else {
+                    return Err(ImageError::Corrupt("PNG header"));
+                };
+                if *interlace != 0 {
+                    return Err(ImageError::Unsupported("interlaced PNG"));
+                }
+                let valid = match colour_type {
+                    0 => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+                    3 => matches!(bit_depth, 1 | 2 | 4 | 8),
+                    2 | 4 | 6 => matches!(bit_depth, 8 | 16),
+                    _ => false,
+                };
+                if !valid {
+                    return Err(ImageError::Corrupt("PNG colour type"));
+                }
+
+                header = Some(PngHeader {
+                    width: u32::from_be_bytes([*w3, *w2, *w1, *w0]) as usize,
+                    height: u32::from_be_bytes([*h3, *h2, *h1, *h0]) as usize,
+                    bit_depth: *bit_depth,
+                    colour_type: *colour_type,
+                });
+            }
+            b"PLTE" => palette = chunk,
+            b"tRNS" => transparency = chunk,
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+
+        // Skip the chunk data and CRC
+        pos += 12 + length;
+    }
+
+    let header = header.ok_or(ImageError::Corrupt("PNG without a header"))?;
+
+    let stride = header.stride()?;
+    let expected = (stride + 1)
+        .checked_mul(header.height)
+        .ok_or(ImageError::Unsupported("PNG too large"))?;
+    let mut filtered = decompress_to_vec_zlib_with_limit(&compressed, expected)
+        .map_err(|_| ImageError::Corrupt("PNG image data"))?;
+    if filtered.len() != expected {
+        return Err(ImageError::Corrupt("PNG image data"));
+    }
+
+    let rows = unfilter(&header, stride, &mut filtered)?;
+    let samples = unpack_samples(&header, stride, &rows);
+
+    let (colour_space, data, alpha) = match header.colour_type {
+        0 => ("DeviceGray", samples, None),
+        2 => ("DeviceRGB", samples, None),
+        3 => {
+            let mut rgb = Vec::with_capacity(samples.len() * 3);
+            let mut alpha = Vec::with_capacity(samples.len());
+            for index in samples {
+                let index = index as usize;
+                let colour = palette
+                    .get(index * 3..index * 3 + 3)
+                    .ok_or(ImageError::Corrupt("PNG palette"))?;
+                rgb.extend_from_slice(colour);
+                alpha.push(transparency.get(index).copied().unwrap_or(0xff));
+            }
+            ("DeviceRGB", rgb, Some(alpha))
+        }
+        colour_type => {
+            // Grey or RGB followed by an alpha channel
+            let channels = if colour_type == 4 { 1 } else { 3 };
+            let mut colour = Vec::with_capacity(samples.len());
+            let mut alpha = Vec::with_capacity(samples.len() / (channels + 1));
+            for pixel in samples.chunks_exact(channels + 1) {
+                colour.extend_from_slice(&pixel[..channels]);
+                alpha.push(pixel[channels]);
+            }
+            let colour_space = if channels == 1 {
+                "DeviceGray"
+            } else {
+                "DeviceRGB"
+            };
+            (colour_space, colour, Some(alpha))
+        }
+    };
+
+    // A fully opaque mask only adds to the file size
+    let alpha = alpha.filter(|alpha| alpha.iter().any(|value| *value != 0xff));
+
+    Ok(DecodedImage {
+        width: header.width,
+        height: header.height,
+        colour_space,
+        data,
+        filter: None,
+        inverted: false,
+        alpha,
+    })
+}
+
+/// Reverses the per-row PNG filters in place, returning the rows without their filter byte.
+fn unfilter(header: &PngHeader, stride: usize, data: &mut [u8]) -> Result<Vec<u8>, ImageError> {
+    let bpp = header.bits_per_pixel().div_ceil(8);
+
+    let mut rows = Vec::with_capacity(stride * header.height);
+    let mut previous = vec![0; stride];
+    for row in data.chunks_exact_mut(stride + 1) {
+        let (filter, row) = row.split_at_mut(1);
+        for idx in 0..stride {
+            let left = if idx >= bpp { row[idx - bpp] } else { 0 };
+            let up = previous[idx];
+            let up_left = if idx >= bpp { previous[idx - bpp] } else { 0 };
+
+            let predicted = match filter[0] {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(ImageError::Corrupt("PNG filter")),
+            };
+            row[idx] = row[idx].wrapping_add(predicted);
+        }
+
+        rows.extend_from_slice(row);
+        previous.copy_from_slice(row);
+    }
+
+    Ok(rows)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Converts rows into one byte per sample. 16 bit samples keep their high byte and
+/// low bit depth grey is scaled up to 8 bits; palette indices are left as they are.
+fn unpack_samples(header: &PngHeader, stride: usize, rows: &[u8]) -> Vec<u8> {
+    let samples_per_row = header.width * header.channels();
+
+    match header.bit_depth {
+        8 => rows.to_vec(),
+        16 => rows.iter().step_by(2).copied().collect(),
+        bits => {
+            let max = (1u16 << bits) - 1;
+            let mut samples = Vec::with_capacity(samples_per_row * header.height);
+            for row in rows.chunks_exact(stride) {
+                for idx in 0..samples_per_row {
+                    let bit = idx * bits as usize;
+                    let shift = 8 - bits as usize - bit % 8;
+                    let value = (row[bit / 8] as u16 >> shift) & max;
+                    if header.colour_type == 3 {
+                        samples.push(value as u8);
+                    } else {
+                        samples.push((value * 255 / max) as u8);
+                    }
+                }
+            }
+            samples
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miniz_oxide::deflate::compress_to_vec_zlib;
+
+    use super::{decode_image, ImageError};
+
+    fn png(width: u32, height: u32, bit_depth: u8, colour_type: u8, rows: &[u8]) -> Vec<u8> {
+        fn chunk(data: &mut Vec<u8>, kind: &[u8], body: &[u8]) {
+            data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            data.extend_from_slice(kind);
+            data.extend_from_slice(body);
+            // The CRC is not checked when decoding
+            data.extend_from_slice(&[0; 4]);
+        }
+
+        let mut header = vec![];
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[bit_depth, colour_type, 0, 0, 0]);
+
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut data, b"IHDR", &header);
+        if colour_type == 3 {
+            chunk(&mut data, b"PLTE", &[255, 0, 0, 0, 0, 255]);
+            chunk(&mut data, b"tRNS", &[0x80]);
+        }
+        chunk(&mut data, b"IDAT", &compress_to_vec_zlib(rows, 6));
+        chunk(&mut data, b"IEND", &[]);
+        data
+    }
+
+    #[test]
+    fn png_rgba_with_filters() {
+        // Two rows of two pixels, the second row using the "up" filter
+        let rows = [
+            0, 10, 20, 30, 255, 40, 50, 60, 128, //
+            2, 1, 1, 1, 0, 1, 1, 1, 0,
+        ];
+
+        let image = decode_image(&png(2, 2, 8, 6, &rows)).unwrap();
+
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.colour_space, "DeviceRGB");
+        assert_eq!(image.data, [10, 20, 30, 40, 50, 60, 11, 21, 31, 41, 51, 61]);
+        assert_eq!(image.alpha, Some(vec![255, 128, 255, 128]));
+    }
+
+    #[test]
+    fn png_palette() {
+        // Four 2 bit indices: 0, 1, 0, 1
+        let rows = [0, 0b0001_0001];
+
+        let image = decode_image(&png(4, 1, 2, 3, &rows)).unwrap();
+
+        assert_eq!(image.data, [255, 0, 0, 0, 0, 255, 255, 0, 0, 0, 0, 255]);
+        assert_eq!(image.alpha, Some(vec![0x80, 0xff, 0x80, 0xff]));
+    }
+
+    #[test]
+    fn opaque_png_has_no_mask() {
+        let image = decode_image(&png(1, 1, 8, 4, &[0, 99, 255])).unwrap();
+
+        assert_eq!(image.colour_space, "DeviceGray");
+        assert_eq!(image.data, [99]);
+        assert_eq!(image.alpha, None);
+    }
+
+    #[test]
+    fn oversized_png_is_refused() {
+        assert_eq!(
+            decode_image(&png(u32::MAX, u32::MAX, 16, 6, &[])).err(),
+            Some(ImageError::Unsupported("PNG too large"))
+        );
+    }
+
+    #[test]
+    fn jpeg_header() {
+        let jpeg = [
+            0xff, 0xd8, // SOI
+            0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, // APP0
+            0xff, 0xc0, 0x00, 0x0b, 0x08, 0x00, 0x20, 0x00, 0x40, 0x03, 0, 0, 0, 0, 0, // SOF0
+        ];
+
+        let image = decode_image(&jpeg).unwrap();
+
+        assert_eq!((image.width, image.height), (64, 32));
+        assert_eq!(image.colour_space, "DeviceRGB");
+        assert_eq!(image.filter, Some("DCTDecode"));
+        assert_eq!(image.data, jpeg);
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert_eq!(
+            decode_image(b"GIF89a").err(),
+            Some(ImageError::UnknownFormat)
+        );
+        assert!(decode_image(&[0xff, 0xd8, 0xff]).is_err());
+    }
+}
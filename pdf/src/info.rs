@@ -0,0 +1,112 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{DictionaryObject, TextString};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Entries written to the document information dictionary.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub producer: Option<String>,
+    pub creation_date: Option<PdfDate>,
+}
+
+impl DocumentInfo {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.author.is_none()
+            && self.producer.is_none()
+            && self.creation_date.is_none()
+    }
+
+    pub(crate) fn to_dictionary(&self) -> DictionaryObject {
+        let mut dict = DictionaryObject::new();
+
+        let entries = [
+            ("Title", &self.title),
+            ("Author", &self.author),
+            ("Producer", &self.producer),
+        ];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                dict.insert_strkey(key, TextString::new(value));
+            }
+        }
+
+        if let Some(date) = self.creation_date {
+            dict.insert_strkey("CreationDate", date.to_string());
+        }
+
+        dict
+    }
+}
+
+/// A UTC date and time, formatted as a PDF date string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PdfDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl PdfDate {
+    /// Converts seconds since the Unix epoch. Callers supply the time, as there is no
+    /// system clock when running in the browser.
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let time = seconds.rem_euclid(SECONDS_PER_DAY) as u32;
+
+        // Days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+        }
+    }
+}
+
+impl Display for PdfDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "D:{:04}{:02}{:02}{:02}{:02}{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PdfDate;
+
+    #[test]
+    fn unix_epoch() {
+        let date = PdfDate::from_unix_seconds(0);
+        assert_eq!(date.to_string(), "D:19700101000000Z");
+    }
+
+    #[test]
+    fn leap_day() {
+        // 2024-02-29T13:45:30Z
+        let date = PdfDate::from_unix_seconds(1_709_214_330);
+        assert_eq!(date.to_string(), "D:20240229134530Z");
+    }
+}
@@ -2,25 +2,61 @@ use std::collections::BTreeMap;
 use std::io::Result;
 use std::io::Write;
 
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+mod form;
+mod image;
+mod info;
+mod metrics;
+mod svg;
+mod transform;
+
+use form::{create_acro_form, TextField};
+use image::decode_image;
+use svg::{render_page, SourceImage};
+
+pub use image::{ImageError, ImageRef};
+pub use info::{DocumentInfo, PdfDate};
+pub use metrics::{win_ansi_code, MIN_FIT_FONT_SIZE};
+pub use transform::Matrix;
+
 const HELVETICA: &str = "Helvetica";
 const HELVETICA_BOLD: &str = "Helvetica-Bold";
 const HELVETICA_ITALICS: &str = "Helvetica-Oblique";
 const HELVETICA_BOLD_ITALICS: &str = "Helvetica-BoldOblique";
 
-pub fn init_page(content: &mut ContentBuilder) {
-    content.save_graphics_state();
-    content.set_colour(0., 0., 0.);
-    content.set_colour_non_stroking(0., 0., 0.);
-    content.stroke_path();
-    content.save_graphics_state();
-}
+const COMPRESSION_LEVEL: u8 = 6;
 
 pub struct ContentBuilder<'a> {
     content: &'a mut Vec<Op>,
     page_size: (f64, f64),
+    font: Option<(FontStyle, f64)>,
+    transform: Matrix,
+    saved_transforms: Vec<Matrix>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Centre,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FontStyle {
     Normal,
     Bold,
@@ -41,7 +77,16 @@ impl FontStyle {
 
 impl<'a> ContentBuilder<'a> {
     pub fn new(page_size: (f64, f64), content: &'a mut Vec<Op>) -> Self {
-        Self { content, page_size }
+        let font = None;
+        let transform = Matrix::IDENTITY;
+        let saved_transforms = vec![];
+        Self {
+            content,
+            page_size,
+            font,
+            transform,
+            saved_transforms,
+        }
     }
 
     pub fn page_size(&self) -> Coord {
@@ -57,6 +102,7 @@ impl<'a> ContentBuilder<'a> {
     }
 
     pub fn set_font(&mut self, style: FontStyle, size: f64) {
+        self.font = Some((style, size));
         let op = Op::Tf {
             font: style.get_font_name().to_owned(),
             size,
@@ -65,13 +111,75 @@ impl<'a> ContentBuilder<'a> {
     }
 
     pub fn save_graphics_state(&mut self) {
+        self.saved_transforms.push(self.transform);
         self.content.push(Op::q);
     }
 
     pub fn restore_graphics_state(&mut self) {
+        self.transform = self.saved_transforms.pop().unwrap_or_default();
         self.content.push(Op::Q);
     }
 
+    /// Runs `draw` between a save and restore of the graphics state, so transforms,
+    /// dashes and clipping set inside it don't leak out.
+    pub fn with_saved_state<F: FnOnce(&mut Self)>(&mut self, draw: F) {
+        self.save_graphics_state();
+        draw(self);
+        self.restore_graphics_state();
+    }
+
+    /// Maps local coordinates, as used by the drawing calls, onto the page in mm.
+    pub fn current_transform(&self) -> Matrix {
+        self.transform
+    }
+
+    /// Applies `matrix` to everything drawn after it, until the graphics state is restored.
+    pub fn transform(&mut self, matrix: &Matrix) {
+        self.transform = self.transform.concat(matrix);
+        let (_, page_height) = self.page_size;
+        let matrix = matrix.to_pdf(1_f64.to_inch(), page_height);
+        self.content
+            .push(Op::cm(matrix.map(|value| value.trim_fraction())));
+    }
+
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.transform(&Matrix::IDENTITY.translate(dx, dy));
+    }
+
+    /// Rotates clockwise by `degrees` about the local origin.
+    pub fn rotate(&mut self, degrees: f64) {
+        self.transform(&Matrix::IDENTITY.rotate(degrees));
+    }
+
+    pub fn scale(&mut self, sx: f64, sy: f64) {
+        self.transform(&Matrix::IDENTITY.scale(sx, sy));
+    }
+
+    /// Sets a dash pattern of alternating dash and gap lengths in mm; an empty pattern
+    /// draws solid lines.
+    pub fn dash(&mut self, pattern: &[f64], phase: f64) {
+        let pattern = pattern
+            .iter()
+            .map(|length| length.to_inch().trim_fraction())
+            .collect();
+        let phase = phase.to_inch().trim_fraction();
+        self.content.push(Op::d { pattern, phase });
+    }
+
+    pub fn line_cap(&mut self, cap: LineCap) {
+        self.content.push(Op::J(cap as u8));
+    }
+
+    pub fn line_join(&mut self, join: LineJoin) {
+        self.content.push(Op::j(join as u8));
+    }
+
+    /// Intersects the clipping area with the current path, which is then discarded.
+    pub fn clip(&mut self) {
+        self.content.push(Op::W);
+        self.content.push(Op::n);
+    }
+
     pub fn stroke_path(&mut self) {
         self.content.push(Op::S);
     }
@@ -101,6 +209,44 @@ impl<'a> ContentBuilder<'a> {
         self.print(text.to_owned());
     }
 
+    /// Width in mm of `text` set in the current font.
+    pub fn text_width(&self, text: &str) -> f64 {
+        match self.font {
+            Some((style, size)) => style.text_width(text, size).to_mm(),
+            None => 0.,
+        }
+    }
+
+    /// Prints `text` aligned within a box `width` mm wide starting at `position`.
+    /// As with `print_at`, this should be the first positioning in the text block.
+    pub fn print_aligned(&mut self, text: &str, position: Coord, width: f64, align: TextAlign) {
+        let text_width = self.text_width(text);
+        let (x, y) = position;
+        let x = match align {
+            TextAlign::Left => x,
+            TextAlign::Centre => x + (width - text_width) / 2.,
+            TextAlign::Right => x + width - text_width,
+        };
+        self.print_at(text, (x, y));
+    }
+
+    /// Prints `text` aligned within a box `width` mm wide, shrinking the font when the
+    /// text would overflow. Text that still does not fit at `MIN_FIT_FONT_SIZE` is
+    /// truncated with an ellipsis.
+    pub fn print_fit(&mut self, text: &str, position: Coord, width: f64, align: TextAlign) {
+        let Some((style, size)) = self.font else {
+            return self.print_aligned(text, position, width, align);
+        };
+
+        let (fitted, text) = style.fit(text, size, width);
+        if fitted == size {
+            return self.print_aligned(&text, position, width, align);
+        }
+        self.set_font(style, fitted);
+        self.print_aligned(&text, position, width, align);
+        self.set_font(style, size);
+    }
+
     pub fn next_line(&mut self) {
         self.content.push(Op::TStar);
     }
@@ -144,6 +290,33 @@ impl<'a> ContentBuilder<'a> {
         let end = flip_y(end.to_inch(), self.page_size).trim_fraction();
         self.content.push(Op::c { ctrl1, ctrl2, end });
     }
+
+    /// Draws `image` as large as possible within the `size` mm rectangle whose top left
+    /// corner is `position`, keeping its aspect ratio and centring it in the spare space.
+    pub fn place_image(&mut self, image: &ImageRef, position: Coord, size: Coord) {
+        let (x, y) = position;
+        let (width, height) = size;
+
+        let aspect = image.aspect_ratio();
+        let draw_width = width.min(height * aspect);
+        let draw_height = draw_width / aspect;
+
+        let left = x + (width - draw_width) / 2.;
+        let bottom = y + (height + draw_height) / 2.;
+        let (left, bottom) = flip_y((left, bottom).to_inch(), self.page_size).trim_fraction();
+
+        self.save_graphics_state();
+        self.content.push(Op::cm([
+            draw_width.to_inch().trim_fraction(),
+            0.,
+            0.,
+            draw_height.to_inch().trim_fraction(),
+            left,
+            bottom,
+        ]));
+        self.content.push(Op::Do(image.name.clone()));
+        self.restore_graphics_state();
+    }
 }
 
 fn flip_y((x, y): Coord, (_, y_page): Coord) -> Coord {
@@ -208,11 +381,13 @@ pub enum PDFType {
     Bool(bool),
     Name(NameObject),
     PDFString(String),
+    TextString(TextString),
     Dictionary(DictionaryObject),
     IndirectRef(IndirectRef),
     Array(ArrayObject),
     PDFOp(Op),
     ContentStream(ContentStream),
+    Stream(StreamObject),
     Null,
 }
 
@@ -225,10 +400,12 @@ impl PDFWritable for PDFType {
             PDFType::NumF64(value) => value,
             PDFType::NumUsize(value) => value,
             PDFType::PDFString(value) => value,
+            PDFType::TextString(value) => value,
             PDFType::IndirectRef(value) => value,
             PDFType::Array(value) => value,
             PDFType::PDFOp(value) => value,
             PDFType::ContentStream(value) => value,
+            PDFType::Stream(value) => value,
             PDFType::Null => &PDFNull,
         };
 
@@ -271,6 +448,16 @@ pub enum Op {
         ctrl2: Coord,
         end: Coord,
     },
+    cm([f64; 6]),
+    Do(String),
+    d {
+        pattern: Vec<f64>,
+        phase: f64,
+    },
+    J(u8),
+    j(u8),
+    W,
+    n,
 }
 
 impl ToPDFType for Op {
@@ -291,6 +478,14 @@ impl PDFWritable for Op {
             Op::h => counting.write_str("h"),
             Op::f => counting.write_str("f"),
             Op::TStar => counting.write_str("T*"),
+            Op::W => counting.write_str("W"),
+            Op::n => counting.write_str("n"),
+            Op::J(cap) => counting.write_str(&format!("{cap} J")),
+            Op::j(join) => counting.write_str(&format!("{join} j")),
+            Op::d { pattern, phase } => {
+                let pattern: Vec<String> = pattern.iter().map(f64::to_string).collect();
+                counting.write_str(&format!("[{}] {phase} d", pattern.join(" ")))
+            }
             Op::re {
                 point: (x, y),
                 width,
@@ -309,6 +504,13 @@ impl PDFWritable for Op {
                 ctrl2: (x2, y2),
                 end: (x3, y3),
             } => counting.write_str(&format!("{x1} {y1} {x2} {y2} {x3} {y3} c")),
+            Op::cm([a, b, c, d, e, f]) => {
+                counting.write_str(&format!("{a} {b} {c} {d} {e} {f} cm"))
+            }
+            Op::Do(name) => {
+                NameObject::new(name).write(&mut counting)?;
+                counting.write_str(" Do")
+            }
             Op::Tf { font, size } => {
                 let name = NameObject::new(font);
                 name.write(&mut counting)?;
@@ -369,12 +571,24 @@ impl PeekAlloc {
 pub struct PDFDocumentBuilder {
     alloc: PDFAllocator,
     pages: Vec<PageStructure>,
-    page_resources: IndirectRef,
+    page_resources: PeekAlloc,
+    fonts: IndirectRef,
+    images: DictionaryObject,
+    source_images: BTreeMap<String, SourceImage>,
+    bookmarks: Vec<Bookmark>,
+    info: DocumentInfo,
+}
+
+struct Bookmark {
+    title: String,
+    page: usize,
 }
 
 struct PageStructure {
+    page_size: Coord,
     page_dict: DictionaryObject,
     contents: ContentStream,
+    fields: Vec<TextField>,
 }
 
 impl PDFDocumentBuilder {
@@ -395,26 +609,110 @@ impl PDFDocumentBuilder {
         let font_italics = create_font(HELVETICA_ITALICS, &mut alloc);
         let font_bold_italics = create_font(HELVETICA_BOLD_ITALICS, &mut alloc);
 
-        let page_resources = {
+        let fonts = {
             let mut font_dict = DictionaryObject::new();
             font_dict.insert_strkey(HELVETICA, font);
             font_dict.insert_strkey(HELVETICA_BOLD, font_bold);
             font_dict.insert_strkey(HELVETICA_ITALICS, font_italics);
             font_dict.insert_strkey(HELVETICA_BOLD_ITALICS, font_bold_italics);
-            let font_ref = alloc.alloc(font_dict);
-
-            let mut resources = DictionaryObject::new();
-            resources.insert_strkey("Font", font_ref);
-            alloc.alloc(resources)
+            alloc.alloc(font_dict)
         };
 
+        // Completed in `to_doc`, once all the images are known
+        let page_resources = alloc.peek_alloc();
+
         let pages = vec![];
+        let images = DictionaryObject::new();
+        let source_images = BTreeMap::new();
+        let bookmarks = vec![];
+        let info = DocumentInfo::default();
 
         Self {
             alloc,
             pages,
             page_resources,
+            fonts,
+            images,
+            source_images,
+            bookmarks,
+            info,
+        }
+    }
+
+    /// Adds a PNG or JPEG image to the document so it can be placed on any page.
+    pub fn add_image(&mut self, data: &[u8]) -> std::result::Result<ImageRef, ImageError> {
+        let image = decode_image(data)?;
+
+        let mut dict = DictionaryObject::new();
+        dict.insert_strkey("Type", NameObject::new("XObject"));
+        dict.insert_strkey("Subtype", NameObject::new("Image"));
+        dict.insert_strkey("Width", image.width);
+        dict.insert_strkey("Height", image.height);
+        dict.insert_strkey("BitsPerComponent", 8);
+
+        if let Some(alpha) = image.alpha {
+            let mut mask = DictionaryObject::new();
+            mask.insert_strkey("Type", NameObject::new("XObject"));
+            mask.insert_strkey("Subtype", NameObject::new("Image"));
+            mask.insert_strkey("Width", image.width);
+            mask.insert_strkey("Height", image.height);
+            mask.insert_strkey("BitsPerComponent", 8);
+            mask.insert_strkey("ColorSpace", NameObject::new("DeviceGray"));
+            let mask = self.alloc.alloc(StreamObject::new(mask, alpha));
+            dict.insert_strkey("SMask", mask);
         }
+
+        dict.insert_strkey("ColorSpace", NameObject::new(image.colour_space));
+        if image.inverted {
+            dict.insert_strkey(
+                "Decode",
+                ArrayObject::new_from([1., 0., 1., 0., 1., 0., 1., 0.]),
+            );
+        }
+
+        let (stream, mime) = match image.filter {
+            Some(filter) => (
+                StreamObject::encoded(dict, image.data, filter),
+                "image/jpeg",
+            ),
+            None => (StreamObject::new(dict, image.data), "image/png"),
+        };
+        let indirect = self.alloc.alloc(stream);
+
+        let name = format!("Im{}", self.images.len() + 1);
+        self.images.insert_strkey(&name, indirect);
+
+        let data = data.to_vec();
+        let source = SourceImage { mime, data };
+        self.source_images.insert(name.clone(), source);
+
+        Ok(ImageRef {
+            name,
+            width: image.width,
+            height: image.height,
+        })
+    }
+
+    pub fn info_mut(&mut self) -> &mut DocumentInfo {
+        &mut self.info
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Renders the page with index `page` as a standalone SVG document, for previews where
+    /// a PDF viewer isn't available. Form fields are not included.
+    pub fn page_svg(&self, page: usize) -> Option<String> {
+        let page = self.pages.get(page)?;
+        let ops = page.contents.ops();
+        Some(render_page(ops, page.page_size, &self.source_images))
+    }
+
+    /// Adds a top level outline entry that opens the page with index `page`.
+    pub fn add_bookmark(&mut self, title: &str, page: usize) {
+        let title = title.to_owned();
+        self.bookmarks.push(Bookmark { title, page });
     }
 
     pub fn create_page(&mut self, page_size: (f64, f64)) -> PDFPageBuilder<'_> {
@@ -422,8 +720,10 @@ impl PDFDocumentBuilder {
         let contents = ContentStream::new();
 
         let page_structure = PageStructure {
+            page_size,
             page_dict: page_dic_vec,
             contents,
+            fields: vec![],
         };
 
         self.pages.push(page_structure);
@@ -437,7 +737,7 @@ impl PDFDocumentBuilder {
         page_structure.page_dict.insert_strkey("MediaBox", mediabox);
         page_structure
             .page_dict
-            .insert_strkey("Resources", self.page_resources);
+            .insert_strkey("Resources", self.page_resources.indirect());
 
         PDFPageBuilder {
             page_structure,
@@ -452,17 +752,33 @@ impl PDFDocumentBuilder {
         let pages_peek = self.alloc.peek_alloc();
         let catalog_peek = self.alloc.peek_alloc();
 
+        let mut page_refs = vec![];
+        let mut fields = vec![];
         {
             let mut page_array = ArrayObject::new();
             for mut page_structure in self.pages.into_iter() {
                 let content_ref = self.alloc.alloc(page_structure.contents);
+                let page_peek = self.alloc.peek_alloc();
+                let indirect = page_peek.indirect();
+
+                if !page_structure.fields.is_empty() {
+                    let mut annots = ArrayObject::new();
+                    for field in &page_structure.fields {
+                        let field = self.alloc.alloc(field.to_dictionary(indirect));
+                        annots.push(field);
+                        fields.push(field);
+                    }
+                    page_structure.page_dict.insert_strkey("Annots", annots);
+                }
+
                 page_structure
                     .page_dict
                     .insert_strkey("Parent", pages_peek.indirect());
                 page_structure
                     .page_dict
                     .insert_strkey("Contents", content_ref);
-                let indirect = self.alloc.alloc(page_structure.page_dict);
+                page_peek.complete(&mut self.alloc, page_structure.page_dict);
+                page_refs.push(indirect);
                 page_array.push(indirect)
             }
             pages.insert_strkey("Count", page_array.len());
@@ -471,6 +787,27 @@ impl PDFDocumentBuilder {
 
         catalog.insert_strkey("Pages", pages_peek.indirect());
         catalog.insert_strkey("PageLayout", "OneColumn");
+        if !fields.is_empty() {
+            catalog.insert_strkey("AcroForm", create_acro_form(fields, self.fonts));
+        }
+
+        let mut resources = DictionaryObject::new();
+        resources.insert_strkey("Font", self.fonts);
+        if !self.images.is_empty() {
+            resources.insert_strkey("XObject", self.images);
+        }
+        self.page_resources.complete(&mut self.alloc, resources);
+
+        let bookmarks: Vec<_> = self
+            .bookmarks
+            .into_iter()
+            .filter_map(|bookmark| Some((bookmark.title, *page_refs.get(bookmark.page)?)))
+            .collect();
+        if !bookmarks.is_empty() {
+            let outlines = create_outline(&mut self.alloc, bookmarks);
+            catalog.insert_strkey("Outlines", outlines);
+            catalog.insert_strkey("PageMode", NameObject::new("UseOutlines"));
+        }
 
         let root = catalog_peek.indirect();
 
@@ -484,8 +821,45 @@ impl PDFDocumentBuilder {
             content,
             indirect,
             root,
+            info: self.info,
+        }
+    }
+}
+
+/// Allocates a flat outline with one item per bookmark, returning the outline dictionary.
+fn create_outline(alloc: &mut PDFAllocator, bookmarks: Vec<(String, IndirectRef)>) -> IndirectRef {
+    let outlines_peek = alloc.peek_alloc();
+    let items: Vec<PeekAlloc> = bookmarks.iter().map(|_| alloc.peek_alloc()).collect();
+    let refs: Vec<IndirectRef> = items.iter().map(PeekAlloc::indirect).collect();
+
+    for (idx, ((title, page), item)) in bookmarks.into_iter().zip(items).enumerate() {
+        let mut dict = DictionaryObject::new();
+        dict.insert_strkey("Title", TextString::new(&title));
+        dict.insert_strkey("Parent", outlines_peek.indirect());
+        let mut dest = ArrayObject::new();
+        dest.push(page);
+        dest.push(NameObject::new("Fit"));
+        dict.insert_strkey("Dest", dest);
+        if idx > 0 {
+            dict.insert_strkey("Prev", refs[idx - 1]);
+        }
+        if let Some(next) = refs.get(idx + 1) {
+            dict.insert_strkey("Next", *next);
         }
+        item.complete(alloc, dict);
+    }
+
+    let mut outlines = DictionaryObject::new();
+    outlines.insert_strkey("Type", NameObject::new("Outlines"));
+    outlines.insert_strkey("Count", refs.len());
+    if let (Some(first), Some(last)) = (refs.first(), refs.last()) {
+        outlines.insert_strkey("First", *first);
+        outlines.insert_strkey("Last", *last);
     }
+
+    let indirect = outlines_peek.indirect();
+    outlines_peek.complete(alloc, outlines);
+    indirect
 }
 
 pub struct PDFPageBuilder<'a> {
@@ -501,52 +875,134 @@ impl<'a> PDFPageBuilder<'a> {
     pub fn content_builder(&mut self) -> ContentBuilder<'_> {
         ContentBuilder::new(self.page_size, self.page_structure.contents.contents())
     }
+
+    /// Adds a fillable text field covering `size` mm from the top left `position`.
+    /// Field names should be unique within the document, as fields sharing a name also
+    /// share their value.
+    pub fn add_text_field(&mut self, name: &str, position: Coord, size: Coord) {
+        let (_, page_height) = self.page_size;
+        let field = TextField::new(name, position, size, page_height);
+        self.page_structure.fields.push(field);
+    }
 }
 
 pub struct PDFDocument {
     content: Vec<PDFType>,
     indirect: Vec<PDFType>,
     root: IndirectRef,
+    info: DocumentInfo,
+}
+
+/// Where an object can be found, as recorded in the cross-reference stream.
+#[derive(Clone, Copy)]
+enum XRefEntry {
+    Free,
+    Offset(usize),
+    Compressed { stream: usize, index: usize },
+}
+
+impl XRefEntry {
+    // Field widths, matching the /W entry of the cross-reference stream
+    const WIDTHS: [usize; 3] = [1, 4, 2];
+
+    fn write(self, data: &mut Vec<u8>) {
+        let fields = match self {
+            XRefEntry::Free => [0, 0, 65535],
+            XRefEntry::Offset(offset) => [1, offset, 0],
+            XRefEntry::Compressed { stream, index } => [2, stream, index],
+        };
+
+        for (value, width) in fields.into_iter().zip(Self::WIDTHS) {
+            data.extend_from_slice(&value.to_be_bytes()[usize::BITS as usize / 8 - width..]);
+        }
+    }
 }
 
 impl PDFDocument {
+    /// The information dictionary can still be changed, e.g. to stamp the creation date.
+    pub fn info_mut(&mut self) -> &mut DocumentInfo {
+        &mut self.info
+    }
+
+    /// Writes the document using compressed object streams and a cross-reference stream.
     pub fn write<T: Write>(&self, writer: &mut T) -> Result<usize> {
         let mut counting = CountingWriter::new(writer);
 
         counting.write_str_ln("%PDF-1.5")?;
+        // Marks the file as binary for transfer tools
+        counting.write(b"%\xe2\xe3\xcf\xd3\n")?;
         for pdf_obj in self.content.iter() {
             pdf_obj.write(&mut counting)?;
             counting.write_str("\n")?;
         }
 
-        let mut xref = vec![];
-        for (id, v) in self.indirect.iter().enumerate() {
-            let offset = counting.size();
-            xref.push(offset);
+        let info = (!self.info.is_empty()).then(|| self.info.to_dictionary().to_pdftype());
+        let info_ref = info.as_ref().map(|_| IndirectRef {
+            id: self.indirect.len(),
+        });
 
-            write_indirect(id, v, &mut counting)?;
-            counting.write_str("\n")?;
+        let objects_count = self.indirect.len() + usize::from(info.is_some());
+        let object_stream_id = objects_count;
+        let xref_id = object_stream_id + 1;
+
+        let mut xref = vec![XRefEntry::Free; xref_id + 2];
+
+        // Streams cannot be stored inside an object stream so are written directly
+        let mut offsets = String::new();
+        let mut objects = vec![];
+        let mut index = 0;
+        for (id, v) in self.indirect.iter().chain(info.iter()).enumerate() {
+            if let PDFType::ContentStream(_) | PDFType::Stream(_) = v {
+                xref[id + 1] = XRefEntry::Offset(counting.size());
+                write_indirect(id, v, &mut counting)?;
+                counting.write_str("\n")?;
+            } else {
+                offsets.push_str(&format!("{} {} ", id + 1, objects.len()));
+                v.write(&mut objects)?;
+                objects.push(b'\n');
+
+                xref[id + 1] = XRefEntry::Compressed {
+                    stream: object_stream_id + 1,
+                    index,
+                };
+                index += 1;
+            }
         }
 
-        let startxref = counting.size();
-        let num_xref = xref.len() + 1;
-
-        counting.write_str_ln("xref")?;
-        counting.write_str_ln(format!("0 {}", num_xref).as_str())?;
-        counting.write_str_ln("0000000000 65535 f ")?;
-        for offset in xref {
-            let line = format!("{:0>10} 00000 n ", offset);
-            counting.write_str_ln(line.as_str())?;
+        {
+            let mut dict = DictionaryObject::new();
+            dict.insert_strkey("Type", NameObject::new("ObjStm"));
+            dict.insert_strkey("N", index);
+            dict.insert_strkey("First", offsets.len());
+
+            let mut data = offsets.into_bytes();
+            data.append(&mut objects);
+
+            xref[object_stream_id + 1] = XRefEntry::Offset(counting.size());
+            let stream = StreamObject::new(dict, data).to_pdftype();
+            write_indirect(object_stream_id, &stream, &mut counting)?;
+            counting.write_str("\n")?;
         }
 
-        let mut trailer = DictionaryObject::new();
-        trailer.insert_strkey("Size", num_xref);
+        let startxref = counting.size();
+        xref[xref_id + 1] = XRefEntry::Offset(startxref);
 
-        trailer.insert_strkey("Root", self.root);
+        let mut data = vec![];
+        for entry in xref.iter() {
+            entry.write(&mut data);
+        }
 
-        counting.write_str_ln("trailer")?;
-        trailer.write(&mut counting)?;
+        let mut dict = DictionaryObject::new();
+        dict.insert_strkey("Type", NameObject::new("XRef"));
+        dict.insert_strkey("Size", xref.len());
+        dict.insert_strkey("W", ArrayObject::new_from(XRefEntry::WIDTHS));
+        dict.insert_strkey("Root", self.root);
+        if let Some(info_ref) = info_ref {
+            dict.insert_strkey("Info", info_ref);
+        }
 
+        let stream = StreamObject::new(dict, data).to_pdftype();
+        write_indirect(xref_id, &stream, &mut counting)?;
         counting.write_ln()?;
 
         counting.write_str_ln("startxref")?;
@@ -628,6 +1084,12 @@ impl ToPDFType for &str {
     }
 }
 
+impl ToPDFType for TextString {
+    fn to_pdftype(self) -> PDFType {
+        PDFType::TextString(self)
+    }
+}
+
 impl ToPDFType for NameObject {
     fn to_pdftype(self) -> PDFType {
         PDFType::Name(self)
@@ -726,7 +1188,10 @@ impl PDFWritable for String {
                 '(' => add_all(&mut result, "\\("),
                 ')' => add_all(&mut result, "\\)"),
                 '\\' => add_all(&mut result, "\\\\"),
-                c if (c as u32) > 0x7f => add_all(&mut result, &format!("\\{:03o}", c as u32)),
+                c if (c as u32) > 0x7f => match win_ansi_code(c) {
+                    Some(code) => add_all(&mut result, &format!("\\{:03o}", code)),
+                    None => result.push('?'),
+                },
                 c => result.push(c),
             }
         }
@@ -735,6 +1200,35 @@ impl PDFWritable for String {
     }
 }
 
+/// A string shown outside page content, such as a document or bookmark title. Unlike
+/// strings drawn with a font these aren't WinAnsi, so anything but ASCII is written as
+/// UTF-16BE with a byte order mark.
+pub struct TextString {
+    text: String,
+}
+
+impl TextString {
+    pub fn new(text: &str) -> Self {
+        TextString {
+            text: text.to_owned(),
+        }
+    }
+}
+
+impl PDFWritable for TextString {
+    fn write(&self, writer: &mut dyn Write) -> Result<usize> {
+        if self.text.is_ascii() {
+            return self.text.write(writer);
+        }
+
+        let hex: String = std::iter::once(0xfeff)
+            .chain(self.text.encode_utf16())
+            .map(|unit| format!("{unit:04X}"))
+            .collect();
+        writer.write(format!("<{hex}>").as_bytes())
+    }
+}
+
 fn add_all(vec: &mut String, values: &str) {
     for value in values.chars() {
         vec.push(value);
@@ -785,6 +1279,14 @@ impl DictionaryObject {
         self.map.insert(NameObject::new(key), value.to_pdftype());
     }
 
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
     pub fn get_mut(&mut self, key: String) -> Option<&mut PDFType> {
         let named_key = NameObject::new(&key);
         self.map.get_mut(&named_key)
@@ -829,14 +1331,47 @@ impl PDFWritable for ContentStream {
             byte_contents.write("\n".as_bytes())?;
         }
 
-        let mut dict = DictionaryObject::new();
-        dict.insert_strkey("Length", byte_contents.len());
+        StreamObject::new(DictionaryObject::new(), byte_contents).write(writer)
+    }
+}
 
+/// A stream of binary data, such as image samples.
+pub struct StreamObject {
+    dict: DictionaryObject,
+    data: Vec<u8>,
+}
+
+impl StreamObject {
+    /// Compresses `data` with FlateDecode.
+    pub fn new(mut dict: DictionaryObject, data: Vec<u8>) -> Self {
+        let data = compress_to_vec_zlib(&data, COMPRESSION_LEVEL);
+        dict.insert_strkey("Filter", NameObject::new("FlateDecode"));
+        dict.insert_strkey("Length", data.len());
+        Self { dict, data }
+    }
+
+    /// The data is already encoded with `filter` and is written as it is.
+    pub fn encoded(mut dict: DictionaryObject, data: Vec<u8>, filter: &str) -> Self {
+        dict.insert_strkey("Filter", NameObject::new(filter));
+        dict.insert_strkey("Length", data.len());
+        Self { dict, data }
+    }
+}
+
+impl ToPDFType for StreamObject {
+    fn to_pdftype(self) -> PDFType {
+        PDFType::Stream(self)
+    }
+}
+
+impl PDFWritable for StreamObject {
+    fn write(&self, writer: &mut dyn Write) -> Result<usize> {
         let mut counting = CountingWriter::new(writer);
-        dict.write(&mut counting)?;
+        self.dict.write(&mut counting)?;
         counting.write_str_ln("\nstream")?;
-        counting.write(&byte_contents)?;
-        counting.write_str("endstream")
+        counting.write(&self.data)?;
+        counting.write_str("\nendstream")?;
+        Ok(counting.size())
     }
 }
 
@@ -849,6 +1384,10 @@ impl ContentStream {
     pub fn contents(&mut self) -> &mut Vec<Op> {
         &mut self.contents
     }
+
+    fn ops(&self) -> &[Op] {
+        &self.contents
+    }
 }
 
 pub struct IndirectObject {
@@ -966,3 +1505,197 @@ impl Default for PDFDocumentBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    use super::{
+        ContentBuilder, FontStyle, LineCap, Matrix, Op, PDFDocumentBuilder, PDFWritable, PdfDate,
+        TextAlign, A5, MIN_FIT_FONT_SIZE,
+    };
+
+    fn stream_data<'a>(pdf: &'a [u8], object: &str) -> &'a [u8] {
+        let start = find(pdf, object.as_bytes()).unwrap();
+        let data = start + find(&pdf[start..], b"stream\n").unwrap() + 7;
+        let end = data + find(&pdf[data..], b"\nendstream").unwrap();
+        &pdf[data..end]
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    #[test]
+    fn content_is_compressed() {
+        let mut builder = PDFDocumentBuilder::new();
+        let mut page = builder.create_page(A5);
+        page.content_builder().print_at("Hello", (10., 10.));
+
+        let mut pdf = vec![];
+        builder.to_doc().write(&mut pdf).unwrap();
+
+        assert!(find(&pdf, b"/Filter /FlateDecode").is_some());
+        assert!(find(&pdf, b"Hello").is_none());
+
+        // Fonts and resources are objects 1 to 6 and the page tree and catalog 7 and 8
+        let contents = decompress_to_vec_zlib(stream_data(&pdf, "9 0 obj")).unwrap();
+        assert!(find(&contents, b"(Hello) Tj").is_some());
+
+        let objects = decompress_to_vec_zlib(stream_data(&pdf, "/Type /ObjStm")).unwrap();
+        assert!(find(&objects, b"/Type /Catalog").is_some());
+    }
+
+    #[test]
+    fn xref_stream_covers_every_object() {
+        let mut builder = PDFDocumentBuilder::new();
+        builder.create_page(A5);
+
+        let mut pdf = vec![];
+        builder.to_doc().write(&mut pdf).unwrap();
+
+        let tail = &pdf[find(&pdf, b"startxref\n").unwrap()..];
+        let tail = String::from_utf8_lossy(tail).into_owned();
+        let startxref: usize = tail.split_whitespace().nth(1).unwrap().parse().unwrap();
+        assert!(pdf[startxref..].starts_with(b"12 0 obj\n<</Filter /FlateDecode"));
+
+        let entries = decompress_to_vec_zlib(stream_data(&pdf[startxref..], "obj")).unwrap();
+        assert_eq!(entries.len(), 13 * 7);
+        assert_eq!(&entries[..7], &[0, 0, 0, 0, 0, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn info_and_outline() {
+        let mut builder = PDFDocumentBuilder::new();
+        builder.info_mut().title = Some("Lydd – Le Touquet".to_owned());
+        builder.add_bookmark("Outbound", 0);
+        builder.create_page(A5);
+        builder.add_bookmark("Le Touquet – Lydd", 1);
+        builder.create_page(A5);
+        builder.add_bookmark("Missing", 5);
+
+        let mut doc = builder.to_doc();
+        doc.info_mut().creation_date = Some(PdfDate::from_unix_seconds(0));
+
+        let mut pdf = vec![];
+        doc.write(&mut pdf).unwrap();
+
+        let objects = decompress_to_vec_zlib(stream_data(&pdf, "/Type /ObjStm")).unwrap();
+        assert!(find(&objects, b"/PageMode /UseOutlines").is_some());
+        assert!(find(&objects, b"/Count 2\n").is_some());
+        assert!(find(&objects, b"/Title (Outbound)").is_some());
+        let back =
+            b"/Title <FEFF004C006500200054006F00750071007500650074002020130020004C007900640064>";
+        assert!(find(&objects, back).is_some());
+        assert!(find(&objects, b"/Title (Missing)").is_none());
+        assert!(find(&objects, b"/CreationDate (D:19700101000000Z)").is_some());
+        let title =
+            b"/Title <FEFF004C007900640064002020130020004C006500200054006F00750071007500650074>";
+        assert!(find(&objects, title).is_some());
+        assert!(find(&pdf, b"/Info ").is_some());
+    }
+
+    #[test]
+    fn text_fields_are_annotations_and_form_fields() {
+        let mut builder = PDFDocumentBuilder::new();
+        builder.create_page(A5);
+        let mut page = builder.create_page(A5);
+        page.add_text_field("route1_leg1_eta", (10., 20.), (14., 10.));
+
+        let mut pdf = vec![];
+        builder.to_doc().write(&mut pdf).unwrap();
+
+        let objects = decompress_to_vec_zlib(stream_data(&pdf, "/Type /ObjStm")).unwrap();
+        let objects = String::from_utf8_lossy(&objects);
+        assert!(objects.contains("/T (route1_leg1_eta)"));
+        assert!(objects.contains("/Rect [28.3465 510.2362 68.0315 538.5827]"));
+        assert_eq!(objects.matches("/Annots [").count(), 1);
+        assert!(objects.contains("/AcroForm <</DA (/Helvetica 0 Tf 0 g)"));
+    }
+
+    #[test]
+    fn restore_resets_transform() {
+        let mut ops = vec![];
+        let mut layer = ContentBuilder::new(A5, &mut ops);
+
+        layer.with_saved_state(|layer| {
+            layer.translate(10., 20.);
+            layer.rotate(90.);
+            layer.line_cap(LineCap::Round);
+            layer.dash(&[2., 1.], 0.);
+            assert_eq!(layer.current_transform().apply((1., 0.)), (10., 21.));
+        });
+        assert_eq!(layer.current_transform(), Matrix::IDENTITY);
+
+        let mut content = vec![];
+        for op in &ops {
+            op.write(&mut content).unwrap();
+            content.push(b'\n');
+        }
+        let content = String::from_utf8(content).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        assert!(matches!(ops.first(), Some(Op::q)));
+        assert!(matches!(ops.last(), Some(Op::Q)));
+        assert!(lines[1].ends_with(" cm"));
+        assert_eq!(lines[3], "1 J");
+        assert_eq!(lines[4], "[5.6693 2.8346] 0 d");
+    }
+
+    /// The ops `print_fit` emits for `text` set in 10pt Helvetica, after the initial `Tf`.
+    fn fit_ops(text: &str, width: f64, align: TextAlign) -> Vec<Op> {
+        let mut ops = vec![];
+        let mut layer = ContentBuilder::new(A5, &mut ops);
+        layer.set_font(FontStyle::Normal, 10.);
+        layer.print_fit(text, (10., 20.), width, align);
+        ops.remove(0);
+        ops
+    }
+
+    #[test]
+    fn print_fit_leaves_fitting_text_alone() {
+        let ops = fit_ops("Hello", 50., TextAlign::Left);
+
+        assert_eq!(ops.len(), 2);
+        assert!(matches!(ops[0], Op::Td((x, _)) if x == 28.3465));
+        assert!(matches!(&ops[1], Op::Tj(text) if text == "Hello"));
+    }
+
+    #[test]
+    fn print_fit_shrinks_and_restores_the_font() {
+        let ops = fit_ops("Hello", 4., TextAlign::Left);
+
+        assert_eq!(ops.len(), 4);
+        assert!(matches!(ops[0], Op::Tf { size, .. } if size == 4.97));
+        assert!(matches!(&ops[2], Op::Tj(text) if text == "Hello"));
+        assert!(matches!(ops[3], Op::Tf { size, .. } if size == 10.));
+    }
+
+    #[test]
+    fn print_fit_truncates_below_the_minimum_size() {
+        let ops = fit_ops("Hello", 2.5, TextAlign::Left);
+
+        assert!(matches!(ops[0], Op::Tf { size, .. } if size == MIN_FIT_FONT_SIZE));
+        assert!(matches!(&ops[2], Op::Tj(text) if text == "H…"));
+        assert!(matches!(ops[3], Op::Tf { size, .. } if size == 10.));
+
+        let mut content = vec![];
+        ops[2].write(&mut content).unwrap();
+        assert_eq!(content, b"(H\\205) Tj");
+    }
+
+    #[test]
+    fn print_fit_aligns_within_the_width() {
+        let x = |align| match fit_ops("Hello", 4., align)[1] {
+            Op::Td((x, _)) => x,
+            _ => panic!("expected a text position"),
+        };
+
+        // At 4.97pt "Hello" is 11.3217pt wide, leaving 0.0169pt of the 4mm box spare
+        assert_eq!(x(TextAlign::Left), 28.3465);
+        assert_eq!(x(TextAlign::Centre), 28.355);
+        assert_eq!(x(TextAlign::Right), 28.3635);
+    }
+}
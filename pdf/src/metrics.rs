@@ -0,0 +1,155 @@
+use crate::{FontStyle, ToMM};
+
+/// Smallest font size `FontStyle::fit` will shrink text to before truncating it.
+pub const MIN_FIT_FONT_SIZE: f64 = 4.;
+
+impl FontStyle {
+    /// Width of `text` in points when set at `size` points.
+    pub fn text_width(self, text: &str, size: f64) -> f64 {
+        let widths = match self {
+            FontStyle::Normal | FontStyle::Italics => &HELVETICA_WIDTHS,
+            FontStyle::Bold | FontStyle::BoldItalics => &HELVETICA_BOLD_WIDTHS,
+        };
+
+        let units: u32 = text
+            .chars()
+            .map(|ch| widths[win_ansi_code(ch).unwrap_or(b'?') as usize] as u32)
+            .sum();
+
+        units as f64 * size / 1000.
+    }
+
+    /// The size and text to print `text` within `width` mm, starting from `size` points.
+    /// The size shrinks until the text fits, and text that still overflows at
+    /// `MIN_FIT_FONT_SIZE` is truncated with an ellipsis.
+    pub fn fit(self, text: &str, size: f64, width: f64) -> (f64, String) {
+        let text_width = self.text_width(text, size).to_mm();
+        if text_width <= width {
+            return (size, text.to_owned());
+        }
+
+        let fitted = (size * width / text_width * 100.).floor() / 100.;
+        if fitted >= MIN_FIT_FONT_SIZE {
+            return (fitted, text.to_owned());
+        }
+
+        let mut truncated = text.to_owned();
+        let ellipsised = |truncated: &str| format!("{truncated}…");
+        while !truncated.is_empty()
+            && self
+                .text_width(&ellipsised(&truncated), MIN_FIT_FONT_SIZE)
+                .to_mm()
+                > width
+        {
+            truncated.pop();
+        }
+        (MIN_FIT_FONT_SIZE, ellipsised(&truncated))
+    }
+}
+
+/// Maps a character onto its WinAnsiEncoding code, if the encoding has one.
+pub fn win_ansi_code(ch: char) -> Option<u8> {
+    let code = match ch {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => ch as u32 as u8,
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8a,
+        '‹' => 0x8b,
+        'Œ' => 0x8c,
+        'Ž' => 0x8e,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9a,
+        '›' => 0x9b,
+        'œ' => 0x9c,
+        'ž' => 0x9e,
+        'Ÿ' => 0x9f,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+// Widths are in 1/1000 of the font size, taken from the Adobe Core 14 AFM files.
+// The oblique faces share the metrics of their upright counterparts.
+
+/// Glyph widths for Helvetica (and Helvetica-Oblique) indexed by WinAnsiEncoding code.
+const HELVETICA_WIDTHS: [u16; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+    350, 556, 350, 222, 556, 333, 1000, 556, 556, 333, 1000, 667, 333, 1000, 350, 611, 350, 350,
+    222, 222, 333, 333, 350, 556, 1000, 333, 1000, 500, 333, 944, 350, 500, 667, 278, 333, 556,
+    556, 556, 556, 260, 556, 333, 737, 370, 556, 584, 333, 737, 333, 400, 584, 333, 333, 333, 556,
+    537, 278, 333, 333, 365, 556, 834, 834, 834, 611, 667, 667, 667, 667, 667, 667, 1000, 722, 667,
+    667, 667, 667, 278, 278, 278, 278, 722, 722, 778, 778, 778, 778, 778, 584, 778, 722, 722, 722,
+    722, 667, 667, 611, 556, 556, 556, 556, 556, 556, 889, 500, 556, 556, 556, 556, 278, 278, 278,
+    278, 556, 556, 556, 556, 556, 556, 556, 584, 611, 556, 556, 556, 556, 500, 556, 500,
+];
+
+/// Glyph widths for Helvetica-Bold (and Helvetica-BoldOblique) indexed by WinAnsiEncoding code.
+const HELVETICA_BOLD_WIDTHS: [u16; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+    350, 556, 350, 278, 556, 500, 1000, 556, 556, 333, 1000, 667, 333, 1000, 350, 611, 350, 350,
+    278, 278, 500, 500, 350, 556, 1000, 333, 1000, 556, 333, 944, 350, 500, 667, 278, 333, 556,
+    556, 556, 556, 280, 556, 333, 737, 370, 556, 584, 333, 737, 333, 400, 584, 333, 333, 333, 611,
+    556, 278, 333, 333, 365, 556, 834, 834, 834, 611, 722, 722, 722, 722, 722, 722, 1000, 722, 667,
+    667, 667, 667, 278, 278, 278, 278, 722, 722, 778, 778, 778, 778, 778, 584, 778, 722, 722, 722,
+    722, 667, 667, 611, 556, 556, 556, 556, 556, 556, 889, 556, 556, 556, 556, 556, 278, 278, 278,
+    278, 611, 611, 611, 611, 611, 611, 611, 584, 611, 611, 611, 611, 611, 556, 611, 556,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{win_ansi_code, MIN_FIT_FONT_SIZE};
+    use crate::FontStyle;
+
+    #[test]
+    fn text_width() {
+        assert_eq!(FontStyle::Normal.text_width("Hello", 10.), 22.78);
+        assert_eq!(FontStyle::Bold.text_width("Hello", 10.), 24.45);
+        assert_eq!(FontStyle::Italics.text_width("Hello", 10.), 22.78);
+        assert_eq!(FontStyle::Normal.text_width("", 10.), 0.);
+    }
+
+    #[test]
+    fn fit() {
+        let fit = |width| FontStyle::Normal.fit("Hello", 10., width);
+
+        assert_eq!(fit(10.), (10., "Hello".to_owned()));
+        assert_eq!(fit(4.), (4.97, "Hello".to_owned()));
+        assert_eq!(fit(2.5), (MIN_FIT_FONT_SIZE, "H…".to_owned()));
+        assert_eq!(fit(0.5), (MIN_FIT_FONT_SIZE, "…".to_owned()));
+    }
+
+    #[test]
+    fn win_ansi() {
+        assert_eq!(win_ansi_code('A'), Some(0x41));
+        assert_eq!(win_ansi_code('\u{b0}'), Some(0xb0));
+        assert_eq!(win_ansi_code('’'), Some(0x92));
+        assert_eq!(win_ansi_code('\n'), None);
+        assert_eq!(win_ansi_code('✈'), None);
+    }
+}
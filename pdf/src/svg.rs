@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::{Coord, FontStyle, Op, ToMM, TrimFraction};
+
+const FONT_FAMILY: &str = "Helvetica, Arial, sans-serif";
+const FONT_STYLES: [FontStyle; 4] = [
+    FontStyle::Normal,
+    FontStyle::Bold,
+    FontStyle::Italics,
+    FontStyle::BoldItalics,
+];
+
+/// The original bytes of an image added to the document, embedded as a data URL in SVG.
+pub(crate) struct SourceImage {
+    pub mime: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// State saved and restored by `q` and `Q`, along with the number of `<g>` elements
+/// opened since the save so they can be closed again on restore.
+#[derive(Clone)]
+struct GraphicsState {
+    stroke: (f64, f64, f64),
+    fill: (f64, f64, f64),
+    line_width: f64,
+    dash: Option<(Vec<f64>, f64)>,
+    cap: u8,
+    join: u8,
+    font: Option<(FontStyle, f64)>,
+    leading: f64,
+    groups: usize,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            stroke: (0., 0., 0.),
+            fill: (0., 0., 0.),
+            line_width: 1.,
+            dash: None,
+            cap: 0,
+            join: 0,
+            font: None,
+            leading: 0.,
+            groups: 0,
+        }
+    }
+}
+
+struct SvgRenderer<'a> {
+    images: &'a BTreeMap<String, SourceImage>,
+    body: String,
+    state: GraphicsState,
+    saved: Vec<GraphicsState>,
+    path: String,
+    clip_pending: bool,
+    clip_count: usize,
+    line_start: Coord,
+    text_position: Coord,
+}
+
+/// Renders the operators of one page as a standalone SVG document. Coordinates stay in
+/// PDF points, with the y axis flipped once for the whole page.
+pub(crate) fn render_page(
+    ops: &[Op],
+    page_size: Coord,
+    images: &BTreeMap<String, SourceImage>,
+) -> String {
+    let mut renderer = SvgRenderer {
+        images,
+        body: String::new(),
+        state: GraphicsState::default(),
+        saved: vec![],
+        path: String::new(),
+        clip_pending: false,
+        clip_count: 0,
+        line_start: (0., 0.),
+        text_position: (0., 0.),
+    };
+
+    for op in ops {
+        renderer.apply(op);
+    }
+    while !renderer.saved.is_empty() {
+        renderer.restore();
+    }
+    renderer.close_groups();
+
+    let (width, height) = page_size;
+    let (width_mm, height_mm) = page_size.to_mm();
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}mm" height="{}mm" viewBox="0 0 {} {}">"#,
+            r##"<rect width="{}" height="{}" fill="#ffffff"/>"##,
+            r#"<g transform="matrix(1 0 0 -1 0 {})">{}</g></svg>"#
+        ),
+        width_mm,
+        height_mm,
+        num(width),
+        num(height),
+        num(width),
+        num(height),
+        num(height),
+        renderer.body
+    )
+}
+
+impl SvgRenderer<'_> {
+    fn apply(&mut self, op: &Op) {
+        match op {
+            Op::q => {
+                let mut state = self.state.clone();
+                state.groups = 0;
+                self.saved.push(std::mem::replace(&mut self.state, state));
+            }
+            Op::Q => self.restore(),
+            Op::cm([a, b, c, d, e, f]) => {
+                let matrix = [a, b, c, d, e, f].map(|value| num(*value)).join(" ");
+                let _ = write!(self.body, r#"<g transform="matrix({matrix})">"#);
+                self.state.groups += 1;
+            }
+            Op::RG(r, g, b) => self.state.stroke = (*r, *g, *b),
+            Op::rg(r, g, b) => self.state.fill = (*r, *g, *b),
+            Op::w(width) => self.state.line_width = *width,
+            Op::d { pattern, phase } => {
+                self.state.dash = (!pattern.is_empty()).then(|| (pattern.clone(), *phase));
+            }
+            Op::J(cap) => self.state.cap = *cap,
+            Op::j(join) => self.state.join = *join,
+
+            Op::m((x, y)) => {
+                let _ = write!(self.path, "M{} {}", num(*x), num(*y));
+            }
+            Op::l((x, y)) => {
+                let _ = write!(self.path, "L{} {}", num(*x), num(*y));
+            }
+            Op::c {
+                ctrl1: (x1, y1),
+                ctrl2: (x2, y2),
+                end: (x3, y3),
+            } => {
+                let points = [x1, y1, x2, y2, x3, y3].map(|value| num(*value)).join(" ");
+                let _ = write!(self.path, "C{points}");
+            }
+            Op::re {
+                point: (x, y),
+                width,
+                height,
+            } => {
+                let _ = write!(
+                    self.path,
+                    "M{} {}H{}V{}H{}Z",
+                    num(*x),
+                    num(*y),
+                    num(x + width),
+                    num(y + height),
+                    num(*x)
+                );
+            }
+            Op::h => self.path.push('Z'),
+            Op::S => self.paint(false),
+            Op::f => self.paint(true),
+            Op::W => self.clip_pending = true,
+            Op::n => self.end_path(),
+            Op::Do(name) => self.image(name),
+
+            Op::BT => {
+                self.line_start = (0., 0.);
+                self.text_position = (0., 0.);
+            }
+            Op::ET => {}
+            Op::Tf { font, size } => {
+                let style = FONT_STYLES
+                    .into_iter()
+                    .find(|style| style.get_font_name() == font)
+                    .unwrap_or(FontStyle::Normal);
+                self.state.font = Some((style, *size));
+            }
+            Op::TL(leading) => self.state.leading = *leading,
+            Op::Td((x, y)) => self.move_text(*x, *y),
+            Op::TD((x, y)) => {
+                self.state.leading = -y;
+                self.move_text(*x, *y);
+            }
+            Op::TStar => self.move_text(0., -self.state.leading),
+            Op::Tj(text) => self.text(text),
+        }
+    }
+
+    fn restore(&mut self) {
+        self.close_groups();
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+    }
+
+    fn close_groups(&mut self) {
+        for _ in 0..self.state.groups {
+            self.body.push_str("</g>");
+        }
+        self.state.groups = 0;
+    }
+
+    fn paint(&mut self, fill: bool) {
+        if !self.path.is_empty() {
+            let _ = write!(self.body, r#"<path d="{}""#, self.path);
+            if fill {
+                let _ = write!(self.body, r#" fill="{}""#, colour(self.state.fill));
+            } else {
+                self.write_stroke();
+            }
+            self.body.push_str("/>");
+        }
+        self.end_path();
+    }
+
+    fn write_stroke(&mut self) {
+        let state = &self.state;
+        let _ = write!(
+            self.body,
+            r#" fill="none" stroke="{}" stroke-width="{}""#,
+            colour(state.stroke),
+            num(state.line_width)
+        );
+        if let Some((pattern, phase)) = &state.dash {
+            let pattern: Vec<String> = pattern.iter().map(|value| num(*value)).collect();
+            let _ = write!(
+                self.body,
+                r#" stroke-dasharray="{}" stroke-dashoffset="{}""#,
+                pattern.join(" "),
+                num(*phase)
+            );
+        }
+        match state.cap {
+            1 => self.body.push_str(r#" stroke-linecap="round""#),
+            2 => self.body.push_str(r#" stroke-linecap="square""#),
+            _ => {}
+        }
+        match state.join {
+            1 => self.body.push_str(r#" stroke-linejoin="round""#),
+            2 => self.body.push_str(r#" stroke-linejoin="bevel""#),
+            _ => {}
+        }
+    }
+
+    /// Finishes the current path, turning it into a clipping group when `W` preceded it.
+    fn end_path(&mut self) {
+        if std::mem::take(&mut self.clip_pending) && !self.path.is_empty() {
+            self.clip_count += 1;
+            let id = format!("clip{}", self.clip_count);
+            let _ = write!(
+                self.body,
+                r#"<clipPath id="{id}"><path d="{}"/></clipPath><g clip-path="url(#{id})">"#,
+                self.path
+            );
+            self.state.groups += 1;
+        }
+        self.path.clear();
+    }
+
+    /// Images fill the unit square, with the top row of samples at the top.
+    fn image(&mut self, name: &str) {
+        if let Some(image) = self.images.get(name) {
+            let _ = write!(
+                self.body,
+                concat!(
+                    r#"<image width="1" height="1" preserveAspectRatio="none" "#,
+                    r#"transform="matrix(1 0 0 -1 0 1)" href="data:{};base64,{}"/>"#
+                ),
+                image.mime,
+                STANDARD.encode(&image.data)
+            );
+        }
+    }
+
+    fn move_text(&mut self, x: f64, y: f64) {
+        let (line_x, line_y) = self.line_start;
+        self.line_start = (line_x + x, line_y + y);
+        self.text_position = self.line_start;
+    }
+
+    fn text(&mut self, text: &str) {
+        let Some((style, size)) = self.state.font else {
+            return;
+        };
+
+        let (x, y) = self.text_position;
+        let _ = write!(
+            self.body,
+            r#"<text transform="matrix(1 0 0 -1 {} {})" font-family="{FONT_FAMILY}" font-size="{}" fill="{}""#,
+            num(x),
+            num(y),
+            num(size),
+            colour(self.state.fill)
+        );
+        if matches!(style, FontStyle::Bold | FontStyle::BoldItalics) {
+            self.body.push_str(r#" font-weight="bold""#);
+        }
+        if matches!(style, FontStyle::Italics | FontStyle::BoldItalics) {
+            self.body.push_str(r#" font-style="italic""#);
+        }
+        let _ = write!(
+            self.body,
+            r#" xml:space="preserve">{}</text>"#,
+            escape(text)
+        );
+
+        self.text_position = (x + style.text_width(text, size), y);
+    }
+}
+
+fn num(value: f64) -> String {
+    value.trim_fraction().to_string()
+}
+
+fn colour((r, g, b): (f64, f64, f64)) -> String {
+    let channel = |value: f64| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{ContentBuilder, FontStyle, A5};
+
+    use super::render_page;
+
+    #[test]
+    fn text_is_placed_upright_at_the_baseline() {
+        let mut ops = vec![];
+        let mut layer = ContentBuilder::new(A5, &mut ops);
+        layer.start_text_block();
+        layer.set_font(FontStyle::Bold, 10.);
+        layer.print_at("A & B", (10., 20.));
+        layer.print(" C".to_owned());
+        layer.end_text_block();
+
+        let svg = render_page(&ops, A5, &BTreeMap::new());
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="148.5mm""#));
+        assert!(svg.contains(concat!(
+            r#"<text transform="matrix(1 0 0 -1 28.3465 538.5827)" "#,
+            r#"font-family="Helvetica, Arial, sans-serif" font-size="10" "#,
+            r##"fill="#000000" "##,
+            r#"font-weight="bold" xml:space="preserve">A &amp; B</text>"#
+        )));
+
+        // The second string continues after the width of the first
+        let width = FontStyle::Bold.text_width("A & B", 10.);
+        let x = ((28.3465 + width) * 10000.).round() / 10000.;
+        assert!(svg.contains(&format!("matrix(1 0 0 -1 {x} 538.5827)")));
+    }
+
+    #[test]
+    fn groups_close_on_restore() {
+        let mut ops = vec![];
+        let mut layer = ContentBuilder::new(A5, &mut ops);
+        // Left open, as pages leave the state they are drawn in
+        layer.save_graphics_state();
+        layer.save_graphics_state();
+        layer.with_saved_state(|layer| {
+            layer.translate(10., 10.);
+            layer.rectangle((0., 0.), 5., 5.);
+            layer.clip();
+            layer.set_colour_non_stroking(1., 0., 0.);
+            layer.rectangle((0., 0.), 10., 10.);
+            layer.fill();
+        });
+        layer.begin_subpath((0., 0.));
+        layer.line((10., 10.));
+        layer.stroke_path();
+
+        let svg = render_page(&ops, A5, &BTreeMap::new());
+
+        assert_eq!(svg.matches("<g").count(), svg.matches("</g>").count());
+        assert!(svg.contains(r#"<clipPath id="clip1">"#));
+        assert!(svg.contains(r##"fill="#ff0000""##));
+        assert!(svg.contains(concat!(
+            r#"<path d="M0 595.2756L28.3465 566.9291" "#,
+            r##"fill="none" stroke="#000000""##
+        )));
+    }
+}
@@ -0,0 +1,130 @@
+/// An affine transform in the same millimetre, y-down space that `ContentBuilder` uses.
+/// A point maps to `(a * x + c * y + e, b * x + d * y + f)`, as with the PDF `cm` operator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Matrix {
+    pub const IDENTITY: Matrix = Matrix {
+        a: 1.,
+        b: 0.,
+        c: 0.,
+        d: 1.,
+        e: 0.,
+        f: 0.,
+    };
+
+    /// Returns the transform that applies `other` first and then `self`.
+    pub fn concat(&self, other: &Matrix) -> Matrix {
+        Matrix {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    pub fn translate(&self, dx: f64, dy: f64) -> Matrix {
+        self.concat(&Matrix {
+            e: dx,
+            f: dy,
+            ..Matrix::IDENTITY
+        })
+    }
+
+    /// Rotates clockwise on the page by `degrees`, as a compass bearing turns.
+    pub fn rotate(&self, degrees: f64) -> Matrix {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        self.concat(&Matrix {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Matrix::IDENTITY
+        })
+    }
+
+    pub fn scale(&self, sx: f64, sy: f64) -> Matrix {
+        self.concat(&Matrix {
+            a: sx,
+            d: sy,
+            ..Matrix::IDENTITY
+        })
+    }
+
+    pub fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Converts the transform into PDF user space, given the conversion `scale` from mm
+    /// to points and the page `height` in points used to flip the y axis.
+    pub(crate) fn to_pdf(self, scale: f64, height: f64) -> [f64; 6] {
+        [
+            self.a,
+            -self.b,
+            -self.c,
+            self.d,
+            self.c * height + scale * self.e,
+            height - self.d * height - scale * self.f,
+        ]
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Matrix::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matrix;
+
+    fn assert_close((x1, y1): (f64, f64), (x2, y2): (f64, f64)) {
+        assert!((x1 - x2).abs() < 1e-9 && (y1 - y2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn transforms_apply_innermost_first() {
+        let matrix = Matrix::IDENTITY.translate(40., 110.).scale(2., -2.);
+
+        assert_close(matrix.apply((0., 0.)), (40., 110.));
+        assert_close(matrix.apply((10., 5.)), (60., 100.));
+    }
+
+    #[test]
+    fn rotation_is_clockwise() {
+        let matrix = Matrix::IDENTITY.rotate(90.);
+
+        // Pointing right turns to pointing down the page
+        assert_close(matrix.apply((1., 0.)), (0., 1.));
+    }
+
+    #[test]
+    fn pdf_matrix_matches_flipped_points() {
+        let (scale, height) = (72. / 25.4, 595.);
+        let flip = |(x, y): (f64, f64)| (x * scale, height - y * scale);
+
+        let matrix = Matrix::IDENTITY
+            .translate(20., 30.)
+            .rotate(30.)
+            .scale(2., 3.);
+        let [a, b, c, d, e, f] = matrix.to_pdf(scale, height);
+
+        let point = (7., -4.);
+        let (u, v) = flip(point);
+        let device = (a * u + c * v + e, b * u + d * v + f);
+        assert_close(device, flip(matrix.apply(point)));
+    }
+}
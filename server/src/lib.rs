@@ -0,0 +1,273 @@
+use std::error::Error;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::migration::read_plan;
+use common::validation::{has_errors, validate_plan, Diagnostic, Severity};
+use definition::Plan;
+use kneeboard_core::planner::{build_planning, create_planning};
+use kneeboard_core::route::{calc_legs, convert_leg, Leg};
+use kneeboard_core::upper_winds::plan_upper_winds;
+use pdf::PdfDate;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response};
+
+/// Largest request body accepted by default. Plans with embedded images can run to a few
+/// megabytes of base64.
+pub const DEFAULT_MAX_BODY: usize = 8 * 1024 * 1024;
+
+pub struct Config {
+    pub address: SocketAddr,
+    /// Requests with a longer body are refused with 413.
+    pub max_body: usize,
+    /// Allows binding to an address other than loopback, exposing the service to the network.
+    pub allow_remote: bool,
+}
+
+/// A response ready to send, kept separate from the HTTP library so routes can be tested
+/// directly.
+#[derive(Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(status: u16, value: &Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+pub struct Server {
+    http: tiny_http::Server,
+    max_body: usize,
+}
+
+impl Server {
+    pub fn bind(config: &Config) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        if !config.address.ip().is_loopback() && !config.allow_remote {
+            let msg = format!(
+                "{} is not a loopback address, pass --allow-remote to listen on it",
+                config.address
+            );
+            return Err(msg.into());
+        }
+
+        let http = tiny_http::Server::http(config.address)?;
+        let max_body = config.max_body;
+        Ok(Self { http, max_body })
+    }
+
+    /// The bound address, useful when binding to port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Handles requests one at a time until the listener fails.
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            self.handle(request);
+        }
+    }
+
+    fn handle(&self, mut request: Request) {
+        let reply = match read_body(&mut request, self.max_body) {
+            Ok(body) => route(request.method(), request.url(), &body),
+            Err(reply) => reply,
+        };
+
+        let header = Header::from_bytes("Content-Type", reply.content_type)
+            .expect("content type is a valid header");
+        let response = Response::from_data(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header);
+        // The client may have gone away, which leaves nothing to report to
+        let _ = request.respond(response);
+    }
+}
+
+fn read_body(request: &mut Request, max_body: usize) -> Result<Vec<u8>, Reply> {
+    let too_large = || Reply::error(413, &format!("Request body exceeds {max_body} bytes"));
+
+    if request
+        .body_length()
+        .is_some_and(|length| length > max_body)
+    {
+        return Err(too_large());
+    }
+
+    // Chunked bodies have no length up front, so read one byte past the limit to spot them
+    let mut body = vec![];
+    request
+        .as_reader()
+        .take(max_body as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| Reply::error(400, &err.to_string()))?;
+
+    if body.len() > max_body {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// Dispatches a request to its endpoint.
+pub fn route(method: &Method, url: &str, body: &[u8]) -> Reply {
+    let path = url.split('?').next().unwrap_or_default();
+
+    let handler: fn(&[u8]) -> Reply = match path {
+        "/health" => {
+            return Reply {
+                status: 200,
+                content_type: "text/plain",
+                body: b"ok".to_vec(),
+            }
+        }
+        "/render" => render,
+        "/validate" => validate,
+        "/legs" => legs,
+        _ => return Reply::error(404, &format!("No endpoint at {path}")),
+    };
+
+    if *method != Method::Post {
+        return Reply::error(405, &format!("{path} expects a POST of plan JSON"));
+    }
+    handler(body)
+}
+
+/// Reads a plan of any format version.
+fn parse_plan(body: &[u8]) -> Result<Plan, Reply> {
+    read_plan(body).map_err(|err| Reply::error(400, &err.to_err_string()))
+}
+
+fn render(body: &[u8]) -> Reply {
+    let plan = match parse_plan(body) {
+        Ok(plan) => plan,
+        Err(reply) => return reply,
+    };
+
+    let mut doc = create_planning(&plan);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    doc.info_mut().creation_date = Some(PdfDate::from_unix_seconds(now));
+
+    let mut data = vec![];
+    if let Err(err) = doc.write(&mut data) {
+        return Reply::error(500, &err.to_string());
+    }
+
+    Reply {
+        status: 200,
+        content_type: "application/pdf",
+        body: data,
+    }
+}
+
+fn validate(body: &[u8]) -> Reply {
+    let plan = match read_plan(body) {
+        Ok(plan) => plan,
+        Err(err) => {
+            let diagnostic = Diagnostic {
+                severity: Severity::Error,
+                path: String::new(),
+                message: err.to_err_string(),
+            };
+            let result = json!({ "valid": false, "diagnostics": [diagnostic] });
+            return Reply::json(200, &result);
+        }
+    };
+
+    let diagnostics = validate_plan(&plan);
+    let result = json!({
+        "valid": !has_errors(&diagnostics),
+        "diagnostics": diagnostics,
+        "pages": build_planning(&plan).page_count(),
+    });
+    Reply::json(200, &result)
+}
+
+fn legs(body: &[u8]) -> Reply {
+    let plan = match parse_plan(body) {
+        Ok(plan) => plan,
+        Err(reply) => return reply,
+    };
+
+    let winds = plan_upper_winds(&plan);
+    let routes: Vec<Value> = plan
+        .routes
+        .iter()
+        .map(|route| {
+            let legs: Vec<Leg> = route.legs.iter().map(convert_leg).collect();
+            let legs: Vec<Value> = calc_legs(&legs, winds.as_ref())
+                .into_iter()
+                .map(|(leg, calc)| {
+                    let (from, to) = &leg.name;
+                    json!({
+                        "from": from,
+                        "to": to,
+                        "course": leg.course.degrees,
+                        "distance": leg.distance,
+                        "speed": leg.speed,
+                        "wind_direction": calc.wind_direction.degrees,
+                        "wind_speed": calc.wind_speed,
+                        "ground_speed": calc.ground_speed,
+                        "heading": calc.heading.degrees,
+                        "heading_magnetic": calc.heading_magnetic.degrees,
+                        "time": calc.time,
+                        "total": calc.total,
+                    })
+                })
+                .collect();
+            json!({ "name": route.name, "legs": legs })
+        })
+        .collect();
+
+    Reply::json(200, &json!({ "routes": routes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tiny_http::Method;
+
+    use super::route;
+
+    #[test]
+    fn unknown_paths_and_methods_are_refused() {
+        assert_eq!(route(&Method::Post, "/missing", b"").status, 404);
+        assert_eq!(route(&Method::Get, "/render", b"").status, 405);
+        assert_eq!(route(&Method::Get, "/health?probe=1", b"").status, 200);
+    }
+
+    #[test]
+    fn invalid_json_is_a_validation_diagnostic() {
+        let reply = route(&Method::Post, "/validate", b"{\"routes\": 3}");
+        let value: serde_json::Value = serde_json::from_slice(&reply.body).unwrap();
+
+        assert_eq!(reply.status, 200);
+        assert_eq!(value["valid"], false);
+        assert_eq!(value["diagnostics"][0]["severity"], "error");
+    }
+
+    #[test]
+    fn plan_problems_are_reported_by_path() {
+        let mut plan = common::create_template_plan();
+        plan.routes[0].legs[0].speed = 0;
+        let body = serde_json::to_vec(&plan).unwrap();
+
+        let reply = route(&Method::Post, "/validate", &body);
+        let value: serde_json::Value = serde_json::from_slice(&reply.body).unwrap();
+
+        assert_eq!(value["valid"], false);
+        assert_eq!(value["diagnostics"][0]["path"], "routes[0].legs[0].speed");
+    }
+}
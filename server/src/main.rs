@@ -0,0 +1,46 @@
+use std::net::SocketAddr;
+use std::process::ExitCode;
+
+use clap::Parser;
+use kneeboard_server::{Config, Server, DEFAULT_MAX_BODY};
+
+#[derive(Parser)]
+#[command(
+    name = "kneeboard-server",
+    version,
+    about = "Render kneeboard plans over HTTP"
+)]
+struct Args {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8088")]
+    address: SocketAddr,
+    /// Largest request body accepted, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_BODY)]
+    max_body: usize,
+    /// Allow listening on an address other than loopback
+    #[arg(long)]
+    allow_remote: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let config = Config {
+        address: args.address,
+        max_body: args.max_body,
+        allow_remote: args.allow_remote,
+    };
+
+    let server = match Server::bind(&config) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("kneeboard-server: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(address) = server.local_addr() {
+        println!("Listening on http://{address}");
+    }
+    server.run();
+    ExitCode::SUCCESS
+}
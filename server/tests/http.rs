@@ -0,0 +1,102 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+
+use kneeboard_server::{Config, Server};
+
+/// Starts a server on a free loopback port for the rest of the test run.
+fn start(max_body: usize) -> SocketAddr {
+    let config = Config {
+        address: "127.0.0.1:0".parse().unwrap(),
+        max_body,
+        allow_remote: false,
+    };
+    let server = Server::bind(&config).unwrap();
+    let address = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    address
+}
+
+/// Sends one request and returns the status code and body.
+fn post(address: SocketAddr, path: &str, body: &[u8]) -> (u16, Vec<u8>) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    let head = format!(
+        "POST {path} HTTP/1.1\r\nHost: {address}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).unwrap();
+    stream.write_all(body).unwrap();
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response).unwrap();
+
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .unwrap();
+    let head = String::from_utf8_lossy(&response[..split]).into_owned();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, response[split + 4..].to_vec())
+}
+
+fn template_plan() -> Vec<u8> {
+    serde_json::to_vec(&common::create_template_plan()).unwrap()
+}
+
+#[test]
+fn render_returns_a_pdf() {
+    let address = start(1024 * 1024);
+
+    let (status, body) = post(address, "/render", &template_plan());
+
+    assert_eq!(status, 200);
+    assert!(body.starts_with(b"%PDF-"));
+}
+
+#[test]
+fn legs_are_computed() {
+    let address = start(1024 * 1024);
+
+    let (status, body) = post(address, "/legs", &template_plan());
+    let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(status, 200);
+    let legs = value["routes"][0]["legs"].as_array().unwrap();
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs[0]["from"], "Place 1");
+    assert!(legs[1]["total"].as_f64().unwrap() > legs[0]["time"].as_f64().unwrap());
+}
+
+#[test]
+fn validate_reports_diagnostics() {
+    let address = start(1024 * 1024);
+
+    let (status, body) = post(address, "/validate", &template_plan());
+    let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(value["valid"], true);
+
+    let (_, body) = post(address, "/validate", b"not json");
+    let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(value["valid"], false);
+}
+
+#[test]
+fn oversized_bodies_are_refused() {
+    let address = start(64);
+
+    let (status, _) = post(address, "/render", &template_plan());
+
+    assert_eq!(status, 413);
+}
+
+#[test]
+fn remote_addresses_need_opting_in() {
+    let config = Config {
+        address: "0.0.0.0:0".parse().unwrap(),
+        max_body: 64,
+        allow_remote: false,
+    };
+
+    assert!(Server::bind(&config).is_err());
+}
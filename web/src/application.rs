@@ -1,21 +1,39 @@
 use crate::common::to_files;
 use crate::detail::{details_html, set_wind_html};
 use crate::diversion::diversion_html;
+use crate::flight_plan::flight_plan_html;
 use crate::hold::hold_html;
+use crate::image::image_html;
 use crate::messages::{AppPage, LoadedFileDetails, PlanChange, PlanMessage, ProfileChange};
 use crate::route::routes_html;
+use crate::weather::{upper_winds_html, weather_html};
 use crate::workspace_storage;
-use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
 use base64::Engine;
+use common::fpl::parse_fpl;
+use common::gpx::{import_gpx, write_gpx};
+use common::icao::parse_icao_route;
+use common::import::Imported;
+use common::kml::write_kml;
+use common::migration::{
+    plan_from_value, profile_from_value, read_plan, read_profile, to_file_value, FileType,
+};
+use common::plog::{write_csv, write_html, write_markdown};
+use common::skydemon::parse_skydemon;
+use common::validation::{validate_plan, Diagnostic};
+use common::waypoints::WaypointDatabase;
 use common::{
-    create_template_diversion, create_template_hold, create_template_leg_with_from,
-    create_template_route, KneeboardError,
+    create_route_from_waypoints, create_template_diversion, create_template_hold,
+    create_template_leg_with_from, create_template_route, KneeboardError,
 };
-use core::planner::create_planning;
+use core::planner::build_planning;
+use core::weather::parse_report;
 use definition::{
-    Diversion, FontType, Hold, Leg, Plan, ProfileConfig, Route, SavedHold, SavedRoute, Velocity,
+    Diversion, FontType, Hold, Leg, Plan, PlanImage, ProfileConfig, Route, SavedHold, SavedRoute,
+    Velocity, WeatherReport,
 };
 use gloo_console::__macro::JsValue;
+use pdf::PdfDate;
 
 use gloo::file::callbacks::read_as_bytes;
 use gloo::file::{callbacks::FileReader, File};
@@ -32,8 +50,17 @@ impl Component for Application {
 
     fn create(_ctx: &Context<Self>) -> Self {
         let profile = workspace_storage::load_profile_from_local_storage().unwrap_or_default();
+        let waypoint_files = workspace_storage::load_waypoint_files();
+        let mut waypoint_database = WaypointDatabase::default();
+        for (file_name, text) in &waypoint_files {
+            if let Ok(database) = WaypointDatabase::load(file_name, text) {
+                waypoint_database.extend(database.entries().to_vec());
+            }
+        }
         let mut app = Application {
             profile,
+            waypoint_database,
+            waypoint_files,
             current_page: AppPage::FlightPlanning,
             ..Default::default()
         };
@@ -51,10 +78,16 @@ impl Component for Application {
             PlanMessage::DataChange(change) => handle_plan_change(self, change),
             PlanMessage::LayoutToggle => self.toggle_layout(),
             PlanMessage::ThemeToggle => toggle_theme(),
+            PlanMessage::ExportReturnToggle => {
+                self.export_return = !self.export_return;
+                self.update_data();
+            }
             PlanMessage::SetMessage(msg) => self.message = Some(msg),
 
             // Navigation
             PlanMessage::NavigateTo(page) => self.current_page = page,
+            PlanMessage::PreviewPage(page) => self.preview_page = page,
+            PlanMessage::SelectFlightPlanRoute(idx) => self.flight_plan_route = idx,
 
             // Initial Route Creation
             PlanMessage::InitialWaypointsInput(value) => self.waypoint_input = value,
@@ -167,7 +200,7 @@ impl Component for Application {
                         <div style="background: var(--bg-panel); border: 2px dashed var(--accent); border-radius: 16px; padding: 32px 56px; text-align: center; box-shadow: 0 8px 32px rgba(0,0,0,0.12);">
                             <div style="font-size: 40px; margin-bottom: 12px; color: var(--accent);">{"↓"}</div>
                             <div style="font-size: 18px; font-weight: 700; color: var(--accent);">{"Drop to load"}</div>
-                            <div style="font-size: 13px; color: var(--text-dim); margin-top: 6px;">{"Plan or Profile JSON"}</div>
+                            <div style="font-size: 13px; color: var(--text-dim); margin-top: 6px;">{"Plan or Profile JSON, GPX, FPL or SkyDemon route, or image"}</div>
                         </div>
                     </div>
                 }
@@ -176,6 +209,60 @@ impl Component for Application {
     }
 }
 
+/// Download links for the computed plog as CSV, Markdown and HTML.
+fn plog_exports_html(app: &Application) -> Html {
+    let exports = [
+        ("csv", "text/csv", "CSV", &app.csv),
+        ("md", "text/markdown", "MD", &app.markdown),
+        ("html", "text/html", "HTML", &app.html),
+    ];
+
+    exports
+        .into_iter()
+        .map(|(extension, media_type, text, data)| {
+            let href = format!("data:{media_type};base64,{}", STANDARD_NO_PAD.encode(data));
+            html!(
+                <a download={format!("kneeboard-plog.{extension}")} title={format!("Save plog as {text}")} href={href}>
+                    <button class="btn">{text}</button>
+                </a>
+            )
+        })
+        .collect()
+}
+
+/// Download links for the routes as GPX and KML, shown once a route has coordinates.
+fn map_exports_html(app: &Application, ctx: &Context<Application>) -> Html {
+    let (Some(gpx), Some(kml)) = (&app.gpx, &app.kml) else {
+        return html!();
+    };
+    let encoded_gpx = format!(
+        "data:application/gpx+xml;base64,{}",
+        STANDARD_NO_PAD.encode(gpx)
+    );
+    let encoded_kml = format!(
+        "data:application/vnd.google-earth.kml+xml;base64,{}",
+        STANDARD_NO_PAD.encode(kml)
+    );
+
+    html!(
+        <>
+            <a download="kneeboard-routes.gpx" title="Save routes as GPX" href={encoded_gpx}>
+                <button class="btn">{"GPX"}</button>
+            </a>
+            <a download="kneeboard-routes.kml" title="Save routes as KML" href={encoded_kml}>
+                <button class="btn">{"KML"}</button>
+            </a>
+            <label class="btn" title="Include return routes in GPX and KML" style="cursor:pointer;">
+                <input
+                    type="checkbox"
+                    checked={app.export_return}
+                    onchange={ctx.link().callback(|_| PlanMessage::ExportReturnToggle)}/>
+                {" Return"}
+            </label>
+        </>
+    )
+}
+
 fn topbar_html(app: &Application, ctx: &Context<Application>) -> Html {
     fn on_click_upload(e: Event) -> PlanMessage {
         upload_files(to_files(e))
@@ -225,6 +312,8 @@ fn topbar_html(app: &Application, ctx: &Context<Application>) -> Html {
                 <a download="kneeboard-notes.json" title="Save notes" href={encoded_json}>
                     <button class="btn">{"Export"}</button>
                 </a>
+                {plog_exports_html(app)}
+                {map_exports_html(app, ctx)}
                 <button class="btn btn-link" onclick={ctx.link().callback(on_click_toggle_layout)} title="Toggle Layout">
                     {layout_text_sidebar(24)}
                 </button>
@@ -243,8 +332,8 @@ fn toggle_theme() {
     let _ = js_sys::eval("window.__toggleTheme()");
 }
 
-fn pdf_preview_html(app: &Application) -> Html {
-    if app.pdf.is_empty() {
+fn pdf_preview_html(app: &Application, ctx: &Context<Application>) -> Html {
+    if app.pdf.is_empty() || app.pages.is_empty() {
         html!(
             <div style="text-align:center;">
                 <div style="font-size:36px;opacity:0.2;margin-bottom:8px;">{"✈"}</div>
@@ -254,10 +343,59 @@ fn pdf_preview_html(app: &Application) -> Html {
         )
     } else {
         let pdf_base64 = STANDARD_NO_PAD.encode(&app.pdf);
-        let embed = format!("data:application/pdf;base64,{pdf_base64}");
+        let encoded_pdf = format!("data:application/pdf;base64,{pdf_base64}");
+
+        // Pages are shown as SVG, as some mobile browsers won't display an embedded PDF
+        let page_urls: Vec<String> = app
+            .pages
+            .iter()
+            .map(|svg| {
+                let svg_base64 = STANDARD_NO_PAD.encode(svg.as_bytes());
+                format!("data:image/svg+xml;base64,{svg_base64}")
+            })
+            .collect();
+
+        let current = app.preview_page;
+        let current_url = page_urls[current].clone();
+        let svg_name = format!("kneeboard-notes-page-{}.svg", current + 1);
+
+        let thumbs = page_urls.into_iter().enumerate().map(|(idx, url)| {
+            let class = if idx == current {
+                "preview-thumb preview-thumb--active"
+            } else {
+                "preview-thumb"
+            };
+            let title = format!("Page {}", idx + 1);
+            html!(
+                <button
+                    class={class}
+                    title={title.clone()}
+                    onclick={ctx.link().callback(move |_| PlanMessage::PreviewPage(idx))}
+                >
+                    <img src={url} alt={title}/>
+                </button>
+            )
+        });
 
         html!(
-            <embed title="kneeboard-notes.pdf" type="application/pdf" width="100%" height="100%" src={embed}/>
+            <div class="preview-pages">
+                <div class="preview-toolbar">
+                    <span>{format!("Page {} of {}", current + 1, app.pages.len())}</span>
+                    <span style="flex:1;"></span>
+                    <a download={svg_name} title="Save this page as SVG" href={current_url.clone()}>
+                        <button class="btn">{"SVG"}</button>
+                    </a>
+                    <a download="kneeboard-notes.pdf" title="Save kneeboard PDF" href={encoded_pdf}>
+                        <button class="btn">{"PDF"}</button>
+                    </a>
+                </div>
+                <div class="preview-page">
+                    <img src={current_url} alt={format!("Page {}", current + 1)}/>
+                </div>
+                <div class="preview-thumbs">
+                    {for thumbs}
+                </div>
+            </div>
         )
     }
 }
@@ -331,7 +469,7 @@ fn flight_planning_page(app: &Application, ctx: &Context<Application>) -> Html {
     } else {
         // Show normal planning view
         let form_html = main_form(app, ctx);
-        let pdf_html = pdf_preview_html(app);
+        let pdf_html = pdf_preview_html(app, ctx);
 
         html!(
             <div class="main">
@@ -351,6 +489,20 @@ fn flight_planning_page(app: &Application, ctx: &Context<Application>) -> Html {
     }
 }
 
+fn waypoint_database_note(app: &Application) -> Html {
+    if app.waypoint_database.is_empty() {
+        return html!();
+    }
+    html!(
+        <p style="margin-bottom:16px; color:var(--text-dim); font-size:12px;">
+            {format!(
+                "Coordinates, courses and distances are filled in from {} waypoints in the database.",
+                app.waypoint_database.len()
+            )}
+        </p>
+    )
+}
+
 fn initial_waypoint_dialog(app: &Application, ctx: &Context<Application>) -> Html {
     let link = ctx.link();
     let sel = app.selected_saved_route;
@@ -379,10 +531,11 @@ fn initial_waypoint_dialog(app: &Application, ctx: &Context<Application>) -> Htm
                     <div style="flex:1; padding:24px;">
                         <div style="font-size:13px; font-weight:700; color:var(--text); margin-bottom:8px;">{"Create a Route"}</div>
                         <p style="margin-bottom:16px; color:var(--text-dim); font-size:13px; line-height:1.6;">
-                            {"Enter waypoints as a comma-separated list. Legs will be generated between consecutive waypoints using your workspace defaults."}
+                            {"Enter waypoints as a comma-separated list, or paste an ICAO route such as EGTK N0100F025 DCT DTY DCT EGBJ. Legs will be generated between consecutive waypoints using your workspace defaults."}
                         </p>
+                        {waypoint_database_note(app)}
                         <div class="fg">
-                            <label>{"Waypoints (comma-separated)"}</label>
+                            <label>{"Waypoints (comma-separated) or ICAO route"}</label>
                             <input
                                 type="text"
                                 placeholder="e.g., EGTF, MAXIT, MID, OCK, EGTF"
@@ -478,7 +631,7 @@ fn initial_waypoint_dialog(app: &Application, ctx: &Context<Application>) -> Htm
                     </div>
                 </div>
                 <div style="border-top:1px solid var(--border); padding:12px 16px; background:var(--accent-mist); border-radius:0 0 12px 12px; text-align:center; font-size:13px; color:var(--accent);">
-                    {"You can also drag & drop a plan or profile JSON file anywhere onto the page to load it."}
+                    {"You can also drag & drop a plan or profile JSON file anywhere onto the page to load it, or a CSV or OpenAIP waypoint database to fill in coordinates."}
                 </div>
             </div>
         </div>
@@ -490,17 +643,25 @@ fn main_form(app: &Application, ctx: &Context<Application>) -> Html {
     let set_wind_html = set_wind_html(ctx, app);
     let routes_html = routes_html(ctx, app);
     let deviation_html = diversion_html(ctx, &app.plan.diversions);
-    let holds_html = hold_html(ctx, &app.plan.holds);
+    let holds_html = hold_html(ctx, &app.plan.holds, &app.diagnostics);
+    let images_html = image_html(ctx, app);
     let saved_routes_html = plan_saved_routes_html(app, ctx);
+    let flight_plan_html = flight_plan_html(ctx, app);
+    let weather_html = weather_html(ctx, app);
+    let upper_winds_html = upper_winds_html(ctx, app);
 
     html!(
         <>
             {details_html}
             {set_wind_html}
+            {upper_winds_html}
             {routes_html}
             {saved_routes_html}
             {deviation_html}
             {holds_html}
+            {weather_html}
+            {images_html}
+            {flight_plan_html}
         </>
     )
 }
@@ -565,6 +726,7 @@ fn handle_plan_change(app: &mut Application, change: PlanChange) {
         PlanChange::Field1(v) => app.plan.detail.field1 = v,
         PlanChange::Field2(v) => app.plan.detail.field2 = v,
         PlanChange::Field3(v) => app.plan.detail.field3 = v,
+        PlanChange::Alternates(alternates) => app.plan.alternates = alternates,
 
         PlanChange::LegFrom(idx, value) => app.get_leg(idx).from = value,
         PlanChange::LegTo(idx, value) => app.get_leg(idx).to = value,
@@ -617,12 +779,42 @@ fn handle_plan_change(app: &mut Application, change: PlanChange) {
         }
         PlanChange::HoldDescription(idx, value) => app.get_hold(idx).description = value,
         PlanChange::HoldRightHand(idx, value) => app.get_hold(idx).right_hand = value,
+        PlanChange::HoldRotateToTrack(idx, value) => app.get_hold(idx).rotate_to_track = value,
         PlanChange::HoldInBoundTrack(idx, value) => app.get_hold(idx).in_bound_track = value,
         PlanChange::HoldSpeed(idx, value) => app.get_hold(idx).aircraft_speed = value,
         PlanChange::HoldVariation(idx, value) => app.get_hold(idx).variation = value,
         PlanChange::HoldWindDirection(idx, value) => app.get_hold(idx).wind.angle = value,
         PlanChange::HoldWindSpeed(idx, value) => app.get_hold(idx).wind.speed = value,
 
+        PlanChange::ImageCaption(idx, value) => app.plan.images[idx].caption = value,
+        PlanChange::ImageAfterRoute(idx, value) => app.plan.images[idx].after_route = value,
+        PlanChange::ImageDelete(idx) => {
+            app.plan.images.remove(idx);
+        }
+
+        PlanChange::WeatherAppend => app.plan.weather.push(WeatherReport::default()),
+        PlanChange::WeatherDelete(idx) => {
+            app.plan.weather.remove(idx);
+        }
+        PlanChange::WeatherText(idx, value) => app.plan.weather[idx].text = value,
+        PlanChange::WeatherRunways(idx, value) => app.plan.weather[idx].runways = value,
+        PlanChange::UpperWinds(value) => app.plan.upper_winds = value,
+        PlanChange::WeatherWindToHolds(idx) => {
+            let wind = parse_report(&app.plan.weather[idx].text)
+                .ok()
+                .and_then(|report| report.conditions.wind);
+            if let Some(wind) = wind {
+                if let Some(direction) = wind.direction {
+                    for hold in &mut app.plan.holds {
+                        hold.wind = Velocity {
+                            angle: direction as i64,
+                            speed: wind.speed_knots().round() as i64,
+                        };
+                    }
+                }
+            }
+        }
+
         PlanChange::RouteFillSafe(route_idx, value) => {
             for leg in &mut app.plan.routes[route_idx].legs {
                 leg.safe = value.clone();
@@ -692,15 +884,10 @@ fn handle_plan_change(app: &mut Application, change: PlanChange) {
     app.update_data();
 }
 
-fn with_file_type(value: &impl serde::Serialize, file_type: &str) -> Vec<u8> {
-    let mut v = serde_json::to_value(value).unwrap_or_default();
-    if let Some(obj) = v.as_object_mut() {
-        obj.insert(
-            "file_type".to_string(),
-            serde_json::Value::String(file_type.to_string()),
-        );
-    }
-    serde_json::to_vec_pretty(&v).unwrap_or_default()
+fn with_file_type(value: &impl serde::Serialize, file_type: FileType) -> Vec<u8> {
+    to_file_value(value, file_type)
+        .and_then(|value| Ok(serde_json::to_vec_pretty(&value)?))
+        .unwrap_or_default()
 }
 
 fn upload_files(files: Option<FileList>) -> PlanMessage {
@@ -763,7 +950,7 @@ fn decode_plan(app: &mut Application, details: LoadedFileDetails) -> Result<Plan
     };
 
     if is_json {
-        Ok(serde_json::from_reader(&data[..])?)
+        read_plan(&data)
     } else {
         Err(KneeboardError::String(
             "Unsupported file type (expect .json)".to_owned(),
@@ -794,6 +981,8 @@ pub struct Application {
     pub message: Option<String>,
     pub readers: HashMap<usize, FileReader>,
     pub pdf: Vec<u8>,
+    pub pages: Vec<String>,
+    pub preview_page: usize,
     pub json: Vec<u8>,
     pub layout_vertical: bool,
     pub next_id: usize,
@@ -807,17 +996,53 @@ pub struct Application {
     pub wind_all_dir: i64,
     pub wind_all_spd: i64,
     pub drag_depth: i32,
+    /// Problems with the plan's fields, marked on their inputs.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Routes with coordinates as GPX and KML, none when no route has them.
+    pub gpx: Option<Vec<u8>>,
+    pub kml: Option<Vec<u8>>,
+    /// Whether the GPX and KML exports include return routes.
+    pub export_return: bool,
+    /// Airfields, reporting points and navaids that fill in the coordinates of new routes,
+    /// loaded from `waypoint_files`.
+    pub waypoint_database: WaypointDatabase,
+    pub waypoint_files: Vec<(String, String)>,
+    /// The route the ICAO flight plan is filed for.
+    pub flight_plan_route: usize,
+    /// The computed plog of every route, to send for checking.
+    pub csv: Vec<u8>,
+    pub markdown: Vec<u8>,
+    pub html: Vec<u8>,
 }
 
 impl Application {
     #[allow(unused_must_use)]
     fn update_data(&mut self) {
-        let doc = create_planning(&self.plan);
+        self.diagnostics = validate_plan(&self.plan);
+
+        let builder = build_planning(&self.plan);
+        self.pages = (0..builder.page_count())
+            .filter_map(|page| builder.page_svg(page))
+            .collect();
+        self.preview_page = self.preview_page.min(self.pages.len().saturating_sub(1));
+
+        let mut doc = builder.to_doc();
+        let now = (js_sys::Date::now() / 1000.) as i64;
+        doc.info_mut().creation_date = Some(PdfDate::from_unix_seconds(now));
         let mut pdf_data = vec![];
-        let json_data = with_file_type(&self.plan, "plan");
+        let json_data = with_file_type(&self.plan, FileType::Plan);
         doc.write(&mut pdf_data);
         self.pdf = pdf_data;
         self.json = json_data;
+        self.gpx = write_gpx(&self.plan, self.export_return)
+            .ok()
+            .map(String::into_bytes);
+        self.kml = write_kml(&self.plan, self.export_return)
+            .ok()
+            .map(String::into_bytes);
+        self.csv = write_csv(&self.plan).into_bytes();
+        self.markdown = write_markdown(&self.plan).into_bytes();
+        self.html = write_html(&self.plan).into_bytes();
     }
 
     fn clear_message(&mut self) {
@@ -945,48 +1170,74 @@ impl Application {
 }
 
 fn handle_create_initial_route(app: &mut Application) {
-    // 1. Parse waypoint CSV
-    let waypoints: Vec<String> = app
-        .waypoint_input
-        .split(',')
-        .map(|s| s.trim().to_owned())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    if waypoints.len() < 2 {
-        app.message = Some("Need at least 2 waypoints".to_owned());
+    // 1-2. Parse the waypoints and create legs using workspace defaults, with coordinates
+    // from the waypoint database
+    let input = app.waypoint_input.clone();
+    let Some(route) = new_route(app, &input) else {
         return;
-    }
-
-    // 2. Create legs from consecutive waypoint pairs using workspace defaults
-    let mut legs = Vec::new();
-    for i in 0..waypoints.len() - 1 {
-        legs.push(Leg {
-            from: waypoints[i].clone(),
-            to: waypoints[i + 1].clone(),
-            safe: app.profile.default_leg_values.safe.clone(),
-            planned: app.profile.default_leg_values.planned.clone(),
-            speed: app.profile.default_leg_values.speed,
-            course: app.profile.default_leg_values.course,
-            distance: app.profile.default_leg_values.distance,
-            variation: app.profile.default_leg_values.variation,
-            wind_direction: app.profile.default_leg_values.wind_direction,
-            wind_speed: app.profile.default_leg_values.wind_speed,
-        });
-    }
+    };
 
-    // 3. Create route and update plan
-    app.plan.routes.push(Route {
-        name: String::new(),
-        legs,
-        notes: vec![FontType::Blank],
-    });
+    // 3. Add route to the plan
+    app.plan.routes.push(route);
 
     // 4. Clear input and update
     app.waypoint_input = String::new();
     app.update_data();
 }
 
+/// A route through the comma separated waypoints or ICAO route string in `input`, noting
+/// points missing from a loaded waypoint database.
+fn new_route(app: &mut Application, input: &str) -> Option<Route> {
+    let defaults = &app.profile.default_leg_values;
+    let (route, missing) = if input.contains(',') {
+        let waypoints: Vec<String> = input
+            .split(',')
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if waypoints.len() < 2 {
+            app.message = Some("Need at least 2 waypoints".to_owned());
+            return None;
+        }
+        create_route_from_waypoints(&waypoints, defaults, &app.waypoint_database)
+    } else {
+        match parse_icao_route(input, defaults, &app.waypoint_database) {
+            Ok(parsed) => {
+                if !parsed.unsupported.is_empty() {
+                    app.message = Some(format!(
+                        "Airways are flown direct: {}",
+                        parsed.unsupported.join(", ")
+                    ));
+                }
+                (parsed.route, parsed.missing)
+            }
+            Err(e) => {
+                app.message = Some(e.to_err_string());
+                return None;
+            }
+        }
+    };
+
+    if !app.waypoint_database.is_empty() && !missing.is_empty() {
+        // Close matches are only suggested, as a code one letter out is another place
+        let missing: Vec<String> = missing
+            .iter()
+            .map(|name| match app.waypoint_database.search(name, 1).first() {
+                Some((entry, _)) => {
+                    let suggestion = entry.ident.as_deref().unwrap_or(&entry.name);
+                    format!("{name} ({suggestion}?)")
+                }
+                None => name.clone(),
+            })
+            .collect();
+        app.message = Some(format!(
+            "Not in the waypoint database, so without coordinates: {}",
+            missing.join(", ")
+        ));
+    }
+    Some(route)
+}
+
 fn route_save_name(route: &Route, route_idx: usize) -> String {
     if !route.name.is_empty() {
         return route.name.clone();
@@ -1038,45 +1289,15 @@ fn handle_create_inserted_route(app: &mut Application) {
         None => return,
     };
 
-    // 1. Parse waypoint CSV
-    let waypoints: Vec<String> = app
-        .insert_waypoints
-        .split(',')
-        .map(|s| s.trim().to_owned())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    if waypoints.len() < 2 {
-        app.message = Some("Need at least 2 waypoints".to_owned());
+    // 1-2. Parse the waypoints and create legs using workspace defaults, with coordinates
+    // from the waypoint database
+    let input = app.insert_waypoints.clone();
+    let Some(route) = new_route(app, &input) else {
         return;
-    }
-
-    // 2. Create legs from consecutive waypoint pairs using workspace defaults
-    let mut legs = Vec::new();
-    for i in 0..waypoints.len() - 1 {
-        legs.push(Leg {
-            from: waypoints[i].clone(),
-            to: waypoints[i + 1].clone(),
-            safe: app.profile.default_leg_values.safe.clone(),
-            planned: app.profile.default_leg_values.planned.clone(),
-            speed: app.profile.default_leg_values.speed,
-            course: app.profile.default_leg_values.course,
-            distance: app.profile.default_leg_values.distance,
-            variation: app.profile.default_leg_values.variation,
-            wind_direction: app.profile.default_leg_values.wind_direction,
-            wind_speed: app.profile.default_leg_values.wind_speed,
-        });
-    }
+    };
 
     // 3. Insert route at the specified position
-    app.plan.routes.insert(
-        insert_idx,
-        Route {
-            name: String::new(),
-            legs,
-            notes: vec![FontType::Blank],
-        },
-    );
+    app.plan.routes.insert(insert_idx, route);
 
     // 4. Clear insertion state and update
     app.inserting_route_at = None;
@@ -1145,6 +1366,12 @@ fn handle_profile_change(app: &mut Application, change: ProfileChange) {
         ProfileChange::DefaultPlanned(val) => {
             app.profile.default_leg_values.planned = val;
         }
+        ProfileChange::AircraftType(val) => app.profile.aircraft.aircraft_type = val,
+        ProfileChange::WakeTurbulence(val) => app.profile.aircraft.wake_turbulence = val,
+        ProfileChange::Equipment(val) => app.profile.aircraft.equipment = val,
+        ProfileChange::FlightRules(val) => app.profile.aircraft.flight_rules = val,
+        ProfileChange::FlightType(val) => app.profile.aircraft.flight_type = val,
+        ProfileChange::OtherInformation(val) => app.profile.aircraft.other_information = val,
         ProfileChange::SavedRouteAdd => {
             app.profile.saved_routes.push(SavedRoute::default());
         }
@@ -1194,6 +1421,7 @@ fn handle_profile_change(app: &mut Application, change: ProfileChange) {
                         angle: saved.wind_angle,
                         speed: saved.wind_speed,
                     },
+                    rotate_to_track: false,
                 });
                 app.current_page = AppPage::FlightPlanning;
                 app.update_data();
@@ -1294,7 +1522,7 @@ fn decode_profile(
     let is_json = file_name.ends_with(".json") || file_name.ends_with(".jsn");
 
     if is_json {
-        Ok(serde_json::from_reader(&data[..])?)
+        read_profile(&data)
     } else {
         Err(KneeboardError::String(
             "Profile files must be JSON format".to_owned(),
@@ -1316,6 +1544,37 @@ fn submit_dropped_load(app: &mut Application, file: File, link: Scope<Applicatio
     app.readers.insert(id, task);
 }
 
+/// Appends the routes from another planner, listing anything in the file that was left out.
+fn add_imported_routes(app: &mut Application, imported: &Imported) {
+    let routes = imported.to_routes(&app.profile.default_leg_values);
+    app.plan.routes.extend(routes);
+    app.update_data();
+
+    if !imported.unsupported.is_empty() {
+        app.message = Some(format!(
+            "Imported the route without: {}",
+            imported.unsupported.join(", ")
+        ));
+    }
+}
+
+/// Adds a CSV or OpenAIP file to the waypoint database, keeping it for the session.
+fn add_waypoint_file(app: &mut Application, file_name: String, text: String) {
+    match WaypointDatabase::load(&file_name, &text) {
+        Ok(database) => {
+            app.waypoint_database.extend(database.entries().to_vec());
+            app.message = Some(format!(
+                "Loaded {} waypoints from {file_name}, {} in total",
+                database.len(),
+                app.waypoint_database.len()
+            ));
+            app.waypoint_files.push((file_name, text));
+            workspace_storage::save_waypoint_files(&app.waypoint_files);
+        }
+        Err(e) => app.message = Some(e.to_err_string()),
+    }
+}
+
 fn handle_dropped_file_loaded(app: &mut Application, details: LoadedFileDetails) {
     let LoadedFileDetails {
         id,
@@ -1333,8 +1592,64 @@ fn handle_dropped_file_loaded(app: &mut Application, details: LoadedFileDetails)
     };
 
     let file_name_lower = file_name.to_lowercase();
+    if [".png", ".jpg", ".jpeg"]
+        .iter()
+        .any(|ext| file_name_lower.ends_with(ext))
+    {
+        let caption = match file_name.rsplit_once('.') {
+            Some((stem, _)) => stem.to_owned(),
+            None => file_name,
+        };
+        app.plan.images.push(PlanImage {
+            caption,
+            data: STANDARD.encode(&data),
+            after_route: None,
+        });
+        app.update_data();
+        return;
+    }
+
+    if file_name_lower.ends_with(".gpx") {
+        let text = String::from_utf8_lossy(&data);
+        match import_gpx(&text, &app.profile.default_leg_values) {
+            Ok(routes) => {
+                app.plan.routes.extend(routes);
+                app.update_data();
+            }
+            Err(e) => app.message = Some(e.to_err_string()),
+        }
+        return;
+    }
+
+    let flight_plan = if file_name_lower.ends_with(".fpl") {
+        Some(parse_fpl as fn(&str) -> Result<Imported, KneeboardError>)
+    } else if file_name_lower.ends_with(".flightplan") {
+        Some(parse_skydemon as fn(&str) -> Result<Imported, KneeboardError>)
+    } else {
+        None
+    };
+    if let Some(parse) = flight_plan {
+        match parse(&String::from_utf8_lossy(&data)) {
+            Ok(imported) => add_imported_routes(app, &imported),
+            Err(e) => app.message = Some(e.to_err_string()),
+        }
+        return;
+    }
+
+    if [".csv", ".xml", ".aip"]
+        .iter()
+        .any(|ext| file_name_lower.ends_with(ext))
+    {
+        add_waypoint_file(app, file_name, String::from_utf8_lossy(&data).into_owned());
+        return;
+    }
+
     if !file_name_lower.ends_with(".json") && !file_name_lower.ends_with(".jsn") {
-        app.message = Some("Only JSON files can be dropped (.json or .jsn)".to_string());
+        app.message = Some(
+            "Only JSON plans or profiles, GPX, Garmin or SkyDemon routes, waypoint databases \
+             and PNG or JPEG images can be dropped"
+                .to_string(),
+        );
         return;
     }
 
@@ -1346,37 +1661,36 @@ fn handle_dropped_file_loaded(app: &mut Application, details: LoadedFileDetails)
         }
     };
 
-    // Detect file type: check explicit "file_type" field first, then fall back to key presence
-    let explicit_type = value.get("file_type").and_then(|v| v.as_str());
-    let is_plan =
-        explicit_type == Some("plan") || (explicit_type.is_none() && value.get("routes").is_some());
-    let is_profile = explicit_type == Some("profile")
-        || (explicit_type.is_none() && value.get("aircraft_registrations").is_some());
-
-    if is_plan {
-        match serde_json::from_slice::<Plan>(&data) {
+    match FileType::detect(&value) {
+        Some(FileType::Plan) => match plan_from_value(value) {
             Ok(plan) => {
                 app.plan = plan;
                 app.update_data();
             }
             Err(e) => {
-                app.message = Some(format!("Failed to parse plan file: {e}"));
+                app.message = Some(format!("Failed to parse plan file: {}", e.to_err_string()));
             }
-        }
-    } else if is_profile {
-        match serde_json::from_slice::<ProfileConfig>(&data) {
+        },
+        Some(FileType::Profile) => match profile_from_value(value) {
             Ok(profile) => {
                 app.profile = profile;
                 app.selected_saved_route = 0;
                 workspace_storage::save_profile_to_local_storage(&app.profile);
             }
             Err(e) => {
-                app.message = Some(format!("Failed to parse profile file: {e}"));
+                app.message = Some(format!(
+                    "Failed to parse profile file: {}",
+                    e.to_err_string()
+                ));
             }
+        },
+        None if value.is_array() || value.get("items").is_some() => {
+            add_waypoint_file(app, file_name, String::from_utf8_lossy(&data).into_owned());
+        }
+        None => {
+            app.message = Some(
+                "Could not determine file type — expected a plan (has 'routes'), profile (has 'aircraft_registrations') or OpenAIP JSON file.".to_string(),
+            );
         }
-    } else {
-        app.message = Some(
-            "Could not determine file type — expected a plan (has 'routes') or profile (has 'aircraft_registrations') JSON file.".to_string(),
-        );
     }
 }
@@ -1,5 +1,7 @@
 use crate::icons::{chevron_bar_down, chevron_bar_up, x_circle};
 
+use common::validation::{Diagnostic, Severity};
+
 use web_sys::{Event, FileList, HtmlInputElement};
 
 use yew::prelude::*;
@@ -19,6 +21,29 @@ pub fn to_files(e: Event) -> Option<FileList> {
     input.files()
 }
 
+/// Class and tooltip marking the input for the field at `path`, the most severe
+/// diagnostic deciding the class.
+pub fn field_state(diagnostics: &[Diagnostic], path: &str) -> (Classes, Option<String>) {
+    let matching: Vec<&Diagnostic> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.path == path)
+        .collect();
+
+    let class = match matching.iter().map(|diagnostic| diagnostic.severity).max() {
+        Some(Severity::Error) => classes!("field-error"),
+        Some(Severity::Warning) => classes!("field-warning"),
+        None => classes!(),
+    };
+    let title = (!matching.is_empty()).then(|| {
+        matching
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    (class, title)
+}
+
 pub fn append_insert_delete(
     append: Callback<MouseEvent>,
     insert: Callback<MouseEvent>,
@@ -0,0 +1,119 @@
+use crate::application::Application;
+use crate::common::to_string;
+use crate::messages::{PlanChange, PlanMessage};
+
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use common::icao::{create_icao_flight_plan, flight_plan_pdf};
+use core::planner::route_name;
+
+use web_sys::Event;
+
+use yew::prelude::*;
+
+/// The ICAO flight plan of the selected route, as a message to copy and as downloads, with
+/// the fields still to fill in.
+pub fn flight_plan_html(ctx: &Context<Application>, app: &Application) -> Html {
+    if app.plan.routes.is_empty() {
+        return html!();
+    }
+
+    let link = ctx.link();
+    let selected = app.flight_plan_route.min(app.plan.routes.len() - 1);
+    let alternates = app.plan.alternates.join(" ");
+
+    let body = match create_icao_flight_plan(&app.plan, selected, &app.profile) {
+        Ok((flight_plan, diagnostics)) => {
+            let message = flight_plan.to_message();
+            let encoded_text = format!(
+                "data:text/plain;base64,{}",
+                STANDARD_NO_PAD.encode(&message)
+            );
+
+            let title = format!(
+                "ICAO flight plan: {}",
+                route_name(&app.plan.routes[selected], selected)
+            );
+            let mut pdf = vec![];
+            let written = flight_plan_pdf(&title, &flight_plan, &diagnostics).write(&mut pdf);
+            let encoded_pdf = written.ok().map(|_| {
+                format!(
+                    "data:application/pdf;base64,{}",
+                    STANDARD_NO_PAD.encode(&pdf)
+                )
+            });
+
+            html!(
+                <>
+                    <pre style="font-size:12px; white-space:pre-wrap; margin:8px 0;">{message}</pre>
+                    if !diagnostics.is_empty() {
+                        <ul style="font-size:12px; color:var(--text-dim); margin:8px 0;">
+                            {diagnostics.iter().map(|diagnostic| html!(
+                                <li>{diagnostic.to_string()}</li>
+                            )).collect::<Html>()}
+                        </ul>
+                    }
+                    <div style="display:flex; gap:8px;">
+                        <a download="flight-plan.txt" title="Save flight plan message" href={encoded_text}>
+                            <button class="btn">{"Text"}</button>
+                        </a>
+                        if let Some(encoded_pdf) = encoded_pdf {
+                            <a download="flight-plan.pdf" title="Save flight plan page" href={encoded_pdf}>
+                                <button class="btn">{"PDF"}</button>
+                            </a>
+                        }
+                    </div>
+                </>
+            )
+        }
+        Err(e) => html!(
+            <div style="font-size:12px; color:var(--text-dim);">{e.to_err_string()}</div>
+        ),
+    };
+
+    html!(
+        <div class="panel">
+            <div class="panel-head">
+                <div class="panel-title">
+                    <span class="marker"></span>
+                    {"ICAO Flight Plan"}
+                </div>
+            </div>
+            <div class="panel-body">
+                <div style="display:grid; grid-template-columns:1fr 1fr; gap:16px;">
+                    <div class="fg">
+                        <label>{"Route"}</label>
+                        <select
+                            onchange={link.callback(|e: Event| {
+                                PlanMessage::SelectFlightPlanRoute(to_string(e).parse().unwrap_or(0))
+                            })}
+                        >
+                            {app.plan.routes.iter().enumerate().map(|(idx, route)| html!(
+                                <option key={idx} value={idx.to_string()} selected={idx == selected}>
+                                    {route_name(route, idx)}
+                                </option>
+                            )).collect::<Html>()}
+                        </select>
+                    </div>
+                    <div class="fg">
+                        <label>{"Alternates"}</label>
+                        <input
+                            type="text"
+                            placeholder="e.g., EGKA EGKK"
+                            value={alternates}
+                            onchange={link.callback(|e: Event| {
+                                let alternates = to_string(e)
+                                    .split(|c: char| c == ',' || c.is_whitespace())
+                                    .filter(|alternate| !alternate.is_empty())
+                                    .map(str::to_owned)
+                                    .collect();
+                                PlanMessage::DataChange(PlanChange::Alternates(alternates))
+                            })}
+                        />
+                    </div>
+                </div>
+                {body}
+            </div>
+        </div>
+    )
+}
@@ -1,18 +1,19 @@
 use crate::application::Application;
-use crate::common::to_number;
+use crate::common::{field_state, to_number};
 use crate::messages::{PlanChange, PlanMessage};
 
+use common::validation::Diagnostic;
 use definition::Hold;
 
 use web_sys::Event;
 
 use yew::prelude::*;
 
-pub fn hold_html(ctx: &Context<Application>, holds: &[Hold]) -> Html {
+pub fn hold_html(ctx: &Context<Application>, holds: &[Hold], diagnostics: &[Diagnostic]) -> Html {
     let holds_html: Html = holds
         .iter()
         .enumerate()
-        .map(|(idx, h)| hold_row(ctx, h, idx))
+        .map(|(idx, h)| hold_row(ctx, h, idx, diagnostics))
         .collect();
 
     let append = ctx
@@ -33,6 +34,7 @@ pub fn hold_html(ctx: &Context<Application>, holds: &[Hold]) -> Html {
                         <tr>
                             <th>{"Description"}</th>
                             <th class="ra">{"RH"}</th>
+                            <th class="ra" title="Rotate the diagram to the inbound track">{"ROT"}</th>
                             <th class="ra">{"IBT"}</th>
                             <th class="ra">{"TAS"}</th>
                             <th class="ra">{"VAR"}</th>
@@ -51,7 +53,12 @@ pub fn hold_html(ctx: &Context<Application>, holds: &[Hold]) -> Html {
     )
 }
 
-fn hold_row(ctx: &Context<Application>, hold: &Hold, idx: usize) -> Html {
+fn hold_row(
+    ctx: &Context<Application>,
+    hold: &Hold,
+    idx: usize,
+    diagnostics: &[Diagnostic],
+) -> Html {
     let link = ctx.link();
 
     let desc_cb = link.callback(move |e: Event| {
@@ -64,12 +71,24 @@ fn hold_row(ctx: &Context<Application>, hold: &Hold, idx: usize) -> Html {
         PlanMessage::DataChange(PlanChange::HoldRightHand(idx, input.checked()))
     });
 
+    let rotate_cb = link.callback(move |e: Event| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        PlanMessage::DataChange(PlanChange::HoldRotateToTrack(idx, input.checked()))
+    });
+
     let ibt_cb = link.callback(move |e| on_num(e, idx, PlanChange::HoldInBoundTrack));
     let speed_cb = link.callback(move |e| on_num(e, idx, PlanChange::HoldSpeed));
     let var_cb = link.callback(move |e| on_num(e, idx, PlanChange::HoldVariation));
     let wdir_cb = link.callback(move |e| on_num(e, idx, PlanChange::HoldWindDirection));
     let wspd_cb = link.callback(move |e| on_num(e, idx, PlanChange::HoldWindSpeed));
 
+    let field = |name: &str| field_state(diagnostics, &format!("holds[{idx}].{name}"));
+    let (ibt_class, ibt_title) = field("in_bound_track");
+    let (speed_class, speed_title) = field("aircraft_speed");
+    let (var_class, var_title) = field("variation");
+    let (wdir_class, wdir_title) = field("wind.angle");
+    let (wspd_class, wspd_title) = field("wind.speed");
+
     let delete = link.callback(move |_| PlanMessage::DataChange(PlanChange::HoldDelete(idx)));
 
     html!(
@@ -89,11 +108,18 @@ fn hold_row(ctx: &Context<Application>, hold: &Hold, idx: usize) -> Html {
                     onchange={rh_cb}
                 />
             </td>
-            <td><input type="number" class="ra" value={hold.in_bound_track.to_string()} onchange={ibt_cb}/></td>
-            <td><input type="number" class="ra" value={hold.aircraft_speed.to_string()} onchange={speed_cb}/></td>
-            <td><input type="number" class="ra" value={hold.variation.to_string()} onchange={var_cb}/></td>
-            <td><input type="number" class="ra" value={hold.wind.angle.to_string()} onchange={wdir_cb}/></td>
-            <td><input type="number" class="ra" value={hold.wind.speed.to_string()} onchange={wspd_cb}/></td>
+            <td style="text-align:center">
+                <input
+                    type="checkbox"
+                    checked={hold.rotate_to_track}
+                    onchange={rotate_cb}
+                />
+            </td>
+            <td><input type="number" class={classes!("ra", ibt_class)} title={ibt_title} value={hold.in_bound_track.to_string()} onchange={ibt_cb}/></td>
+            <td><input type="number" class={classes!("ra", speed_class)} title={speed_title} value={hold.aircraft_speed.to_string()} onchange={speed_cb}/></td>
+            <td><input type="number" class={classes!("ra", var_class)} title={var_title} value={hold.variation.to_string()} onchange={var_cb}/></td>
+            <td><input type="number" class={classes!("ra", wdir_class)} title={wdir_title} value={hold.wind.angle.to_string()} onchange={wdir_cb}/></td>
+            <td><input type="number" class={classes!("ra", wspd_class)} title={wspd_title} value={hold.wind.speed.to_string()} onchange={wspd_cb}/></td>
             <td>
                 <div class="row-acts" style="opacity:1">
                     <button class="ibtn del" onclick={delete} title="Delete hold">{"×"}</button>
@@ -0,0 +1,106 @@
+use crate::application::Application;
+use crate::messages::{PlanChange, PlanMessage};
+
+use definition::PlanImage;
+
+use web_sys::Event;
+
+use yew::prelude::*;
+
+pub fn image_html(ctx: &Context<Application>, app: &Application) -> Html {
+    let route_names: Vec<String> = app
+        .plan
+        .routes
+        .iter()
+        .enumerate()
+        .map(|(idx, route)| match route.name.trim() {
+            "" => format!("Route {}", idx + 1),
+            name => name.to_owned(),
+        })
+        .collect();
+
+    let images_html: Html = app
+        .plan
+        .images
+        .iter()
+        .enumerate()
+        .map(|(idx, image)| image_row(ctx, image, idx, &route_names))
+        .collect();
+
+    html!(
+        <div class="panel">
+            <div class="panel-head">
+                <div class="panel-title">
+                    <span class="marker"></span>
+                    {"Images"}
+                </div>
+            </div>
+            <div class="panel-body" style="padding:8px 14px;">
+                <table>
+                    <thead>
+                        <tr>
+                            <th>{"Caption"}</th>
+                            <th>{"Page after"}</th>
+                            <th style="width:36px"></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {images_html}
+                    </tbody>
+                </table>
+                <div style="font-size:13px; color:var(--accent); padding-top:8px;">
+                    {"Drag & drop a PNG or JPEG file onto the page to add it to the plan."}
+                </div>
+            </div>
+        </div>
+    )
+}
+
+fn image_row(
+    ctx: &Context<Application>,
+    image: &PlanImage,
+    idx: usize,
+    route_names: &[String],
+) -> Html {
+    let link = ctx.link();
+
+    let caption_cb = link.callback(move |e: Event| {
+        let value = crate::common::to_string(e);
+        PlanMessage::DataChange(PlanChange::ImageCaption(idx, value))
+    });
+
+    let after_cb = link.callback(move |e: Event| {
+        let select: web_sys::HtmlInputElement = e.target_unchecked_into();
+        let after_route = select.value().parse::<usize>().ok();
+        PlanMessage::DataChange(PlanChange::ImageAfterRoute(idx, after_route))
+    });
+
+    let delete = link.callback(move |_| PlanMessage::DataChange(PlanChange::ImageDelete(idx)));
+
+    html!(
+        <tr>
+            <td>
+                <input
+                    type="text"
+                    value={image.caption.clone()}
+                    onchange={caption_cb}
+                    style="width:160px"
+                />
+            </td>
+            <td>
+                <select class="fg-bare" onchange={after_cb}>
+                    <option value="" selected={image.after_route.is_none()}>{"End of plan"}</option>
+                    {route_names.iter().enumerate().map(|(route_idx, name)| {
+                        let selected = image.after_route == Some(route_idx);
+                        html!(<option value={route_idx.to_string()} {selected}>{name}</option>)
+                    }).collect::<Html>()}
+                </select>
+            </td>
+            <td>
+                <div class="row-acts" style="opacity:1">
+                    <button class="ibtn del" onclick={delete} title="Delete image">{"×"}</button>
+                </div>
+            </td>
+        </tr>
+    )
+}
@@ -1,22 +1,31 @@
 use crate::application::Application;
-use crate::common::{to_number, to_string, IsLast};
+use crate::common::{field_state, to_number, to_string, IsLast};
 
 use crate::messages::{PlanChange, PlanMessage};
 
+use common::validation::Diagnostic;
 use definition::Leg;
 
 use web_sys::Event;
 
 use yew::prelude::*;
 
-pub fn legs_html(ctx: &Context<Application>, route_idx: usize, legs: &[Leg]) -> Html {
+pub fn legs_html(
+    ctx: &Context<Application>,
+    route_idx: usize,
+    legs: &[Leg],
+    diagnostics: &[Diagnostic],
+) -> Html {
     let rows = if legs.is_empty() {
         html!()
     } else {
         let rows: Html = legs
             .iter()
             .enumerate()
-            .map(|(leg_idx, leg)| leg_html(ctx, (route_idx, leg_idx), legs.is_last(leg_idx), leg))
+            .map(|(leg_idx, leg)| {
+                let is_last = legs.is_last(leg_idx);
+                leg_html(ctx, (route_idx, leg_idx), is_last, leg, diagnostics)
+            })
             .collect();
         rows
     };
@@ -108,7 +117,13 @@ fn fill_row_html(ctx: &Context<Application>, route_idx: usize) -> Html {
     )
 }
 
-fn leg_html(ctx: &Context<Application>, idx: (usize, usize), _is_last: bool, leg: &Leg) -> Html {
+fn leg_html(
+    ctx: &Context<Application>,
+    idx: (usize, usize),
+    _is_last: bool,
+    leg: &Leg,
+    diagnostics: &[Diagnostic],
+) -> Html {
     let from = leg.from.clone();
     let to = leg.to.clone();
     let safe = leg.safe.clone();
@@ -134,21 +149,31 @@ fn leg_html(ctx: &Context<Application>, idx: (usize, usize), _is_last: bool, leg
         link.callback(move |e| on_change_num(idx, e, PlanChange::LegWindDirection));
     let wind_speed_fn = link.callback(move |e| on_change_num(idx, e, PlanChange::LegWindSpeed));
 
+    let path = format!("routes[{}].legs[{}]", idx.0, idx.1);
+    let field = |name: &str| field_state(diagnostics, &format!("{path}.{name}"));
+    let (from_class, from_title) = field("from");
+    let (speed_class, speed_title) = field("speed");
+    let (course_class, course_title) = field("course");
+    let (distance_class, distance_title) = field("distance");
+    let (variation_class, variation_title) = field("variation");
+    let (wind_direction_class, wind_direction_title) = field("wind_direction");
+    let (wind_speed_class, wind_speed_title) = field("wind_speed");
+
     let delete_leg = link.callback(move |_| on_click_delete_leg(idx));
     let insert_leg = link.callback(move |_| on_click_insert_leg(idx));
 
     html!(
         <tr>
-            <td><input type="text" value={from} onchange={from_fn} /></td>
+            <td><input type="text" class={from_class} title={from_title} value={from} onchange={from_fn} /></td>
             <td><input type="text" value={to} onchange={to_fn} /></td>
             <td><input type="text" value={safe} onchange={safe_fn} /></td>
             <td><input type="text" value={planned} onchange={planned_fn} /></td>
-            <td><input type="number" class="ra" value={speed} onchange={speed_fn} /></td>
-            <td><input type="number" class="ra" value={course} onchange={course_fn} /></td>
-            <td><input type="number" class="ra" value={distance} onchange={distance_fn} /></td>
-            <td><input type="number" class="ra" value={variation} onchange={variation_fn} /></td>
-            <td><input type="number" class="ra" value={wind_direction} onchange={wind_direction_fn} /></td>
-            <td><input type="number" class="ra" value={wind_speed} onchange={wind_speed_fn} /></td>
+            <td><input type="number" class={classes!("ra", speed_class)} title={speed_title} value={speed} onchange={speed_fn} /></td>
+            <td><input type="number" class={classes!("ra", course_class)} title={course_title} value={course} onchange={course_fn} /></td>
+            <td><input type="number" class={classes!("ra", distance_class)} title={distance_title} value={distance} onchange={distance_fn} /></td>
+            <td><input type="number" class={classes!("ra", variation_class)} title={variation_title} value={variation} onchange={variation_fn} /></td>
+            <td><input type="number" class={classes!("ra", wind_direction_class)} title={wind_direction_title} value={wind_direction} onchange={wind_direction_fn} /></td>
+            <td><input type="number" class={classes!("ra", wind_speed_class)} title={wind_speed_title} value={wind_speed} onchange={wind_speed_fn} /></td>
             <td>
                 <div class="row-acts">
                     <button class="ibtn" onclick={insert_leg} title="Insert leg">{"↥"}</button>
@@ -2,11 +2,14 @@ pub mod application;
 pub mod common;
 pub mod detail;
 pub mod diversion;
+pub mod flight_plan;
 pub mod hold;
 pub mod icons;
+pub mod image;
 pub mod leg;
 pub mod messages;
 pub mod note;
 pub mod route;
+pub mod weather;
 pub mod workspace;
 pub mod workspace_storage;
@@ -17,6 +17,7 @@ pub enum PlanMessage {
     SetMessage(String),
     LayoutToggle,
     ThemeToggle,
+    ExportReturnToggle,
 
     // Drag and drop
     DragEnter,
@@ -26,6 +27,8 @@ pub enum PlanMessage {
 
     // Navigation
     NavigateTo(AppPage),
+    PreviewPage(usize),
+    SelectFlightPlanRoute(usize),
 
     // Initial Route Creation
     InitialWaypointsInput(String),
@@ -64,6 +67,7 @@ pub enum PlanChange {
     Field1(Option<String>),
     Field2(Option<String>),
     Field3(Option<String>),
+    Alternates(Vec<String>),
     LegFrom((usize, usize), String),
     LegTo((usize, usize), String),
     LegSafe((usize, usize), String),
@@ -100,12 +104,24 @@ pub enum PlanChange {
     HoldDelete(usize),
     HoldDescription(usize, String),
     HoldRightHand(usize, bool),
+    HoldRotateToTrack(usize, bool),
     HoldInBoundTrack(usize, i64),
     HoldSpeed(usize, i64),
     HoldVariation(usize, i64),
     HoldWindDirection(usize, i64),
     HoldWindSpeed(usize, i64),
 
+    ImageCaption(usize, String),
+    ImageAfterRoute(usize, Option<usize>),
+    ImageDelete(usize),
+
+    WeatherAppend,
+    WeatherDelete(usize),
+    WeatherText(usize, String),
+    WeatherRunways(usize, Vec<String>),
+    WeatherWindToHolds(usize),
+    UpperWinds(String),
+
     SetWindAllDir(i64),
     SetWindAllSpd(i64),
     ApplyWindAll,
@@ -150,6 +166,14 @@ pub enum ProfileChange {
     DefaultSafe(String),
     DefaultPlanned(String),
 
+    // Aircraft details for flight plans
+    AircraftType(String),
+    WakeTurbulence(String),
+    Equipment(String),
+    FlightRules(String),
+    FlightType(String),
+    OtherInformation(String),
+
     // Saved routes
     SavedRouteAdd,
     SavedRouteDelete(usize),
@@ -50,7 +50,7 @@ fn route(ctx: &Context<Application>, app: &Application, route_idx: usize, route:
     let insert_route_below = link.callback(move |_| on_change_insert_route_below(route_idx));
     let save_to_workspace = link.callback(move |_| PlanMessage::SaveRouteToProfile(route_idx));
 
-    let legs_html = legs_html(ctx, route_idx, &route.legs);
+    let legs_html = legs_html(ctx, route_idx, &route.legs, &app.diagnostics);
     let notes_html = notes_html(ctx, route_idx, &route.notes);
     let placeholder = format!("Route {:02}", route_idx + 1);
     let route_meta = leg_name(&route.legs);
@@ -169,7 +169,7 @@ fn route_insertion_dialog(ctx: &Context<Application>, app: &Application) -> Html
             </div>
             <div class="panel-body">
                 <div class="fg">
-                    <label>{"Waypoints (comma-separated)"}</label>
+                    <label>{"Waypoints (comma-separated) or ICAO route"}</label>
                     <input
                         type="text"
                         placeholder="e.g., EGTF, MAXIT, MID, OCK, EGTF"
@@ -0,0 +1,217 @@
+use crate::application::Application;
+use crate::common::{field_state, to_string};
+use crate::messages::{PlanChange, PlanMessage};
+
+use core::upper_winds::{parse_upper_winds, UpperWinds};
+use core::weather::parse_report;
+use definition::WeatherReport;
+
+use web_sys::Event;
+
+use yew::prelude::*;
+
+/// Pasted METARs and TAFs, decoded as they'll be printed, with the surface wind on the
+/// runways given and a button to carry it over to the holds.
+pub fn weather_html(ctx: &Context<Application>, app: &Application) -> Html {
+    let reports_html: Html = app
+        .plan
+        .weather
+        .iter()
+        .enumerate()
+        .map(|(idx, report)| weather_report(ctx, app, report, idx))
+        .collect();
+
+    let append = ctx
+        .link()
+        .callback(move |_| PlanMessage::DataChange(PlanChange::WeatherAppend));
+
+    html!(
+        <div class="panel">
+            <div class="panel-head">
+                <div class="panel-title">
+                    <span class="marker"></span>
+                    {"Weather"}
+                </div>
+            </div>
+            <div class="panel-body" style="padding:8px 14px;">
+                {reports_html}
+                <button class="add-row" onclick={append}>{"+ METAR / TAF"}</button>
+            </div>
+        </div>
+    )
+}
+
+fn weather_report(
+    ctx: &Context<Application>,
+    app: &Application,
+    report: &WeatherReport,
+    idx: usize,
+) -> Html {
+    let link = ctx.link();
+
+    let text_cb = link.callback(move |e: Event| {
+        PlanMessage::DataChange(PlanChange::WeatherText(idx, to_string(e)))
+    });
+    let runways_cb = link.callback(move |e: Event| {
+        let runways = to_string(e)
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|runway| !runway.is_empty())
+            .map(str::to_owned)
+            .collect();
+        PlanMessage::DataChange(PlanChange::WeatherRunways(idx, runways))
+    });
+    let delete = link.callback(move |_| PlanMessage::DataChange(PlanChange::WeatherDelete(idx)));
+    let apply =
+        link.callback(move |_| PlanMessage::DataChange(PlanChange::WeatherWindToHolds(idx)));
+
+    let (text_class, text_title) = field_state(&app.diagnostics, &format!("weather[{idx}].text"));
+
+    let decoded = match parse_report(&report.text) {
+        Ok(decoded) => {
+            let runway_winds = decoded.runway_winds(&report.runways);
+            let steady_wind = decoded
+                .conditions
+                .wind
+                .as_ref()
+                .is_some_and(|wind| wind.direction.is_some());
+            html!(
+                <div style="font-size:12px; margin:8px 0;">
+                    <b>{decoded.title()}</b>
+                    {decoded.decode().into_iter().map(|(heading, lines)| html!(
+                        <>
+                            <div style="margin-top:4px;">{heading}</div>
+                            <ul style="margin:2px 0; color:var(--text-dim);">
+                                {lines.into_iter().map(|line| html!(<li>{line}</li>)).collect::<Html>()}
+                            </ul>
+                        </>
+                    )).collect::<Html>()}
+                    if !runway_winds.is_empty() {
+                        <ul style="margin:2px 0;">
+                            {runway_winds.into_iter().map(|line| html!(<li>{line}</li>)).collect::<Html>()}
+                        </ul>
+                    }
+                    if steady_wind && !app.plan.holds.is_empty() {
+                        <button class="btn" onclick={apply} title="Set the wind of every hold to the surface wind">
+                            {"Apply wind to holds"}
+                        </button>
+                    }
+                </div>
+            )
+        }
+        Err(_) if report.text.trim().is_empty() => html!(),
+        Err(e) => html!(
+            <div style="font-size:12px; color:var(--text-dim); margin:8px 0;">{e}</div>
+        ),
+    };
+
+    html!(
+        <div style="padding:8px 0; border-bottom:1px solid var(--border);">
+            <div style="display:grid; grid-template-columns:1fr 140px 36px; gap:8px; align-items:start;">
+                <textarea
+                    class={text_class}
+                    title={text_title}
+                    rows="3"
+                    placeholder="Paste a METAR or TAF, e.g., METAR EGKA 281150Z 24012KT 9999 FEW030 15/09 Q1013"
+                    value={report.text.clone()}
+                    onchange={text_cb}
+                />
+                <input
+                    type="text"
+                    placeholder="Runways, e.g., 02 20"
+                    value={report.runways.join(" ")}
+                    onchange={runways_cb}
+                />
+                <div class="row-acts" style="opacity:1">
+                    <button class="ibtn del" onclick={delete} title="Delete report">{"×"}</button>
+                </div>
+            </div>
+            {decoded}
+        </div>
+    )
+}
+
+/// A pasted upper-wind forecast and the table read from it, whose winds the legs are worked
+/// out with.
+pub fn upper_winds_html(ctx: &Context<Application>, app: &Application) -> Html {
+    let text_cb = ctx
+        .link()
+        .callback(move |e: Event| PlanMessage::DataChange(PlanChange::UpperWinds(to_string(e))));
+    let (text_class, text_title) = field_state(&app.diagnostics, "upper_winds");
+
+    let table = match parse_upper_winds(&app.plan.upper_winds) {
+        Ok(winds) => upper_winds_table(&winds),
+        Err(_) if app.plan.upper_winds.trim().is_empty() => html!(),
+        Err(e) => html!(
+            <div style="font-size:12px; color:var(--text-dim); margin:8px 0;">{e}</div>
+        ),
+    };
+
+    html!(
+        <div class="panel">
+            <div class="panel-head">
+                <div class="panel-title">
+                    <span class="marker"></span>
+                    {"Upper Winds"}
+                </div>
+            </div>
+            <div class="panel-body" style="padding:8px 14px;">
+                <textarea
+                    class={text_class}
+                    title={text_title}
+                    rows="4"
+                    style="width:100%; font-family:monospace;"
+                    placeholder="Paste FB winds aloft, or UK spot winds such as:\n5230N 00130W\n02 250 15 +08\n05 260 20 +03"
+                    value={app.plan.upper_winds.clone()}
+                    onchange={text_cb}
+                />
+                {table}
+                <div style="font-size:13px; color:var(--accent); padding-top:8px;">
+                    {"Legs with a planned altitude inside the forecast take its wind there, from the location named like one of their waypoints, the nearest, or the only one. Other legs keep their own wind."}
+                </div>
+            </div>
+        </div>
+    )
+}
+
+fn upper_winds_table(winds: &UpperWinds) -> Html {
+    let altitudes = winds.altitudes();
+
+    html!(
+        <table style="margin-top:8px;">
+            <thead>
+                <tr>
+                    <th>{"FT"}</th>
+                    {winds.stations.iter().map(|station| html!(
+                        <th class="ra">{station.name.clone()}</th>
+                    )).collect::<Html>()}
+                </tr>
+            </thead>
+            <tbody>
+                {altitudes.iter().map(|&altitude| html!(
+                    <tr>
+                        <td>{altitude.to_string()}</td>
+                        {winds.stations.iter().map(|station| {
+                            let cell = station
+                                .winds
+                                .iter()
+                                .find(|wind| wind.altitude == altitude)
+                                .map(|wind| {
+                                    let direction = match wind.direction {
+                                        Some(direction) => format!("{direction:03}"),
+                                        None => "VRB".to_owned(),
+                                    };
+                                    let temperature = wind
+                                        .temperature
+                                        .map(|temperature| format!(" {temperature:+}"))
+                                        .unwrap_or_default();
+                                    format!("{direction}/{}{temperature}", wind.speed)
+                                })
+                                .unwrap_or_default();
+                            html!(<td class="ra">{cell}</td>)
+                        }).collect::<Html>()}
+                    </tr>
+                )).collect::<Html>()}
+            </tbody>
+        </table>
+    )
+}
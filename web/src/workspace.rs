@@ -3,6 +3,7 @@ use crate::common::{to_files, to_number};
 use crate::messages::{PlanMessage, ProfileChange};
 use base64::engine::general_purpose::STANDARD_NO_PAD;
 use base64::Engine;
+use common::migration::{to_file_value, FileType};
 use web_sys::Event;
 use yew::prelude::*;
 
@@ -16,6 +17,7 @@ pub fn profile_page(app: &Application, ctx: &Context<Application>) -> Html {
             {pics_panel(app, ctx)}
             {call_signs_panel(app, ctx)}
             {default_leg_values_panel(app, ctx)}
+            {aircraft_panel(app, ctx)}
         </div>
     )
 }
@@ -36,16 +38,9 @@ fn file_management_panel(app: &Application, ctx: &Context<Application>) -> Html
         }
     }
 
-    let workspace_json = {
-        let mut v = serde_json::to_value(&app.profile).unwrap_or_default();
-        if let Some(obj) = v.as_object_mut() {
-            obj.insert(
-                "file_type".to_string(),
-                serde_json::Value::String("profile".to_string()),
-            );
-        }
-        serde_json::to_string_pretty(&v).unwrap_or_default()
-    };
+    let workspace_json = to_file_value(&app.profile, FileType::Profile)
+        .and_then(|value| Ok(serde_json::to_string_pretty(&value)?))
+        .unwrap_or_default();
     let workspace_base64 = STANDARD_NO_PAD.encode(workspace_json.as_bytes());
     let encoded_workspace = format!("data:application/json;base64,{workspace_base64}");
 
@@ -387,6 +382,87 @@ fn default_leg_values_panel(app: &Application, ctx: &Context<Application>) -> Ht
     )
 }
 
+/// Label, placeholder, value and the change made when it is edited.
+type AircraftField<'a> = (
+    &'static str,
+    &'static str,
+    &'a String,
+    fn(String) -> ProfileChange,
+);
+
+fn aircraft_panel(app: &Application, ctx: &Context<Application>) -> Html {
+    let link = ctx.link();
+    let aircraft = &app.profile.aircraft;
+
+    let fields: [AircraftField; 6] = [
+        (
+            "Type (ICAO)",
+            "e.g., C172",
+            &aircraft.aircraft_type,
+            ProfileChange::AircraftType,
+        ),
+        (
+            "Wake Turbulence",
+            "L",
+            &aircraft.wake_turbulence,
+            ProfileChange::WakeTurbulence,
+        ),
+        (
+            "Equipment",
+            "e.g., SDFGY/S",
+            &aircraft.equipment,
+            ProfileChange::Equipment,
+        ),
+        (
+            "Flight Rules",
+            "V",
+            &aircraft.flight_rules,
+            ProfileChange::FlightRules,
+        ),
+        (
+            "Type of Flight",
+            "G",
+            &aircraft.flight_type,
+            ProfileChange::FlightType,
+        ),
+        (
+            "Other Information",
+            "e.g., PBN/B2",
+            &aircraft.other_information,
+            ProfileChange::OtherInformation,
+        ),
+    ];
+
+    html!(
+        <div class="panel" style="margin-top:24px;">
+            <div class="panel-head">
+                <div class="panel-title">
+                    <span class="marker"></span>
+                    {"Aircraft for Flight Plans"}
+                </div>
+            </div>
+            <div class="panel-body">
+                <div style="display:grid; grid-template-columns:1fr 1fr; gap:16px;">
+                    {fields.into_iter().map(|(label, placeholder, value, change)| html!(
+                        <div class="fg">
+                            <label>{label}</label>
+                            <input
+                                type="text"
+                                placeholder={placeholder}
+                                value={value.clone()}
+                                oninput={link.callback(move |e: InputEvent| {
+                                    let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                    PlanMessage::ProfileChange(change(input.value()))
+                                })}
+                            />
+                        </div>
+                    )).collect::<Html>()}
+                </div>
+            </div>
+        </div>
+    )
+}
+
 fn saved_holds_panel(app: &Application, ctx: &Context<Application>) -> Html {
     let link = ctx.link();
 
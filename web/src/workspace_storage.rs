@@ -1,7 +1,9 @@
+use common::migration::profile_from_value;
 use definition::ProfileConfig;
 use gloo::storage::{SessionStorage, Storage};
 
 const PROFILE_KEY: &str = "kneeboard_workspace_v1";
+const WAYPOINTS_KEY: &str = "kneeboard_waypoints_v1";
 
 pub fn save_profile_to_local_storage(profile: &ProfileConfig) {
     if let Err(e) = SessionStorage::set(PROFILE_KEY, profile) {
@@ -9,10 +11,23 @@ pub fn save_profile_to_local_storage(profile: &ProfileConfig) {
     }
 }
 
+/// Loads the stored profile, upgrading one saved by an older version of the app.
 pub fn load_profile_from_local_storage() -> Option<ProfileConfig> {
-    SessionStorage::get(PROFILE_KEY).ok()
+    let value: serde_json::Value = SessionStorage::get(PROFILE_KEY).ok()?;
+    profile_from_value(value).ok()
 }
 
 pub fn clear_profile_from_local_storage() {
     SessionStorage::delete(PROFILE_KEY);
 }
+
+/// Keeps the waypoint database files, as name and text, so they survive a reload.
+pub fn save_waypoint_files(files: &[(String, String)]) {
+    if let Err(e) = SessionStorage::set(WAYPOINTS_KEY, files) {
+        gloo_console::error!("Failed to save waypoint files:", e.to_string());
+    }
+}
+
+pub fn load_waypoint_files() -> Vec<(String, String)> {
+    SessionStorage::get(WAYPOINTS_KEY).unwrap_or_default()
+}